@@ -13,7 +13,7 @@ pub use ewebsock;
 pub use filter::Filter;
 pub use keypair::{FilledKeypair, FullKeypair, Keypair, SerializableKeypair};
 pub use nostr::SecretKey;
-pub use note::{Note, NoteId};
+pub use note::{Note, NoteId, NoteVerification};
 pub use profile::Profile;
 pub use pubkey::Pubkey;
 pub use relay::message::{RelayEvent, RelayMessage};
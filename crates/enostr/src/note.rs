@@ -1,5 +1,7 @@
 use crate::{Error, Pubkey};
 
+use nostr::hashes::{sha256, Hash as _};
+use nostr::secp256k1::{schnorr, Message, Secp256k1, XOnlyPublicKey};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -73,6 +75,21 @@ impl PartialEq for Note {
 
 impl Eq for Note {}
 
+/// The result of re-checking a note's id and signature
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct NoteVerification {
+    /// The id is the sha256 of the serialized event
+    pub id_matches: bool,
+    /// The signature is a valid schnorr signature of the id by the pubkey
+    pub sig_valid: bool,
+}
+
+impl NoteVerification {
+    pub fn is_valid(&self) -> bool {
+        self.id_matches && self.sig_valid
+    }
+}
+
 impl Note {
     pub fn from_json(s: &str) -> Result<Self, Error> {
         serde_json::from_str(s).map_err(Into::into)
@@ -82,6 +99,47 @@ impl Note {
         Err(Error::InvalidSignature)
     }
 
+    /// The sha256 of the event serialized as described in NIP-01, which
+    /// is what the id should be
+    pub fn compute_id(&self) -> [u8; 32] {
+        let serialized = serde_json::json!([
+            0,
+            self.pubkey.hex(),
+            self.created_at,
+            self.kind,
+            self.tags,
+            self.content
+        ])
+        .to_string();
+
+        sha256::Hash::hash(serialized.as_bytes()).to_byte_array()
+    }
+
+    /// Check that the id is the hash of the event and that the signature
+    /// over it is valid for the pubkey. The signature is checked against
+    /// the claimed id, so a note can have a valid signature over an id
+    /// that doesn't match its contents.
+    pub fn verify_integrity(&self) -> NoteVerification {
+        let id_matches = &self.compute_id() == self.id.bytes();
+
+        let sig_valid = (|| {
+            let sig = schnorr::Signature::from_slice(&hex::decode(&self.sig).ok()?).ok()?;
+            let pubkey = XOnlyPublicKey::from_slice(self.pubkey.bytes()).ok()?;
+            let message = Message::from_digest(*self.id.bytes());
+            Some(
+                Secp256k1::verification_only()
+                    .verify_schnorr(&sig, &message, &pubkey)
+                    .is_ok(),
+            )
+        })()
+        .unwrap_or(false);
+
+        NoteVerification {
+            id_matches,
+            sig_valid,
+        }
+    }
+
     /// This is just for serde sanity checking
     #[allow(dead_code)]
     pub(crate) fn new_dummy(
@@ -133,3 +191,46 @@ impl<'de> Deserialize<'de> for NoteId {
         NoteId::from_hex(&s).map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostrdb::NoteBuilder;
+
+    fn signed_note() -> Note {
+        let note = NoteBuilder::new()
+            .kind(1)
+            .content("hello")
+            .sign(&[1; 32])
+            .build()
+            .expect("note");
+
+        Note::from_json(&note.json().expect("json")).expect("parse")
+    }
+
+    #[test]
+    fn test_verify_integrity() {
+        let note = signed_note();
+        assert!(note.verify_integrity().is_valid());
+
+        let mut tampered = note.clone();
+        tampered.content = "goodbye".to_string();
+        assert_eq!(
+            tampered.verify_integrity(),
+            NoteVerification {
+                id_matches: false,
+                sig_valid: true,
+            }
+        );
+
+        let mut forged = note;
+        forged.sig = "00".repeat(64);
+        assert_eq!(
+            forged.verify_integrity(),
+            NoteVerification {
+                id_matches: true,
+                sig_valid: false,
+            }
+        );
+    }
+}
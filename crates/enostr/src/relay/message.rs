@@ -8,6 +8,20 @@ pub struct CommandResult<'a> {
     message: &'a str,
 }
 
+impl<'a> CommandResult<'a> {
+    pub fn event_id(&self) -> &'a str {
+        self.event_id
+    }
+
+    pub fn status(&self) -> bool {
+        self.status
+    }
+
+    pub fn message(&self) -> &'a str {
+        self.message
+    }
+}
+
 pub fn calculate_command_result_size(result: &CommandResult) -> usize {
     std::mem::size_of_val(result)
         + result.event_id.as_bytes().len()
@@ -15,6 +15,10 @@ pub struct Args {
     pub use_keystore: bool,
     pub dbpath: Option<String>,
     pub datapath: Option<String>,
+
+    /// Evict the least recently shown images when their textures take
+    /// more than this many bytes
+    pub image_budget: Option<usize>,
 }
 
 impl Args {
@@ -30,6 +34,7 @@ impl Args {
             use_keystore: true,
             dbpath: None,
             datapath: None,
+            image_budget: None,
         };
 
         let mut i = 0;
@@ -99,6 +104,20 @@ impl Args {
                     continue;
                 };
                 res.datapath = Some(path.clone());
+            } else if arg == "--image-budget" {
+                i += 1;
+                let megabytes = if let Some(next_arg) = args.get(i) {
+                    next_arg
+                } else {
+                    error!("image-budget argument missing?");
+                    continue;
+                };
+
+                if let Ok(megabytes) = megabytes.parse::<usize>() {
+                    res.image_budget = Some(megabytes * 1024 * 1024);
+                } else {
+                    error!("image-budget expects a size in megabytes");
+                }
             } else if arg == "-r" || arg == "--relay" {
                 i += 1;
                 let relay = if let Some(next_arg) = args.get(i) {
@@ -1,20 +1,88 @@
-use crate::Result;
+use crate::{Error, Result};
 use egui::TextureHandle;
 use poll_promise::Promise;
 
 use egui::ColorImage;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::sync::{Arc, Mutex};
 
 use std::path;
+use tracing::info;
 
 pub type ImageCacheValue = Promise<Result<TextureHandle>>;
 pub type ImageCacheMap = HashMap<String, ImageCacheValue>;
 
+/// Urls that were served as gifs. Shared with the threads fetching images
+pub type GifUrls = Arc<Mutex<HashSet<String>>>;
+
+/// How long a resolved url is trusted before we resolve it again
+pub const RESOLVED_URL_TTL: f64 = 60.0 * 60.0;
+
+/// Where a shortened url redirects to, or `None` while we're resolving
+/// it or when resolving failed
+#[derive(Debug, Clone)]
+pub struct ResolvedUrl {
+    pub url: Option<String>,
+    /// When we started resolving it, in seconds
+    pub at: f64,
+}
+
+/// Shortened urls and where they go. Shared with the threads resolving them
+pub type ResolvedUrls = Arc<Mutex<HashMap<String, ResolvedUrl>>>;
+
+/// Why an image couldn't be shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFailure {
+    /// We got the data but couldn't decode it. Retrying won't help
+    Unsupported,
+
+    /// The fetch failed, eg. a network error. Worth retrying later
+    LoadFailed,
+}
+
+impl ImageFailure {
+    pub fn from_error(err: &Error) -> Self {
+        match err {
+            Error::Image(
+                image::ImageError::Unsupported(_)
+                | image::ImageError::Decoding(_)
+                | image::ImageError::Limits(_),
+            ) => ImageFailure::Unsupported,
+            _ => ImageFailure::LoadFailed,
+        }
+    }
+}
+
+/// How many times we refetch an image that failed to load
+const MAX_IMAGE_RETRIES: u8 = 4;
+
+/// A failed image and when it's ok to fetch it again
+struct FailedImage {
+    failure: ImageFailure,
+    attempts: u8,
+    retry_at: f64,
+}
+
+/// What [`ImageCache::evict_lru`] dropped
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Eviction {
+    pub evicted: usize,
+    pub freed_bytes: usize,
+}
+
 pub struct ImageCache {
     pub cache_dir: path::PathBuf,
     url_imgs: ImageCacheMap,
+    /// When each image was last shown, as a use counter. See
+    /// [`ImageCache::touch`]
+    last_used: HashMap<String, u64>,
+    uses: u64,
+    gif_urls: GifUrls,
+    resolved_urls: ResolvedUrls,
+    playing: HashSet<String>,
+    failed: HashMap<String, FailedImage>,
 }
 
 impl ImageCache {
@@ -22,6 +90,12 @@ impl ImageCache {
         Self {
             cache_dir,
             url_imgs: HashMap::new(),
+            last_used: HashMap::new(),
+            uses: 0,
+            gif_urls: GifUrls::default(),
+            resolved_urls: ResolvedUrls::default(),
+            playing: HashSet::new(),
+            failed: HashMap::new(),
         }
     }
 
@@ -74,4 +148,248 @@ impl ImageCache {
     pub fn map_mut(&mut self) -> &mut ImageCacheMap {
         &mut self.url_imgs
     }
+
+    /// Mark an image as just shown, so [`ImageCache::evict_lru`] keeps it
+    /// over the ones we haven't shown in a while
+    pub fn touch(&mut self, url: &str) {
+        self.uses += 1;
+        if let Some(last_used) = self.last_used.get_mut(url) {
+            *last_used = self.uses;
+        } else {
+            self.last_used.insert(url.to_owned(), self.uses);
+        }
+    }
+
+    /// Drop the least recently shown textures until the ones left take
+    /// under `target_bytes`, estimating 4 bytes per pixel. Images still loading are kept. Dropped images
+    /// are loaded again, from the disk cache, the next time they're shown.
+    pub fn evict_lru(&mut self, target_bytes: usize) -> Eviction {
+        let loaded: Vec<(String, usize, u64)> = self
+            .url_imgs
+            .iter()
+            .filter_map(|(url, promise)| match promise.ready() {
+                Some(Ok(texture)) => Some((
+                    url.clone(),
+                    texture_bytes(texture),
+                    self.last_used.get(url).copied().unwrap_or(0),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let mut eviction = Eviction::default();
+        for (url, bytes) in lru_victims(loaded, target_bytes) {
+            self.url_imgs.remove(&url);
+            self.last_used.remove(&url);
+            eviction.evicted += 1;
+            eviction.freed_bytes += bytes;
+        }
+
+        if eviction.evicted > 0 {
+            info!(
+                "evicted {} images, freeing {} KiB",
+                eviction.evicted,
+                eviction.freed_bytes / 1024
+            );
+        }
+
+        eviction
+    }
+
+    /// Fetchers record urls served with an `image/gif` content type here,
+    /// since gif links don't always end in `.gif`
+    pub fn gif_urls(&self) -> GifUrls {
+        self.gif_urls.clone()
+    }
+
+    /// Whether a url is a gif, either by its extension or by the content
+    /// type it was served with
+    pub fn is_gif(&self, url: &str) -> bool {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        path.to_lowercase().ends_with(".gif")
+            || self
+                .gif_urls
+                .lock()
+                .map_or(false, |gif_urls| gif_urls.contains(url))
+    }
+
+    /// Resolvers record where shortened urls go here
+    pub fn resolved_urls(&self) -> ResolvedUrls {
+        self.resolved_urls.clone()
+    }
+
+    /// Where a shortened url goes, if we resolved it within the last
+    /// [`RESOLVED_URL_TTL`] seconds. `now` is in seconds.
+    pub fn resolved_url(&self, url: &str, now: f64) -> Option<String> {
+        let resolved_urls = self.resolved_urls.lock().ok()?;
+        let resolved = resolved_urls.get(url)?;
+        if now - resolved.at < RESOLVED_URL_TTL {
+            resolved.url.clone()
+        } else {
+            None
+        }
+    }
+
+    /// Whether a shortened url needs resolving, because we haven't tried
+    /// yet or our last try expired
+    pub fn should_resolve(&self, url: &str, now: f64) -> bool {
+        self.resolved_urls.lock().map_or(false, |resolved_urls| {
+            resolved_urls
+                .get(url)
+                .map_or(true, |resolved| now - resolved.at >= RESOLVED_URL_TTL)
+        })
+    }
+
+    /// Remember we're resolving a url so we don't start again every frame
+    pub fn start_resolving(&mut self, url: &str, now: f64) {
+        if let Ok(mut resolved_urls) = self.resolved_urls.lock() {
+            resolved_urls.insert(url.to_owned(), ResolvedUrl { url: None, at: now });
+        }
+    }
+
+    /// Animate this media instead of showing its first frame
+    pub fn play(&mut self, url: String) {
+        self.playing.insert(url);
+    }
+
+    pub fn is_playing(&self, url: &str) -> bool {
+        self.playing.contains(url)
+    }
+
+    /// Remember that an image failed so we don't refetch it every frame.
+    /// Load failures back off exponentially, `now` is in seconds.
+    pub fn record_failure(&mut self, url: &str, failure: ImageFailure, now: f64) {
+        let failed = self
+            .failed
+            .entry(url.to_owned())
+            .or_insert_with(|| FailedImage {
+                failure,
+                attempts: 0,
+                retry_at: now,
+            });
+
+        failed.failure = failure;
+        failed.attempts = failed.attempts.saturating_add(1);
+        failed.retry_at = now + 2.0_f64.powi(failed.attempts as i32);
+    }
+
+    /// Why an image failed, if it did
+    pub fn failure(&self, url: &str) -> Option<ImageFailure> {
+        self.failed.get(url).map(|failed| failed.failure)
+    }
+
+    /// Whether we should (re)fetch an image. Unsupported images are never
+    /// refetched, and load failures only a few times.
+    pub fn should_fetch(&self, url: &str, now: f64) -> bool {
+        match self.failed.get(url) {
+            None => true,
+            Some(failed) => {
+                failed.failure == ImageFailure::LoadFailed
+                    && failed.attempts <= MAX_IMAGE_RETRIES
+                    && now >= failed.retry_at
+            }
+        }
+    }
+
+    /// Forget a failure, eg. once the image finally loaded
+    pub fn clear_failure(&mut self, url: &str) {
+        self.failed.remove(url);
+    }
+}
+
+fn texture_bytes(texture: &TextureHandle) -> usize {
+    let [width, height] = texture.size();
+    width * height * 4
+}
+
+/// The least recently used of `loaded` (url, bytes, last use) to drop so
+/// the rest fit in `target_bytes`, along with their size
+fn lru_victims(mut loaded: Vec<(String, usize, u64)>, target_bytes: usize) -> Vec<(String, usize)> {
+    let mut total: usize = loaded.iter().map(|(_, bytes, _)| bytes).sum();
+    loaded.sort_by_key(|(_, _, last_used)| *last_used);
+
+    let mut victims = vec![];
+    for (url, bytes, _) in loaded {
+        if total <= target_bytes {
+            break;
+        }
+        total -= bytes;
+        victims.push((url, bytes));
+    }
+    victims
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolved_url_expires() {
+        let mut cache = ImageCache::new(path::PathBuf::new());
+        let short = "https://t.co/abc";
+        assert!(cache.should_resolve(short, 0.0));
+
+        cache.start_resolving(short, 0.0);
+        assert!(!cache.should_resolve(short, 1.0));
+        assert_eq!(cache.resolved_url(short, 1.0), None);
+
+        cache.resolved_urls().lock().unwrap().insert(
+            short.to_owned(),
+            ResolvedUrl {
+                url: Some("https://example.com/".to_owned()),
+                at: 0.0,
+            },
+        );
+        assert_eq!(
+            cache.resolved_url(short, 1.0).as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(cache.resolved_url(short, RESOLVED_URL_TTL), None);
+        assert!(cache.should_resolve(short, RESOLVED_URL_TTL));
+    }
+
+    #[test]
+    fn test_lru_victims() {
+        let loaded = vec![
+            ("new".to_owned(), 100, 3),
+            ("old".to_owned(), 100, 1),
+            ("mid".to_owned(), 100, 2),
+        ];
+        assert!(lru_victims(loaded.clone(), 300).is_empty());
+        assert_eq!(
+            lru_victims(loaded.clone(), 150),
+            vec![("old".to_owned(), 100), ("mid".to_owned(), 100)]
+        );
+        assert_eq!(lru_victims(loaded, 0).len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_image_is_unsupported() {
+        let err = Error::from(image::load_from_memory(b"definitely not an image").unwrap_err());
+        assert_eq!(ImageFailure::from_error(&err), ImageFailure::Unsupported);
+
+        let err = Error::Generic("connection reset".to_owned());
+        assert_eq!(ImageFailure::from_error(&err), ImageFailure::LoadFailed);
+    }
+
+    #[test]
+    fn test_failure_backoff() {
+        let mut cache = ImageCache::new(path::PathBuf::new());
+        let url = "https://example.com/a.png";
+        assert!(cache.should_fetch(url, 0.0));
+
+        cache.record_failure(url, ImageFailure::LoadFailed, 0.0);
+        assert!(!cache.should_fetch(url, 1.0));
+        assert!(cache.should_fetch(url, 2.0));
+
+        cache.record_failure(url, ImageFailure::LoadFailed, 2.0);
+        assert!(!cache.should_fetch(url, 5.0));
+        assert!(cache.should_fetch(url, 6.0));
+
+        cache.clear_failure(url);
+        assert_eq!(cache.failure(url), None);
+
+        cache.record_failure(url, ImageFailure::Unsupported, 0.0);
+        assert!(!cache.should_fetch(url, 1000.0));
+    }
 }
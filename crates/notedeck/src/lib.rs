@@ -27,7 +27,7 @@ pub use context::AppContext;
 pub use error::{Error, FilterError};
 pub use filter::{FilterState, FilterStates, UnifiedSubscription};
 pub use fonts::NamedFontFamily;
-pub use imgcache::ImageCache;
+pub use imgcache::{Eviction, GifUrls, ImageCache, ImageFailure, ResolvedUrl, ResolvedUrls};
 pub use muted::{MuteFun, Muted};
 pub use note::NoteRef;
 pub use notecache::{CachedNote, NoteCache};
@@ -38,7 +38,7 @@ pub use storage::{
 pub use style::NotedeckTextStyle;
 pub use theme::ColorTheme;
 pub use theme_handler::ThemeHandler;
-pub use time::time_ago_since;
+pub use time::{date_label, time_ago_since, unix_now};
 pub use timecache::TimeCached;
 pub use unknowns::{get_unknown_note_ids, NoteRefsUnkIdAction, SingleUnkIdAction, UnknownIds};
 pub use user_account::UserAccount;
@@ -1,5 +1,5 @@
 use crate::notecache::NoteCache;
-use nostrdb::{Ndb, Note, NoteKey, QueryResult, Transaction};
+use nostrdb::{Filter, Ndb, Note, NoteKey, NoteReply, QueryResult, Subscription, Transaction};
 use std::cmp::Ordering;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -68,3 +68,331 @@ pub fn root_note_id_from_selected_id<'a>(
         .root()
         .map_or_else(|| selected_note_id, |nr| nr.id)
 }
+
+/// Check if the author of a note has deleted it with a kind-5 deletion
+/// event (NIP-09). The result is cached in the [`NoteCache`] until a
+/// deletion referencing the note arrives, see [`NoteCacheRefresher`].
+pub fn is_note_deleted(
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    txn: &Transaction,
+    note_key: NoteKey,
+    note: &Note,
+) -> bool {
+    let cached_note = note_cache.cached_note_or_insert_mut(note_key, note);
+    if let Some(deleted) = cached_note.deleted {
+        return deleted;
+    }
+
+    // only the author can delete their own notes
+    let filter = Filter::new()
+        .kinds([5])
+        .authors([note.pubkey()])
+        .event(note.id())
+        .limit(1)
+        .build();
+
+    let deleted = ndb
+        .query(txn, &[filter], 1)
+        .map_or(false, |results| !results.is_empty());
+
+    cached_note.deleted = Some(deleted);
+    deleted
+}
+
+/// Kinds whose arrival changes what's cached about the notes they
+/// reference: deletions (kind 5), reactions (kind 7) and zap receipts
+/// (kind 9735)
+const INVALIDATING_KINDS: [u64; 3] = [5, 7, 9735];
+
+/// Watches for new events that change what the [`NoteCache`] knows about
+/// the notes they reference, such as a deletion making
+/// [`is_note_deleted`] true or a reaction adding to [`note_engagement`].
+/// Those notes are invalidated so it's looked up again the next time
+/// they're shown.
+#[derive(Default)]
+pub struct NoteCacheRefresher {
+    sub: Option<Subscription>,
+}
+
+impl NoteCacheRefresher {
+    pub fn subscribe(&mut self, ndb: &Ndb) {
+        let filter = Filter::new().kinds(INVALIDATING_KINDS).build();
+        self.sub = ndb.subscribe(&[filter]).ok();
+    }
+
+    /// Invalidate the notes referenced by the events ingested since we
+    /// last looked. Returns how many cached notes were dropped.
+    pub fn poll(&mut self, ndb: &Ndb, txn: &Transaction, note_cache: &mut NoteCache) -> usize {
+        let sub = if let Some(sub) = self.sub {
+            sub
+        } else {
+            return 0;
+        };
+
+        let mut invalidated = 0;
+        for note_key in ndb.poll_for_notes(sub, 500) {
+            let note = if let Ok(note) = ndb.get_note_by_key(txn, note_key) {
+                note
+            } else {
+                continue;
+            };
+
+            for tag in note.tags() {
+                if tag.count() < 2 || tag.get_unchecked(0).variant().str() != Some("e") {
+                    continue;
+                }
+
+                let id = if let Some(id) = tag.get_unchecked(1).variant().id() {
+                    id
+                } else {
+                    continue;
+                };
+
+                if let Ok(referenced) = ndb.get_notekey_by_id(txn, id) {
+                    if note_cache.invalidate(referenced) {
+                        invalidated += 1;
+                    }
+                }
+            }
+        }
+        invalidated
+    }
+}
+
+/// The deepest a reply chain is followed by [`thread_depth`]
+pub const MAX_THREAD_DEPTH: u32 = 255;
+
+/// How many replies deep a note is in its thread, up to
+/// [`MAX_THREAD_DEPTH`]. A thread root is 0. We walk up the reply chain
+/// in nostrdb, and a parent we don't have still counts as a level but
+/// ends the walk. The result is cached in the [`NoteCache`].
+pub fn thread_depth(
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    txn: &Transaction,
+    note_key: NoteKey,
+    note: &Note,
+) -> u32 {
+    let cached_note = note_cache.cached_note_or_insert(note_key, note);
+    if let Some(depth) = cached_note.thread_depth {
+        return depth;
+    }
+
+    let mut parent = cached_note.reply.borrow(note.tags()).reply().map(|r| *r.id);
+    let mut depth = 0;
+
+    while let Some(parent_id) = parent {
+        depth += 1;
+        if depth >= MAX_THREAD_DEPTH {
+            break;
+        }
+
+        parent = ndb
+            .get_note_by_id(txn, &parent_id)
+            .ok()
+            .and_then(|parent| NoteReply::new(parent.tags()).reply().map(|r| *r.id));
+    }
+
+    note_cache
+        .cached_note_or_insert_mut(note_key, note)
+        .thread_depth = Some(depth);
+    depth
+}
+
+/// The number of reactions (kind 7) and zaps (kind 9735) on a note, capped
+/// at `limit`. The count is cached in the [`NoteCache`] with the limit it
+/// was counted to, and only counted again for a higher limit when it was
+/// capped, or once a reaction or zap arrives, see [`NoteCacheRefresher`].
+pub fn note_engagement(
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    txn: &Transaction,
+    note_key: NoteKey,
+    note: &Note,
+    limit: u32,
+) -> u32 {
+    let cached_note = note_cache.cached_note_or_insert_mut(note_key, note);
+    if let Some((engagement, counted_to)) = cached_note.engagement {
+        // a count under its limit is every reaction and zap we have
+        if engagement < counted_to || limit <= counted_to {
+            return engagement.min(limit);
+        }
+    }
+
+    let filter = Filter::new()
+        .kinds([7, 9735])
+        .event(note.id())
+        .limit(limit as u64)
+        .build();
+
+    let engagement = ndb
+        .query(txn, &[filter], limit as i32)
+        .map_or(0, |results| results.len() as u32);
+
+    cached_note.engagement = Some((engagement, limit));
+    engagement
+}
+
+fn count_note_refs(ndb: &Ndb, txn: &Transaction, note: &Note, kind: u64) -> u32 {
+    let limit = crate::filter::default_limit();
+    let filter = Filter::new()
+        .kinds([kind])
+        .event(note.id())
+        .limit(limit)
+        .build();
+
+    ndb.query(txn, &[filter], limit as i32)
+        .map_or(0, |results| results.len() as u32)
+}
+
+/// The number of reactions (kind 7) on a note. The count is cached in
+/// the [`NoteCache`] until a reaction arrives, see [`NoteCacheRefresher`].
+pub fn note_reactions(
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    txn: &Transaction,
+    note_key: NoteKey,
+    note: &Note,
+) -> u32 {
+    let cached_note = note_cache.cached_note_or_insert_mut(note_key, note);
+    *cached_note
+        .reactions
+        .get_or_insert_with(|| count_note_refs(ndb, txn, note, 7))
+}
+
+/// The number of zaps (kind 9735) on a note. The count is cached in the
+/// [`NoteCache`] until a zap arrives, see [`NoteCacheRefresher`].
+pub fn note_zaps(
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    txn: &Transaction,
+    note_key: NoteKey,
+    note: &Note,
+) -> u32 {
+    let cached_note = note_cache.cached_note_or_insert_mut(note_key, note);
+    *cached_note
+        .zaps
+        .get_or_insert_with(|| count_note_refs(ndb, txn, note, 9735))
+}
+
+/// The total sats zapped to a note (NIP-57), summed from the bolt11
+/// invoices of its kind 9735 zap receipts. The total is cached in the
+/// [`NoteCache`] with the number of receipts it came from, and is only
+/// summed again when new receipts arrive.
+pub fn note_zap_total(
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    txn: &Transaction,
+    note_key: NoteKey,
+    note: &Note,
+) -> u64 {
+    let limit = crate::filter::default_limit();
+    let filter = Filter::new()
+        .kinds([9735])
+        .event(note.id())
+        .limit(limit)
+        .build();
+    let receipts = ndb.query(txn, &[filter], limit as i32).unwrap_or_default();
+
+    let cached_note = note_cache.cached_note_or_insert_mut(note_key, note);
+    let count = receipts.len() as u32;
+    if let Some((counted, sats)) = cached_note.zap_total {
+        if counted == count {
+            return sats;
+        }
+    }
+
+    let sats = receipts
+        .iter()
+        .filter_map(|receipt| zap_receipt_sats(&receipt.note))
+        .sum();
+    cached_note.zap_total = Some((count, sats));
+    sats
+}
+
+/// The sats a zap receipt paid, from the invoice in its `bolt11` tag
+fn zap_receipt_sats(receipt: &Note) -> Option<u64> {
+    receipt.tags().iter().find_map(|tag| {
+        if tag.count() >= 2 && tag.get(0).and_then(|t| t.variant().str()) == Some("bolt11") {
+            tag.get(1)
+                .and_then(|t| t.variant().str())
+                .and_then(bolt11_sats)
+        } else {
+            None
+        }
+    })
+}
+
+/// The amount of a bolt11 invoice in sats, from its human readable part,
+/// eg. `lnbc2500u1...` is 2500 µBTC or 250,000 sats. Invoices without an
+/// amount have none.
+pub fn bolt11_sats(invoice: &str) -> Option<u64> {
+    let invoice = invoice.to_lowercase();
+
+    // the human readable part ends at the last 1, the bech32 separator
+    let hrp = &invoice[..invoice.rfind('1')?];
+
+    // then skip the currency, eg. bc, tb or bcrt
+    let amount = hrp
+        .strip_prefix("ln")?
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic());
+
+    let (digits, multiplier) = match amount.chars().last()? {
+        c if c.is_ascii_digit() => (amount, None),
+        c => (&amount[..amount.len() - 1], Some(c)),
+    };
+    let value: u64 = digits.parse().ok()?;
+
+    let msats = match multiplier {
+        None => value.checked_mul(100_000_000_000)?,
+        Some('m') => value.checked_mul(100_000_000)?,
+        Some('u') => value.checked_mul(100_000)?,
+        Some('n') => value.checked_mul(100)?,
+        Some('p') => value / 10,
+        Some(_) => return None,
+    };
+
+    Some(msats / 1000)
+}
+
+/// The proof of work difficulty of a note id: its number of leading zero
+/// bits
+pub fn pow_difficulty(id: &[u8; 32]) -> u32 {
+    let mut bits = 0;
+    for byte in id {
+        if *byte == 0 {
+            bits += 8;
+        } else {
+            bits += byte.leading_zeros();
+            break;
+        }
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pow_difficulty() {
+        let mut id = [0xff; 32];
+        assert_eq!(pow_difficulty(&id), 0);
+
+        id[0] = 0;
+        id[1] = 0b0001_0000;
+        assert_eq!(pow_difficulty(&id), 11);
+
+        assert_eq!(pow_difficulty(&[0; 32]), 256);
+    }
+
+    #[test]
+    fn test_bolt11_sats() {
+        assert_eq!(bolt11_sats("lnbc2500u1pvjluezpp5qqqsyq"), Some(250_000));
+        assert_eq!(bolt11_sats("LNBC20M1PVJLUEZ"), Some(2_000_000));
+        assert_eq!(bolt11_sats("lnbcrt10n1pvjluez"), Some(1));
+        assert_eq!(bolt11_sats("lnbc1pvjluezpp5qqqsyq"), None);
+        assert_eq!(bolt11_sats("not an invoice"), None);
+    }
+}
@@ -28,12 +28,51 @@ impl NoteCache {
             .entry(note_key)
             .or_insert_with(|| CachedNote::new(note))
     }
+
+    /// Populate the cache with many notes at once, reserving room for
+    /// all of them up front. Notes that are already cached keep their
+    /// existing data.
+    pub fn bulk_insert(&mut self, entries: Vec<(NoteKey, CachedNote)>) {
+        self.cache.reserve(entries.len());
+        for (note_key, cached_note) in entries {
+            self.cache.entry(note_key).or_insert(cached_note);
+        }
+    }
+
+    /// Drop a note's cached data so it's recomputed the next time it's
+    /// accessed, eg. by [`NoteCacheRefresher`](crate::note::NoteCacheRefresher)
+    /// when a deletion, reaction or zap referencing it arrives. Returns
+    /// whether the note was cached.
+    pub fn invalidate(&mut self, note_key: NoteKey) -> bool {
+        self.cache.remove(&note_key).is_some()
+    }
 }
 
 #[derive(Clone)]
 pub struct CachedNote {
     reltime: TimeCached<String>,
     pub reply: NoteReplyBuf,
+    /// Whether the author deleted this note (NIP-09). `None` if we
+    /// haven't checked yet
+    pub deleted: Option<bool>,
+    /// Number of reactions and zaps on this note, and the limit they were
+    /// counted up to. `None` if we haven't counted them yet
+    pub engagement: Option<(u32, u32)>,
+    /// Number of reactions on this note. `None` if we haven't counted
+    /// them yet
+    pub reactions: Option<u32>,
+    /// Number of zaps on this note. `None` if we haven't counted them yet
+    pub zaps: Option<u32>,
+    /// The receipts counted and the sats summed by
+    /// [`note_zap_total`](crate::note::note_zap_total). `None` until then
+    pub zap_total: Option<(u32, u64)>,
+    /// How many replies deep this note is in its thread, see
+    /// [`thread_depth`](crate::note::thread_depth). `None` if we haven't
+    /// walked the reply chain yet
+    pub thread_depth: Option<u32>,
+    /// How tall this note was the last time it was rendered in a
+    /// timeline. `None` until then
+    pub estimated_height: Option<f32>,
 }
 
 impl CachedNote {
@@ -44,7 +83,17 @@ impl CachedNote {
             Box::new(move || time_ago_since(created_at)),
         );
         let reply = NoteReply::new(note.tags()).to_owned();
-        CachedNote { reltime, reply }
+        CachedNote {
+            reltime,
+            reply,
+            deleted: None,
+            engagement: None,
+            reactions: None,
+            zaps: None,
+            zap_total: None,
+            thread_depth: None,
+            estimated_height: None,
+        }
     }
 
     pub fn reltime_str_mut(&mut self) -> &str {
@@ -55,3 +104,52 @@ impl CachedNote {
         self.reltime.get().map(|x| x.as_str())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostrdb::NoteBuilder;
+
+    fn test_note(seckey: &[u8; 32]) -> Note<'static> {
+        NoteBuilder::new()
+            .kind(1)
+            .content("hello")
+            .sign(seckey)
+            .build()
+            .expect("note")
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let mut cache = NoteCache::default();
+        let note = test_note(&[1; 32]);
+        let key = NoteKey::new(1);
+
+        cache.cached_note_or_insert(key, &note);
+        assert!(cache.cached_note(key).is_some());
+
+        assert!(cache.invalidate(key));
+        assert!(cache.cached_note(key).is_none());
+        assert!(!cache.invalidate(key));
+    }
+
+    #[test]
+    fn test_bulk_insert_keeps_existing() {
+        let mut cache = NoteCache::default();
+        let note = test_note(&[1; 32]);
+
+        cache
+            .cached_note_or_insert_mut(NoteKey::new(1), &note)
+            .reactions = Some(3);
+        cache.bulk_insert(vec![
+            (NoteKey::new(1), CachedNote::new(&note)),
+            (NoteKey::new(2), CachedNote::new(&note)),
+        ]);
+
+        assert_eq!(cache.cache.len(), 2);
+        assert_eq!(
+            cache.cached_note(NoteKey::new(1)).unwrap().reactions,
+            Some(3)
+        );
+    }
+}
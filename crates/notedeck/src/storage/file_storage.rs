@@ -6,6 +6,9 @@ use std::{
     time::SystemTime,
 };
 
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::error;
+
 use crate::{Error, Result};
 
 #[derive(Debug, Clone)]
@@ -191,6 +194,40 @@ pub fn write_file(directory: &Path, file_name: String, data: &str) -> Result<()>
     Ok(())
 }
 
+/// Parse the JSON settings file `file_name`. `None` when it doesn't exist
+/// yet, or when it can't be parsed, which is logged.
+pub fn load_json<T: DeserializeOwned>(path: &DataPath, file_name: &str) -> Option<T> {
+    let data_path = path.path(DataPathType::Setting);
+    // a missing file just means nothing was saved yet
+    let json = Directory::new(data_path)
+        .get_file(file_name.to_owned())
+        .ok()?;
+
+    match serde_json::from_str(&json) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            error!("Could not parse {}: {}", file_name, e);
+            None
+        }
+    }
+}
+
+/// Save `value` to the JSON settings file `file_name`, logging failures
+pub fn save_json<T: Serialize + ?Sized>(path: &DataPath, file_name: &str, value: &T) {
+    let json = match serde_json::to_string(value) {
+        Ok(json) => json,
+        Err(e) => {
+            error!("Could not serialize {}: {}", file_name, e);
+            return;
+        }
+    };
+
+    let data_path = path.path(DataPathType::Setting);
+    if let Err(e) = write_file(&data_path, file_name.to_owned(), &json) {
+        error!("Could not write {}: {}", file_name, e);
+    }
+}
+
 pub fn delete_file(directory: &Path, file_name: String) -> Result<()> {
     let file_to_delete = directory.join(file_name.clone());
     if file_to_delete.exists() && file_to_delete.is_file() {
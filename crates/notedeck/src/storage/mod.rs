@@ -2,7 +2,9 @@ mod file_key_storage;
 mod file_storage;
 
 pub use file_key_storage::FileKeyStorage;
-pub use file_storage::{delete_file, write_file, DataPath, DataPathType, Directory};
+pub use file_storage::{
+    delete_file, load_json, save_json, write_file, DataPath, DataPathType, Directory,
+};
 
 #[cfg(target_os = "macos")]
 mod security_framework_key_storage;
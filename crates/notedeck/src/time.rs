@@ -1,10 +1,15 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn time_ago_since(timestamp: u64) -> String {
-    let now = SystemTime::now()
+/// The current time in seconds since the unix epoch
+pub fn unix_now() -> u64 {
+    SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .expect("Time went backwards")
-        .as_secs();
+        .as_secs()
+}
+
+pub fn time_ago_since(timestamp: u64) -> String {
+    let now = unix_now();
 
     // Determine if the timestamp is in the future or the past
     let duration = if now >= timestamp {
@@ -53,3 +58,58 @@ pub fn time_ago_since(timestamp: u64) -> String {
 
     "now".to_string()
 }
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Convert days since the unix epoch into a (year, month, day) civil
+/// date. See http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// A human readable date for a unix timestamp, such as "Today",
+/// "Yesterday" or "Oct 14, 2024". Dates are in UTC.
+pub fn date_label(timestamp: u64) -> String {
+    let now = unix_now();
+
+    let day = (timestamp / 86_400) as i64;
+    let today = (now / 86_400) as i64;
+
+    if day == today {
+        return "Today".to_string();
+    } else if day + 1 == today {
+        return "Yesterday".to_string();
+    }
+
+    let (year, month, day) = civil_from_days(day);
+    format!("{} {}, {}", MONTHS[month as usize - 1], day, year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_737), (2024, 1, 15));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+    }
+
+    #[test]
+    fn test_date_label() {
+        assert_eq!(date_label(0), "Jan 1, 1970");
+    }
+}
@@ -26,8 +26,16 @@ pub struct Notedeck {
     theme: ThemeHandler,
     tabs: Tabs,
     app_rect_handler: AppSizeHandler,
+    /// When we last checked the image cache against `--image-budget`, in
+    /// egui time
+    last_eviction: f64,
 }
 
+/// How often to check the image cache against `--image-budget`, in
+/// seconds. Eviction walks every cached image, so it isn't done every
+/// frame.
+const IMAGE_EVICTION_INTERVAL: f64 = 5.0;
+
 fn margin_top(narrow: bool) -> f32 {
     #[cfg(target_os = "android")]
     {
@@ -81,6 +89,14 @@ impl eframe::App for Notedeck {
 
         self.app_rect_handler.try_save_app_size(ctx);
 
+        if let Some(budget) = self.args.image_budget {
+            let now = ctx.input(|i| i.time);
+            if now - self.last_eviction >= IMAGE_EVICTION_INTERVAL {
+                self.last_eviction = now;
+                self.img_cache.evict_lru(budget);
+            }
+        }
+
         if self.args.relay_debug {
             if self.pool.debug.is_none() {
                 self.pool.use_debug();
@@ -211,6 +227,7 @@ impl Notedeck {
             ndb,
             img_cache,
             app_rect_handler,
+            last_eviction: 0.0,
             unknown_ids,
             pool,
             note_cache,
@@ -1,21 +1,49 @@
 use crate::{
     column::Columns,
+    help::HelpTrigger,
     notes_holder::{NotesHolder, NotesHolderStorage},
     profile::Profile,
     route::{Route, Router},
     thread::Thread,
+    timeline::TimelineId,
 };
 
 use enostr::{NoteId, Pubkey, RelayPool};
-use nostrdb::{Ndb, Transaction};
+use nostrdb::{Ndb, NoteKey, Transaction};
 use notedeck::{note::root_note_id_from_selected_id, NoteCache, NoteRef};
+use url::Url;
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum NoteAction {
     Reply(NoteId),
     Quote(NoteId),
     OpenThread(NoteId),
     OpenProfile(Pubkey),
+    AddRelay(Url),
+    PlayMedia(String),
+    /// Show these images fullscreen, starting with the first
+    ShowMediaViewer(Vec<String>),
+    FocusColumn(TimelineId),
+    /// Ask the relays for this timeline's notes again
+    RefreshTimeline(TimelineId),
+    /// Open a column subscribed to this hashtag, without the `#`
+    OpenHashtagColumn(String),
+    /// Hide the thread this note is in for this many seconds
+    SnoozeThread(NoteId, u64),
+    /// Never show this contextual help again
+    DismissHelp(HelpTrigger),
+    /// Find where this shortened url really goes
+    ResolveUrl(String),
+    /// Copy the note's raw event JSON to the clipboard
+    CopyEventJson(NoteKey),
+    /// Publish a new note with `content` at the unix time `publish_at`
+    ScheduleNote {
+        content: String,
+        publish_at: u64,
+    },
+    /// This note was shown in a timeline for the first time, see
+    /// [`TimelineTabView::with_seen_notes`](crate::ui::timeline::TimelineTabView::with_seen_notes)
+    Seen(NoteKey),
 }
 
 pub struct NewNotes {
@@ -79,6 +107,24 @@ impl NoteAction {
                 router.route_to(Route::quote(note_id));
                 None
             }
+
+            // these need state the app owns, like the relay pool, image
+            // cache, clipboard or scheduler, so nav handles them before
+            // they get here. Adding a relay waits for the user to confirm,
+            // see `ViewState::pending_relay`. Seen is only for callers
+            // tracking what has been read.
+            NoteAction::AddRelay(_)
+            | NoteAction::PlayMedia(_)
+            | NoteAction::ShowMediaViewer(_)
+            | NoteAction::FocusColumn(_)
+            | NoteAction::RefreshTimeline(_)
+            | NoteAction::OpenHashtagColumn(_)
+            | NoteAction::SnoozeThread(..)
+            | NoteAction::DismissHelp(_)
+            | NoteAction::ResolveUrl(_)
+            | NoteAction::CopyEventJson(_)
+            | NoteAction::ScheduleNote { .. }
+            | NoteAction::Seen(_) => None,
         }
     }
 
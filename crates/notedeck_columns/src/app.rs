@@ -3,21 +3,34 @@ use crate::{
     column::Columns,
     decks::{Decks, DecksCache, FALLBACK_PUBKEY},
     draft::Drafts,
+    help::SeenHelp,
     nav,
     notes_holder::NotesHolderStorage,
     profile::Profile,
+    relay_pool_manager::RelayPoolManager,
+    route::Route,
+    scheduler::Scheduler,
+    snooze::Snoozes,
     storage,
     subscriptions::{SubKind, Subscriptions},
     support::Support,
     thread::Thread,
-    timeline::{self, Timeline},
-    ui::{self, DesktopSidePanel},
+    timeline::{self, csv::RelayCounts, Timeline, TimelineSettings},
+    ui::{
+        self,
+        column::ColumnDivider,
+        note::{DisplayNameFormat, LinkifyMode, NoteDensity, NoteOptions, VideoAutoplay},
+        DesktopSidePanel,
+    },
     unknowns,
     view_state::ViewState,
     Result,
 };
 
-use notedeck::{Accounts, AppContext, DataPath, DataPathType, FilterState, ImageCache, UnknownIds};
+use notedeck::note::NoteCacheRefresher;
+use notedeck::{
+    unix_now, Accounts, AppContext, DataPath, DataPathType, FilterState, ImageCache, UnknownIds,
+};
 
 use enostr::{ClientMessage, Keypair, PoolRelay, Pubkey, RelayEvent, RelayMessage, RelayPool};
 use uuid::Uuid;
@@ -54,6 +67,55 @@ pub struct Damus {
     pub debug: bool,
     pub since_optimize: bool,
     pub textmode: bool,
+    pub touch_mode: bool,
+    pub reduce_autoplay: bool,
+    /// How timeline columns behave, see [`TimelineSettings`]
+    pub timeline_settings: TimelineSettings,
+    /// Show a preview of the thread root above replies
+    pub thread_root_preview: bool,
+    /// Show map thumbnails for geotagged notes
+    pub geo_map: bool,
+    /// Play gifs without waiting for a tap
+    pub gif_autoplay: bool,
+    /// Warn when we have no relays to load notes from
+    pub relay_warning: bool,
+    /// Show the proof of work difficulty of note ids
+    pub show_pow: bool,
+    /// Indent replies by how deep they are in their thread
+    pub thread_depth: bool,
+    /// Show links as cards below the note text instead of inline
+    pub hide_urls: bool,
+    /// Badge notes that aren't kind 1 with their event kind
+    pub kind_badges: bool,
+    /// Show the location of geotagged notes in their header
+    pub geo_tags: bool,
+    /// Show where shortened links really go
+    pub expand_urls: bool,
+    /// Show a stub for muted notes that can be tapped to reveal them
+    pub muted_placeholder: bool,
+    /// Show the total sats zapped to each note
+    pub zap_summary: bool,
+    /// Which links in notes are clickable
+    pub linkify: LinkifyMode,
+    /// How tightly notes are packed in timelines
+    pub density: NoteDensity,
+    /// How note authors are named
+    pub display_name_format: DisplayNameFormat,
+    /// When videos in notes load without being played
+    pub video_autoplay: VideoAutoplay,
+    /// How adjacent columns are separated
+    pub column_divider: ColumnDivider,
+    /// Threads hidden for a while, see [`NoteAction::SnoozeThread`](crate::actionbar::NoteAction::SnoozeThread)
+    pub snoozes: Snoozes,
+    /// Contextual help the user dismissed, see [`NoteAction::DismissHelp`](crate::actionbar::NoteAction::DismissHelp)
+    pub seen_help: SeenHelp,
+    /// Notes waiting to be published, see [`NoteAction::ScheduleNote`](crate::actionbar::NoteAction::ScheduleNote)
+    pub scheduler: Scheduler,
+    /// Which relays sent us each note, counted in debug mode for the CSV
+    /// export
+    pub relay_counts: RelayCounts,
+    /// Keeps cached note data current as deletions arrive
+    pub note_cache_refresher: NoteCacheRefresher,
 }
 
 fn handle_key_events(input: &egui::InputState, columns: &mut Columns) {
@@ -73,7 +135,7 @@ fn handle_key_events(input: &egui::InputState, columns: &mut Columns) {
                     columns.select_left();
                 }
                 egui::Key::L => {
-                    columns.select_left();
+                    columns.select_right();
                 }
                 _ => {}
             }
@@ -87,7 +149,10 @@ fn try_process_event(
     ctx: &egui::Context,
 ) -> Result<()> {
     let current_columns = get_active_columns_mut(app_ctx.accounts, &mut damus.decks_cache);
-    ctx.input(|i| handle_key_events(i, current_columns));
+    // don't steal keys from the composer or search boxes
+    if !ctx.wants_keyboard_input() {
+        ctx.input(|i| handle_key_events(i, current_columns));
+    }
 
     let ctx2 = ctx.clone();
     let wakeup = move || {
@@ -119,6 +184,13 @@ fn try_process_event(
                     app_ctx.pool,
                     &ev.relay,
                 );
+
+                timeline::read_markers::send_initial_filter(
+                    get_active_columns(app_ctx.accounts, &damus.decks_cache),
+                    app_ctx.accounts,
+                    app_ctx.pool,
+                    &ev.relay,
+                );
             }
             // TODO: handle reconnects
             RelayEvent::Closed => warn!("{} connection closed", &ev.relay),
@@ -130,6 +202,13 @@ fn try_process_event(
         }
     }
 
+    if let Ok(txn) = Transaction::new(app_ctx.ndb) {
+        damus
+            .note_cache_refresher
+            .poll(app_ctx.ndb, &txn, app_ctx.note_cache);
+    }
+
+    let is_muted = app_ctx.accounts.mutefun();
     let current_columns = get_active_columns_mut(app_ctx.accounts, &mut damus.decks_cache);
     let n_timelines = current_columns.timelines().len();
     for timeline_ind in 0..n_timelines {
@@ -158,6 +237,7 @@ fn try_process_event(
                 &txn,
                 app_ctx.unknown_ids,
                 app_ctx.note_cache,
+                &*is_muted,
             ) {
                 error!("poll_notes_into_view: {err}");
             }
@@ -194,6 +274,7 @@ fn update_damus(damus: &mut Damus, app_ctx: &mut AppContext<'_>, ctx: &egui::Con
             damus
                 .subscriptions()
                 .insert("unknownids".to_string(), SubKind::OneShot);
+            damus.note_cache_refresher.subscribe(app_ctx.ndb);
             if let Err(err) = timeline::setup_initial_nostrdb_subs(
                 app_ctx.ndb,
                 app_ctx.note_cache,
@@ -206,11 +287,50 @@ fn update_damus(damus: &mut Damus, app_ctx: &mut AppContext<'_>, ctx: &egui::Con
         DamusState::Initialized => (),
     };
 
+    if let Some(due) = damus.scheduler.next_due() {
+        let now = unix_now();
+        if due <= now {
+            damus
+                .scheduler
+                .publish_due(app_ctx.accounts, app_ctx.pool, app_ctx.path, now);
+        } else {
+            // make sure we wake up in time even if nothing else happens
+            ctx.request_repaint_after(Duration::from_secs(due - now));
+        }
+    }
+
+    publish_read_markers(damus, app_ctx, ctx);
+
     if let Err(err) = try_process_event(damus, app_ctx, ctx) {
         error!("error processing event: {}", err);
     }
 }
 
+/// Publish the read markers every column has been collecting for long
+/// enough
+fn publish_read_markers(damus: &mut Damus, app_ctx: &mut AppContext<'_>, ctx: &egui::Context) {
+    let now = unix_now();
+    let columns = get_active_columns_mut(app_ctx.accounts, &mut damus.decks_cache);
+    let txn = if let Ok(txn) = Transaction::new(app_ctx.ndb) {
+        txn
+    } else {
+        return;
+    };
+
+    for timeline in columns.timelines_mut() {
+        let markers = if let Some(markers) = timeline.read_markers.as_mut() {
+            markers
+        } else {
+            continue;
+        };
+
+        markers.publish_due(app_ctx.ndb, &txn, app_ctx.accounts, app_ctx.pool, now);
+        if let Some(due) = markers.next_due() {
+            ctx.request_repaint_after(Duration::from_secs(due.saturating_sub(now)));
+        }
+    }
+}
+
 fn handle_eose(
     damus: &mut Damus,
     ctx: &mut AppContext<'_>,
@@ -232,7 +352,13 @@ fn handle_eose(
         SubKind::Timeline(_) => {
             // eose on timeline? whatevs
         }
-        SubKind::Initial => {
+        SubKind::Initial(timeline_uid) => {
+            if let Some(timeline) = get_active_columns_mut(ctx.accounts, &mut damus.decks_cache)
+                .find_timeline_mut(timeline_uid)
+            {
+                timeline.backfill.insert(relay_url.to_owned(), true);
+            }
+
             let txn = Transaction::new(ctx.ndb)?;
             unknowns::update_from_columns(
                 &txn,
@@ -304,6 +430,10 @@ fn handle_eose(
 fn process_message(damus: &mut Damus, ctx: &mut AppContext<'_>, relay: &str, msg: &RelayMessage) {
     match msg {
         RelayMessage::Event(_subid, ev) => {
+            if damus.debug {
+                damus.relay_counts.record(relay, ev);
+            }
+
             let relay = if let Some(relay) = ctx.pool.relays.iter().find(|r| r.url() == relay) {
                 relay
             } else {
@@ -327,7 +457,12 @@ fn process_message(damus: &mut Damus, ctx: &mut AppContext<'_>, relay: &str, msg
             }
         }
         RelayMessage::Notice(msg) => warn!("Notice from {}: {}", relay, msg),
-        RelayMessage::OK(cr) => info!("OK {:?}", cr),
+        RelayMessage::OK(cr) => {
+            info!("OK {:?}", cr);
+            damus
+                .drafts
+                .handle_ok(relay, cr.event_id(), cr.status(), cr.message());
+        }
         RelayMessage::Eose(sid) => {
             if let Err(err) = handle_eose(damus, ctx, sid, relay) {
                 error!("error handling eose: {}", err);
@@ -337,12 +472,43 @@ fn process_message(damus: &mut Damus, ctx: &mut AppContext<'_>, relay: &str, msg
 }
 
 fn render_damus(damus: &mut Damus, app_ctx: &mut AppContext<'_>, ui: &mut egui::Ui) {
+    if damus.relay_warning && RelayPoolManager::new(app_ctx.pool).is_offline() {
+        let num_relays = app_ctx.pool.relays.len();
+        if ui::relay::no_relays_banner(ui, num_relays) {
+            let router = damus.columns_mut(app_ctx.accounts).get_first_router();
+            if !router.routes().iter().any(|&r| r == Route::Relays) {
+                router.route_to(Route::relays());
+            }
+        }
+    }
+
     if notedeck::ui::is_narrow(ui.ctx()) {
         render_damus_mobile(damus, app_ctx, ui);
     } else {
         render_damus_desktop(damus, app_ctx, ui);
     }
 
+    if let Some(relay_url) = damus.view_state.pending_relay.clone() {
+        if let Some(confirmed) = ui::relay::add_relay_prompt(ui.ctx(), relay_url.as_str()) {
+            if confirmed {
+                RelayPoolManager::new(app_ctx.pool).add_relay(ui.ctx(), relay_url.to_string());
+            }
+            damus.view_state.pending_relay = None;
+        }
+    }
+
+    if let Some(message) = &damus.view_state.workspace_error {
+        if ui::column::workspace_error_dialog(ui.ctx(), message) {
+            damus.view_state.workspace_error = None;
+        }
+    }
+
+    if let Some(viewer) = &mut damus.view_state.media_viewer {
+        if ui::media_viewer::media_viewer(ui.ctx(), app_ctx.img_cache, viewer) {
+            damus.view_state.media_viewer = None;
+        }
+    }
+
     // We use this for keeping timestamps and things up to date
     ui.ctx().request_repaint_after(Duration::from_secs(1));
 }
@@ -422,6 +588,31 @@ impl Damus {
             drafts: Drafts::default(),
             state: DamusState::Initializing,
             textmode: parsed_args.textmode,
+            touch_mode: parsed_args.touch_mode,
+            reduce_autoplay: parsed_args.reduce_autoplay,
+            timeline_settings: parsed_args.timeline,
+            thread_root_preview: parsed_args.thread_root_preview,
+            geo_map: parsed_args.geo_map,
+            gif_autoplay: parsed_args.gif_autoplay,
+            relay_warning: parsed_args.relay_warning,
+            show_pow: parsed_args.show_pow,
+            thread_depth: parsed_args.thread_depth,
+            hide_urls: parsed_args.hide_urls,
+            kind_badges: parsed_args.kind_badges,
+            geo_tags: parsed_args.geo_tags,
+            expand_urls: parsed_args.expand_urls,
+            muted_placeholder: parsed_args.muted_placeholder,
+            zap_summary: parsed_args.zap_summary,
+            linkify: parsed_args.linkify,
+            density: parsed_args.density,
+            display_name_format: parsed_args.display_name_format,
+            video_autoplay: parsed_args.video_autoplay,
+            column_divider: parsed_args.column_divider,
+            snoozes: Snoozes::load(ctx.path),
+            seen_help: SeenHelp::load(ctx.path),
+            scheduler: Scheduler::load(ctx.path),
+            relay_counts: RelayCounts::default(),
+            note_cache_refresher: NoteCacheRefresher::default(),
             //frame_history: FrameHistory::default(),
             view_state: ViewState::default(),
             support,
@@ -438,6 +629,32 @@ impl Damus {
         get_active_columns(accounts, &self.decks_cache)
     }
 
+    /// The note options that apply to every view, derived from our
+    /// app-wide settings. Views may further customize these.
+    pub fn note_options(&self) -> NoteOptions {
+        let mut options = NoteOptions::default();
+        options.set_textmode(self.textmode);
+        options.set_touch_mode(self.touch_mode);
+        options.set_reduce_autoplay(self.reduce_autoplay);
+        options.set_show_thread_root_preview(self.thread_root_preview);
+        options.set_show_geo_map(self.geo_map);
+        options.set_gif_autoplay(self.gif_autoplay);
+        options.set_show_client_proof_of_work(self.show_pow);
+        options.set_show_thread_depth(self.thread_depth);
+        options.set_hide_urls_in_content(self.hide_urls);
+        options.set_show_event_kind_badge(self.kind_badges);
+        options.set_show_geo_tag(self.geo_tags);
+        options.set_expand_shortened_urls(self.expand_urls);
+        options.set_show_muted_placeholder(self.muted_placeholder);
+        options.set_show_zap_summary(self.zap_summary);
+        options.set_linkify_mode(self.linkify);
+        options.set_density(self.density);
+        options.set_display_name_format(self.display_name_format);
+        options.set_video_autoplay(self.video_autoplay);
+        options.set_verify_signature(self.debug);
+        options
+    }
+
     pub fn gen_subid(&self, kind: &SubKind) -> String {
         if self.debug {
             format!("{:?}", kind)
@@ -465,6 +682,31 @@ impl Damus {
             drafts: Drafts::default(),
             state: DamusState::Initializing,
             textmode: false,
+            touch_mode: false,
+            reduce_autoplay: false,
+            timeline_settings: TimelineSettings::default(),
+            thread_root_preview: false,
+            geo_map: false,
+            gif_autoplay: false,
+            relay_warning: true,
+            show_pow: false,
+            thread_depth: false,
+            hide_urls: false,
+            kind_badges: false,
+            geo_tags: false,
+            expand_urls: false,
+            muted_placeholder: false,
+            zap_summary: false,
+            linkify: LinkifyMode::default(),
+            density: NoteDensity::default(),
+            display_name_format: DisplayNameFormat::default(),
+            video_autoplay: VideoAutoplay::default(),
+            column_divider: ColumnDivider::default(),
+            snoozes: Snoozes::default(),
+            seen_help: SeenHelp::default(),
+            scheduler: Scheduler::default(),
+            relay_counts: RelayCounts::default(),
+            note_cache_refresher: NoteCacheRefresher::default(),
             //frame_history: FrameHistory::default(),
             view_state: ViewState::default(),
             support,
@@ -501,7 +743,7 @@ fn render_damus_mobile(app: &mut Damus, app_ctx: &mut AppContext<'_>, ui: &mut e
     //let routes = app.timelines[0].routes.clone();
 
     if !app.columns(app_ctx.accounts).columns().is_empty()
-        && nav::render_nav(0, app, app_ctx, ui).process_render_nav_response(app, app_ctx)
+        && nav::render_nav(0, app, app_ctx, ui).process_render_nav_response(app, app_ctx, ui.ctx())
     {
         storage::save_decks_cache(app_ctx.path, &app.decks_cache);
     }
@@ -512,11 +754,21 @@ fn render_damus_desktop(app: &mut Damus, app_ctx: &mut AppContext<'_>, ui: &mut
     puffin::profile_function!();
 
     let screen_size = ui.ctx().screen_rect().width();
-    let calc_panel_width = (screen_size
-        / get_active_columns(app_ctx.accounts, &app.decks_cache).num_columns() as f32)
-        - 30.0;
+    let num_cols = app.columns(app_ctx.accounts).num_columns();
+    let resized: Vec<f32> = (0..num_cols)
+        .filter_map(|col| column_width(app, app_ctx, col))
+        .collect();
+    let resized_width: f32 = resized.iter().sum();
+    // the columns the user didn't resize share what's left
+    let shared_cols = num_cols - resized.len();
+    let calc_panel_width = if shared_cols == 0 {
+        f32::INFINITY
+    } else {
+        ((screen_size - resized_width) / shared_cols as f32) - 30.0
+    };
     let min_width = 320.0;
-    let need_scroll = calc_panel_width < min_width;
+    let need_scroll = calc_panel_width < min_width
+        || resized_width + ui::side_panel::SIDE_PANEL_WIDTH > screen_size;
     let panel_sizes = if need_scroll {
         Size::exact(min_width)
     } else {
@@ -534,77 +786,116 @@ fn render_damus_desktop(app: &mut Damus, app_ctx: &mut AppContext<'_>, ui: &mut
 }
 
 fn timelines_view(ui: &mut egui::Ui, sizes: Size, app: &mut Damus, ctx: &mut AppContext<'_>) {
-    StripBuilder::new(ui)
-        .size(Size::exact(ui::side_panel::SIDE_PANEL_WIDTH))
-        .sizes(
-            sizes,
-            get_active_columns(ctx.accounts, &app.decks_cache).num_columns(),
-        )
-        .clip(true)
-        .horizontal(|mut strip| {
-            let mut side_panel_action: Option<nav::SwitchingAction> = None;
+    // the strip borrows `ui` until it's done
+    let egui_ctx = ui.ctx().clone();
+    app.columns_mut(ctx.accounts).archive_inactive(unix_now());
+
+    let mut builder = StripBuilder::new(ui).size(Size::exact(ui::side_panel::SIDE_PANEL_WIDTH));
+    for col_index in 0..app.columns(ctx.accounts).num_columns() {
+        builder = builder.size(column_width(app, ctx, col_index).map_or(sizes, Size::exact));
+    }
+
+    builder.clip(true).horizontal(|mut strip| {
+        let mut side_panel_action: Option<nav::SwitchingAction> = None;
+        strip.cell(|ui| {
+            let rect = ui.available_rect_before_wrap();
+            let side_panel = DesktopSidePanel::new(
+                ctx.ndb,
+                ctx.img_cache,
+                ctx.accounts.get_selected_account(),
+                &app.decks_cache,
+            )
+            .show(ui);
+
+            if side_panel.response.clicked() || side_panel.response.secondary_clicked() {
+                if let Some(action) = DesktopSidePanel::perform_action(
+                    &mut app.decks_cache,
+                    ctx.accounts,
+                    &mut app.support,
+                    ctx.theme,
+                    side_panel.action,
+                ) {
+                    side_panel_action = Some(action);
+                }
+            }
+
+            // vertical sidebar line
+            ui.painter().vline(
+                rect.right(),
+                rect.y_range(),
+                ui.visuals().widgets.noninteractive.bg_stroke,
+            );
+        });
+
+        let mut save_cols = false;
+        if let Some(action) = side_panel_action {
+            save_cols = save_cols || action.process(&mut app.decks_cache, ctx);
+        }
+
+        let num_cols = app.columns(ctx.accounts).num_columns();
+        let mut responses = Vec::with_capacity(num_cols);
+        for col_index in 0..num_cols {
             strip.cell(|ui| {
                 let rect = ui.available_rect_before_wrap();
-                let side_panel = DesktopSidePanel::new(
-                    ctx.ndb,
-                    ctx.img_cache,
-                    ctx.accounts.get_selected_account(),
-                    &app.decks_cache,
-                )
-                .show(ui);
-
-                if side_panel.response.clicked() || side_panel.response.secondary_clicked() {
-                    if let Some(action) = DesktopSidePanel::perform_action(
-                        &mut app.decks_cache,
-                        ctx.accounts,
-                        &mut app.support,
-                        ctx.theme,
-                        side_panel.action,
-                    ) {
-                        side_panel_action = Some(action);
+                let divider = column_divider(app, ctx, col_index);
+                let content_rect = rect.with_max_x(rect.right() - divider.gap());
+                let response = ui
+                    .allocate_new_ui(egui::UiBuilder::new().max_rect(content_rect), |ui| {
+                        nav::render_nav(col_index, app, ctx, ui)
+                    })
+                    .inner;
+                responses.push(response);
+
+                // the column may have picked a divider while rendering
+                column_divider(app, ctx, col_index).paint(ui, rect);
+
+                let snap = app
+                    .columns(ctx.accounts)
+                    .find_timeline_for_column_index(col_index)
+                    .and_then(|tl| tl.column_snap);
+                let handle_id = ui.id().with(("column_resize", col_index));
+                if let Some(width) = ui::column::resize_handle(ui, handle_id, rect, snap) {
+                    if let Some(timeline) = app
+                        .columns_mut(ctx.accounts)
+                        .find_timeline_for_column_index_mut(col_index)
+                    {
+                        timeline.width = Some(width);
                     }
                 }
-
-                // vertical sidebar line
-                ui.painter().vline(
-                    rect.right(),
-                    rect.y_range(),
-                    ui.visuals().widgets.noninteractive.bg_stroke,
-                );
             });
 
-            let mut save_cols = false;
-            if let Some(action) = side_panel_action {
-                save_cols = save_cols || action.process(&mut app.decks_cache, ctx);
-            }
+            //strip.cell(|ui| timeline::timeline_view(ui, app, timeline_ind));
+        }
 
-            let num_cols = app.columns(ctx.accounts).num_columns();
-            let mut responses = Vec::with_capacity(num_cols);
-            for col_index in 0..num_cols {
-                strip.cell(|ui| {
-                    let rect = ui.available_rect_before_wrap();
-                    responses.push(nav::render_nav(col_index, app, ctx, ui));
-
-                    // vertical line
-                    ui.painter().vline(
-                        rect.right(),
-                        rect.y_range(),
-                        ui.visuals().widgets.noninteractive.bg_stroke,
-                    );
-                });
-
-                //strip.cell(|ui| timeline::timeline_view(ui, app, timeline_ind));
-            }
+        for response in responses {
+            let save = response.process_render_nav_response(app, ctx, &egui_ctx);
+            save_cols = save_cols || save;
+        }
 
-            for response in responses {
-                let save = response.process_render_nav_response(app, ctx);
-                save_cols = save_cols || save;
-            }
+        if let Some(id) = ui::column::archived_drawer(&egui_ctx, app.columns(ctx.accounts)) {
+            let restored = app.columns_mut(ctx.accounts).restore_from_archive(id);
+            save_cols = save_cols || restored.is_ok();
+        }
 
-            if save_cols {
-                storage::save_decks_cache(ctx.path, &app.decks_cache);
-            }
-        });
+        if save_cols {
+            storage::save_decks_cache(ctx.path, &app.decks_cache);
+        }
+    });
+}
+
+/// The divider after the column at `col`: its timeline's, or the app's
+fn column_divider(app: &Damus, ctx: &AppContext<'_>, col: usize) -> ColumnDivider {
+    app.columns(ctx.accounts)
+        .find_timeline_for_column_index(col)
+        .and_then(|tl| tl.divider)
+        .unwrap_or(app.column_divider)
+}
+
+/// The width the user dragged the column at `col` to, if they resized it
+fn column_width(app: &Damus, ctx: &AppContext<'_>, col: usize) -> Option<f32> {
+    app.columns(ctx.accounts)
+        .find_timeline_for_column_index(col)
+        .and_then(|tl| tl.width)
 }
 
 impl notedeck::App for Damus {
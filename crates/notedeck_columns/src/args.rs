@@ -1,22 +1,65 @@
 use notedeck::FilterState;
 
-use crate::timeline::{PubkeySource, Timeline, TimelineKind, TimelineTab};
+use crate::timeline::{PubkeySource, Timeline, TimelineKind, TimelineSettings, TimelineTab};
+use crate::ui::column::ColumnDivider;
+use crate::ui::note::{DisplayNameFormat, LinkifyMode, NoteDensity, VideoAutoplay};
 use enostr::{Filter, Pubkey};
 use nostrdb::Ndb;
+use std::time::Duration;
 use tracing::{debug, error, info};
 
 pub struct ColumnsArgs {
     pub columns: Vec<ArgColumn>,
+    pub timeline: TimelineSettings,
     pub since_optimize: bool,
     pub textmode: bool,
+    pub touch_mode: bool,
+    pub reduce_autoplay: bool,
+    pub thread_root_preview: bool,
+    pub geo_map: bool,
+    pub gif_autoplay: bool,
+    pub relay_warning: bool,
+    pub show_pow: bool,
+    pub thread_depth: bool,
+    pub hide_urls: bool,
+    pub kind_badges: bool,
+    pub geo_tags: bool,
+    pub expand_urls: bool,
+    pub muted_placeholder: bool,
+    pub zap_summary: bool,
+    pub linkify: LinkifyMode,
+    pub density: NoteDensity,
+    pub display_name_format: DisplayNameFormat,
+    pub video_autoplay: VideoAutoplay,
+    pub column_divider: ColumnDivider,
 }
 
 impl ColumnsArgs {
     pub fn parse(args: &[String]) -> Self {
         let mut res = Self {
             columns: vec![],
+            timeline: TimelineSettings::default(),
             since_optimize: true,
             textmode: false,
+            touch_mode: notedeck::ui::is_compiled_as_mobile(),
+            reduce_autoplay: false,
+            thread_root_preview: false,
+            geo_map: false,
+            gif_autoplay: false,
+            relay_warning: true,
+            show_pow: false,
+            thread_depth: false,
+            hide_urls: false,
+            kind_badges: false,
+            geo_tags: false,
+            expand_urls: false,
+            muted_placeholder: false,
+            zap_summary: false,
+            linkify: LinkifyMode::default(),
+            density: NoteDensity::default(),
+            display_name_format: DisplayNameFormat::default(),
+            video_autoplay: VideoAutoplay::default(),
+            column_divider: ColumnDivider::default(),
         };
 
         let mut i = 0;
@@ -26,6 +69,135 @@ impl ColumnsArgs {
 
             if arg == "--textmode" {
                 res.textmode = true;
+            } else if arg == "--touch-mode" {
+                res.touch_mode = true;
+            } else if arg == "--no-touch-mode" {
+                res.touch_mode = false;
+            } else if arg == "--reduce-autoplay" {
+                res.reduce_autoplay = true;
+            } else if arg == "--hide-own-reposts" {
+                res.timeline.hide_own_reposts = true;
+            } else if arg == "--thread-root-preview" {
+                res.thread_root_preview = true;
+            } else if arg == "--no-relay-warning" {
+                res.relay_warning = false;
+            } else if arg == "--show-pow" {
+                res.show_pow = true;
+            } else if arg == "--linkify" {
+                i += 1;
+                match args.get(i).map(|s| s.as_str()) {
+                    Some("all") => res.linkify = LinkifyMode::All,
+                    Some("https") => res.linkify = LinkifyMode::HttpsOnly,
+                    Some("none") => res.linkify = LinkifyMode::None,
+                    _ => error!("linkify expects all, https or none"),
+                }
+            } else if arg == "--density" {
+                i += 1;
+                match args.get(i).map(|s| s.as_str()) {
+                    Some("compact") => res.density = NoteDensity::Compact,
+                    Some("comfortable") => res.density = NoteDensity::Comfortable,
+                    _ => error!("density expects compact or comfortable"),
+                }
+            } else if arg == "--display-name-format" {
+                i += 1;
+                match args.get(i).map(|s| s.as_str()) {
+                    Some("display-name") => {
+                        res.display_name_format = DisplayNameFormat::DisplayName
+                    }
+                    Some("username") => res.display_name_format = DisplayNameFormat::Username,
+                    Some("pubkey") => res.display_name_format = DisplayNameFormat::Pubkey,
+                    Some("both") => res.display_name_format = DisplayNameFormat::Both,
+                    _ => {
+                        error!("display-name-format expects display-name, username, pubkey or both")
+                    }
+                }
+            } else if arg == "--video-autoplay" {
+                i += 1;
+                match args.get(i).map(|s| s.as_str()) {
+                    Some("always") => res.video_autoplay = VideoAutoplay::Always,
+                    Some("wifi") => res.video_autoplay = VideoAutoplay::OnWifi,
+                    Some("never") => res.video_autoplay = VideoAutoplay::Never,
+                    _ => error!("video-autoplay expects always, wifi or never"),
+                }
+            } else if arg == "--timeline-search" {
+                res.timeline.timeline_search = true;
+            } else if arg == "--tab-drag-reorder" {
+                res.timeline.tab_drag_reorder = true;
+            } else if arg == "--read-markers" {
+                res.timeline.read_markers = true;
+            } else if arg == "--zap-summary" {
+                res.zap_summary = true;
+            } else if arg == "--muted-placeholder" {
+                res.muted_placeholder = true;
+            } else if arg == "--expand-urls" {
+                res.expand_urls = true;
+            } else if arg == "--geo-tags" {
+                res.geo_tags = true;
+            } else if arg == "--kind-badges" {
+                res.kind_badges = true;
+            } else if arg == "--hide-urls" {
+                res.hide_urls = true;
+            } else if arg == "--thread-depth" {
+                res.thread_depth = true;
+            } else if arg == "--gif-autoplay" {
+                res.gif_autoplay = true;
+            } else if arg == "--geo-map" {
+                res.geo_map = true;
+            } else if arg == "--collapse-header" {
+                res.timeline.collapse_header = true;
+            } else if arg == "--watermark" {
+                i += 1;
+                if let Some(text) = args.get(i) {
+                    res.timeline.watermark = Some(text.clone());
+                } else {
+                    error!("watermark argument missing");
+                }
+            } else if arg == "--column-divider" {
+                i += 1;
+                if let Some(divider) = args.get(i).and_then(|s| ColumnDivider::parse(s)) {
+                    res.column_divider = divider;
+                } else {
+                    error!("column-divider expects line, shadow or a gap width");
+                }
+            } else if arg == "--column-snap" {
+                i += 1;
+                if let Some(grid) = args
+                    .get(i)
+                    .and_then(|s| s.parse::<f32>().ok())
+                    .filter(|grid| *grid > 0.0)
+                {
+                    res.timeline.column_snap = Some(grid);
+                } else {
+                    error!("column-snap expects a grid size in points");
+                }
+            } else if arg == "--archive-after" {
+                i += 1;
+                if let Some(secs) = args.get(i).and_then(|s| s.parse::<u64>().ok()) {
+                    res.timeline.archive_after = Some(Duration::from_secs(secs));
+                } else {
+                    error!("archive-after expects a number of seconds");
+                }
+            } else if arg == "--collapse-replies" {
+                i += 1;
+                let threshold = if let Some(next_arg) = args.get(i) {
+                    next_arg
+                } else {
+                    error!("collapse-replies argument missing");
+                    continue;
+                };
+
+                if let Ok(threshold) = threshold.parse() {
+                    res.timeline.reply_collapse_threshold = Some(threshold);
+                } else {
+                    error!("failed to parse collapse-replies threshold '{}'", threshold);
+                }
+            } else if arg == "--reply-depth-limit" {
+                i += 1;
+                if let Some(limit) = args.get(i).and_then(|s| s.parse::<u8>().ok()) {
+                    res.timeline.reply_depth_limit = Some(limit);
+                } else {
+                    error!("reply-depth-limit expects a number of replies");
+                }
             } else if arg == "--no-since-optimize" {
                 res.since_optimize = false;
             } else if arg == "--filter" {
@@ -89,6 +261,18 @@ impl ColumnsArgs {
                     res.columns.push(ArgColumn::Timeline(TimelineKind::profile(
                         PubkeySource::DeckAuthor,
                     )))
+                } else if let Some(geohash) = column_name.strip_prefix("geo:") {
+                    if crate::geo::decode_geohash(geohash).is_some() {
+                        info!("got geo column for geohash {}", geohash);
+                        res.columns.push(ArgColumn::Generic(vec![Filter::new()
+                            .kinds([1])
+                            .limit(notedeck::filter::default_limit())
+                            .tags([geohash.to_lowercase()], 'g')
+                            .build()]))
+                    } else {
+                        error!("error parsing geohash {}", geohash);
+                        continue;
+                    }
                 } else if column_name == "universe" {
                     debug!("got universe column");
                     res.columns
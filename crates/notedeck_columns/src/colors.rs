@@ -4,3 +4,4 @@ pub const ALMOST_WHITE: Color32 = Color32::from_rgb(0xFA, 0xFA, 0xFA);
 pub const MID_GRAY: Color32 = Color32::from_rgb(0xbd, 0xbd, 0xbd);
 pub const PINK: Color32 = Color32::from_rgb(0xE4, 0x5A, 0xC9);
 pub const TEAL: Color32 = Color32::from_rgb(0x77, 0xDC, 0xE1);
+pub const GREEN: Color32 = Color32::from_rgb(0x3C, 0xB9, 0x5B);
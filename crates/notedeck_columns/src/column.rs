@@ -1,19 +1,29 @@
 use crate::route::{Route, Router};
-use crate::timeline::{Timeline, TimelineId};
+use crate::timeline::{AnalyticsReport, Timeline, TimelineId};
+use crate::{Error, Result};
 use indexmap::IndexMap;
 use std::iter::Iterator;
 use std::sync::atomic::{AtomicU32, Ordering};
-use tracing::warn;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Clone)]
 pub struct Column {
     router: Router<Route>,
+    /// Pinned columns can't be closed, see [`Columns::pin_column`]
+    pinned: bool,
 }
 
 impl Column {
     pub fn new(routes: Vec<Route>) -> Self {
         let router = Router::new(routes);
-        Column { router }
+        Column {
+            router,
+            pinned: false,
+        }
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
     }
 
     pub fn router(&self) -> &Router<Route> {
@@ -35,8 +45,16 @@ pub struct Columns {
     /// etc.
     pub timelines: IndexMap<u32, Timeline>,
 
+    /// Columns taken out of the layout, see [`Columns::archive_column`].
+    /// Their timelines stay in `timelines`, so they keep updating.
+    archived: IndexMap<u32, Column>,
+
     /// The selected column for key navigation
     selected: i32,
+
+    /// When [`Columns::archive_inactive`] last looked at our columns, in
+    /// unix seconds
+    archive_checked_at: u64,
 }
 static UIDS: AtomicU32 = AtomicU32::new(0);
 
@@ -60,6 +78,34 @@ impl Columns {
         self.timelines.insert(col_id, timeline);
     }
 
+    /// Add a copy of the timeline `id` in a new column right after its
+    /// column, returning the new timeline's id. The copy starts with no
+    /// notes or subscription, callers should set it up with
+    /// [`setup_new_timeline`](crate::timeline::setup_new_timeline).
+    pub fn clone_column(&mut self, id: TimelineId) -> Option<TimelineId> {
+        let (timeline_ind, col_id, source) = self
+            .timelines
+            .iter()
+            .enumerate()
+            .find(|(_, (_, tl))| tl.id == id)
+            .map(|(ind, (col_id, tl))| (ind, *col_id, tl))?;
+
+        let timeline = source.duplicate();
+        let timeline_id = timeline.id;
+        let col_ind = self.columns.get_index_of(&col_id)?;
+
+        let new_id = Self::get_new_id();
+        self.columns.shift_insert(
+            col_ind + 1,
+            new_id,
+            Column::new(vec![Route::timeline(timeline_id)]),
+        );
+        self.timelines
+            .shift_insert(timeline_ind + 1, new_id, timeline);
+
+        Some(timeline_id)
+    }
+
     pub fn new_column_picker(&mut self) {
         self.add_column(Column::new(vec![Route::AddColumn(
             crate::ui::add_column::AddColumnRoute::Base,
@@ -154,6 +200,116 @@ impl Columns {
         self.timelines.values_mut().collect()
     }
 
+    /// Collect the analytics of every column that records them, see
+    /// [`TimelineView::with_column_analytics`](crate::ui::TimelineView::with_column_analytics)
+    pub fn export_analytics(&self) -> AnalyticsReport {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut report = AnalyticsReport::new(now);
+        for timeline in self.timelines.values() {
+            if let Some(analytics) = &timeline.analytics {
+                report.add_column(timeline.kind.to_string(), analytics);
+            }
+        }
+        report
+    }
+
+    /// Columns that were archived, in the order they were archived
+    pub fn archived_columns(&self) -> Vec<&Column> {
+        self.archived.values().collect()
+    }
+
+    /// The timelines of the archived columns
+    pub fn archived_timelines(&self) -> Vec<&Timeline> {
+        self.archived
+            .keys()
+            .filter_map(|col_id| self.timelines.get(col_id))
+            .collect()
+    }
+
+    /// Take the column holding timeline `id` out of the layout. Its
+    /// timeline keeps its subscription, so it's up to date when it's
+    /// brought back with [`Columns::restore_from_archive`]. Pinned
+    /// columns can't be archived.
+    pub fn archive_column(&mut self, id: TimelineId) -> Result<()> {
+        let ind = self
+            .timeline_column_index(id)
+            .ok_or(Error::TimelineNotFound)?;
+        if self.column(ind).is_pinned() {
+            return Err(Error::ColumnPinned);
+        }
+
+        if let Some((col_id, column)) = self.columns.shift_remove_index(ind) {
+            self.archived.insert(col_id, column);
+        }
+
+        // keep the same column selected
+        if self.selected as usize > ind {
+            self.selected -= 1;
+        }
+
+        if self.columns.is_empty() {
+            self.new_column_picker();
+        }
+        self.selected = self.selected.min(self.columns.len() as i32 - 1);
+
+        Ok(())
+    }
+
+    /// Put the archived column holding timeline `id` back at the end of
+    /// the layout
+    pub fn restore_from_archive(&mut self, id: TimelineId) -> Result<()> {
+        let (col_id, timeline) = self
+            .timelines
+            .iter_mut()
+            .find(|(col_id, tl)| tl.id == id && self.archived.contains_key(*col_id))
+            .ok_or(Error::TimelineNotFound)?;
+
+        // otherwise it would be archived again right away
+        timeline.last_active = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(timeline.last_active, |d| d.as_secs());
+
+        let col_id = *col_id;
+        if let Some(column) = self.archived.shift_remove(&col_id) {
+            self.columns.insert(col_id, column);
+        }
+
+        Ok(())
+    }
+
+    /// Archive every column whose timeline has been inactive for longer
+    /// than its [`Timeline::archive_after`]. The selected column and
+    /// pinned columns stay. Inactivity is counted in seconds, so this
+    /// only looks at the columns once a second however often it's called.
+    pub fn archive_inactive(&mut self, now: u64) {
+        if now <= self.archive_checked_at {
+            return;
+        }
+        self.archive_checked_at = now;
+
+        let inactive: Vec<TimelineId> = self
+            .columns
+            .keys()
+            .filter_map(|col_id| self.timelines.get(col_id))
+            .filter(|tl| {
+                tl.archive_after.map_or(false, |after| {
+                    now.saturating_sub(tl.last_active) > after.as_secs()
+                })
+            })
+            .map(|tl| tl.id)
+            .collect();
+
+        for id in inactive {
+            if !self.is_timeline_focused(id) && !self.is_pinned(id) {
+                let _ = self.archive_column(id);
+            }
+        }
+    }
+
     pub fn timelines(&self) -> Vec<&Timeline> {
         self.timelines.values().collect()
     }
@@ -178,12 +334,73 @@ impl Columns {
         self.timelines.get(&col_id)
     }
 
+    pub fn find_timeline_for_column_index_mut(&mut self, ind: usize) -> Option<&mut Timeline> {
+        let col_id = self.get_column_id_at_index(ind);
+        self.timelines.get_mut(&col_id)
+    }
+
+    /// Whether the column holding timeline `id` is the selected column
+    pub fn is_timeline_focused(&self, id: TimelineId) -> bool {
+        self.timeline_column_index(id)
+            .map_or(false, |ind| ind as i32 == self.selected)
+    }
+
+    /// Select the column holding timeline `id`
+    pub fn focus_timeline(&mut self, id: TimelineId) {
+        if let Some(ind) = self.timeline_column_index(id) {
+            self.selected = ind as i32;
+        }
+    }
+
+    /// Pin the column holding timeline `id` so it can't be closed with
+    /// [`Columns::close_column`]
+    pub fn pin_column(&mut self, id: TimelineId) {
+        self.set_pinned(id, true);
+    }
+
+    pub fn unpin_column(&mut self, id: TimelineId) {
+        self.set_pinned(id, false);
+    }
+
+    /// Whether the column holding timeline `id` is pinned
+    pub fn is_pinned(&self, id: TimelineId) -> bool {
+        self.timeline_column_index(id)
+            .map_or(false, |ind| self.column(ind).is_pinned())
+    }
+
+    fn set_pinned(&mut self, id: TimelineId, pinned: bool) {
+        if let Some(ind) = self.timeline_column_index(id) {
+            self.column_mut(ind).pinned = pinned;
+        }
+    }
+
+    fn timeline_column_index(&self, id: TimelineId) -> Option<usize> {
+        let col_id = self
+            .timelines
+            .iter()
+            .find(|(_, tl)| tl.id == id)
+            .map(|(col_id, _)| *col_id)?;
+        self.columns.get_index_of(&col_id)
+    }
+
+    /// The timeline in the selected column, if it has one
+    fn selected_timeline_mut(&mut self) -> Option<&mut Timeline> {
+        let col_id = *self.columns.get_index(self.selected as usize)?.0;
+        self.timelines.get_mut(&col_id)
+    }
+
+    /// Move the note selection of the selected column down a row
     pub fn select_down(&mut self) {
-        warn!("todo: implement select_down");
+        if let Some(timeline) = self.selected_timeline_mut() {
+            timeline.current_view_mut().select_down();
+        }
     }
 
+    /// Move the note selection of the selected column up a row
     pub fn select_up(&mut self) {
-        warn!("todo: implement select_up");
+        if let Some(timeline) = self.selected_timeline_mut() {
+            timeline.current_view_mut().select_up();
+        }
     }
 
     pub fn select_left(&mut self) {
@@ -200,6 +417,22 @@ impl Columns {
         self.selected += 1;
     }
 
+    /// Close the column at `index` unless it's pinned. Use this for
+    /// anything the user asked for, [`Columns::delete_column`] ignores
+    /// pins.
+    pub fn close_column(&mut self, index: usize) -> Result<()> {
+        if self
+            .columns
+            .get_index(index)
+            .map_or(false, |(_, col)| col.is_pinned())
+        {
+            return Err(Error::ColumnPinned);
+        }
+
+        self.delete_column(index);
+        Ok(())
+    }
+
     pub fn delete_column(&mut self, index: usize) {
         if let Some((key, _)) = self.columns.get_index_mut(index) {
             self.timelines.shift_remove(key);
@@ -222,3 +455,50 @@ pub enum ColumnsAction {
     // Switch(usize), TODO: could use for keyboard selection
     Remove(usize),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_and_restore_column() {
+        let mut columns = Columns::new();
+        let first = Timeline::hashtag("nostr".to_string());
+        let second = Timeline::hashtag("zaps".to_string());
+        let (first_id, second_id) = (first.id, second.id);
+        columns.add_new_timeline_column(first);
+        columns.add_new_timeline_column(second);
+
+        columns.pin_column(first_id);
+        assert!(columns.archive_column(first_id).is_err());
+
+        columns.archive_column(second_id).unwrap();
+        assert_eq!(columns.num_columns(), 1);
+        assert_eq!(columns.archived_timelines()[0].id, second_id);
+        // archived timelines keep updating
+        assert!(columns.find_timeline(second_id).is_some());
+
+        columns.restore_from_archive(second_id).unwrap();
+        assert_eq!(columns.num_columns(), 2);
+        assert!(columns.archived_timelines().is_empty());
+        assert!(columns.restore_from_archive(second_id).is_err());
+    }
+
+    #[test]
+    fn test_archive_inactive() {
+        let mut columns = Columns::new();
+        let mut timeline = Timeline::hashtag("nostr".to_string());
+        timeline.archive_after = Some(std::time::Duration::from_secs(60));
+        let last_active = timeline.last_active;
+        let id = timeline.id;
+        columns.add_new_timeline_column(Timeline::hashtag("zaps".to_string()));
+        columns.add_new_timeline_column(timeline);
+
+        columns.archive_inactive(last_active + 60);
+        assert_eq!(columns.num_columns(), 2);
+
+        columns.archive_inactive(last_active + 61);
+        assert_eq!(columns.num_columns(), 1);
+        assert_eq!(columns.archived_timelines()[0].id, id);
+    }
+}
@@ -1,9 +1,12 @@
+use crate::post::PublishStatus;
 use crate::ui::note::PostType;
 use std::collections::HashMap;
 
 #[derive(Default)]
 pub struct Draft {
     pub buffer: String,
+    /// Relay write results for the last note posted from this draft
+    pub publish_status: Option<PublishStatus>,
 }
 
 #[derive(Default)]
@@ -33,6 +36,19 @@ impl Drafts {
     pub fn quote_mut(&mut self, id: &[u8; 32]) -> &mut Draft {
         self.quotes.entry(*id).or_default()
     }
+
+    /// Update the publish status of whichever draft posted `event_id`
+    pub fn handle_ok(&mut self, relay: &str, event_id: &str, accepted: bool, message: &str) {
+        let drafts = std::iter::once(&mut self.compose)
+            .chain(self.replies.values_mut())
+            .chain(self.quotes.values_mut());
+
+        for draft in drafts {
+            if let Some(status) = &mut draft.publish_status {
+                status.handle_ok(relay, event_id, accepted, message);
+            }
+        }
+    }
 }
 
 impl Draft {
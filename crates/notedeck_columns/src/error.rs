@@ -8,6 +8,9 @@ pub enum Error {
     #[error("load failed")]
     LoadFailed,
 
+    #[error("column is pinned")]
+    ColumnPinned,
+
     #[error("network error: {0}")]
     Nostr(#[from] enostr::Error),
 
@@ -0,0 +1,157 @@
+use nostrdb::Note;
+
+const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// Map tiles for geotagged notes. Tiles are fetched through the image
+/// cache like any other note media.
+const TILE_URL: &str = "https://tile.openstreetmap.org";
+
+/// A point decoded from a geohash. `lat_err` and `lon_err` are half the
+/// size of the geohash cell, in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    pub lat: f64,
+    pub lon: f64,
+    pub lat_err: f64,
+    pub lon_err: f64,
+}
+
+/// Decode a geohash into the center of its cell
+pub fn decode_geohash(geohash: &str) -> Option<GeoPoint> {
+    if geohash.is_empty() {
+        return None;
+    }
+
+    let mut lat = (-90.0f64, 90.0f64);
+    let mut lon = (-180.0f64, 180.0f64);
+    let mut even = true;
+
+    for c in geohash.bytes() {
+        let bits = GEOHASH_ALPHABET
+            .iter()
+            .position(|a| *a == c.to_ascii_lowercase())?;
+
+        for shift in (0..5).rev() {
+            let bit = (bits >> shift) & 1 == 1;
+            let range = if even { &mut lon } else { &mut lat };
+            let mid = (range.0 + range.1) / 2.0;
+            if bit {
+                range.0 = mid;
+            } else {
+                range.1 = mid;
+            }
+            even = !even;
+        }
+    }
+
+    Some(GeoPoint {
+        lat: (lat.0 + lat.1) / 2.0,
+        lon: (lon.0 + lon.1) / 2.0,
+        lat_err: (lat.1 - lat.0) / 2.0,
+        lon_err: (lon.1 - lon.0) / 2.0,
+    })
+}
+
+/// The most precise `g` (geohash) tag on a note
+pub fn note_geohash<'a>(note: &Note<'a>) -> Option<&'a str> {
+    let mut best: Option<&'a str> = None;
+
+    for tag in note.tags().iter() {
+        if tag.count() < 2 || tag.get(0).and_then(|t| t.variant().str()) != Some("g") {
+            continue;
+        }
+
+        if let Some(geohash) = tag.get(1).and_then(|t| t.variant().str()) {
+            if best.map_or(true, |b| geohash.len() > b.len()) {
+                best = Some(geohash);
+            }
+        }
+    }
+
+    best.filter(|geohash| decode_geohash(geohash).is_some())
+}
+
+/// A map zoom level that roughly fits a geohash cell of this length
+pub fn zoom_for_geohash(geohash: &str) -> u32 {
+    (geohash.len() as u32 * 5 / 2 + 2).clamp(2, 16)
+}
+
+/// Web mercator tile coordinates for a point. The fractional part is
+/// where the point falls within the tile.
+pub fn tile_coords(point: &GeoPoint, zoom: u32) -> (f64, f64) {
+    let n = f64::from(1u32 << zoom);
+    let lat = point.lat.to_radians();
+    let x = (point.lon + 180.0) / 360.0 * n;
+    let y = (1.0 - lat.tan().asinh() / std::f64::consts::PI) / 2.0 * n;
+    (x, y)
+}
+
+pub fn tile_url(zoom: u32, x: u32, y: u32) -> String {
+    format!("{}/{}/{}/{}.png", TILE_URL, zoom, x, y)
+}
+
+/// A point as degrees north/south and east/west, eg. `57.6°N 10.4°E`,
+/// with only as many decimals as the geohash is precise to
+pub fn format_point(point: &GeoPoint) -> String {
+    let decimals = (-point.lat_err.min(point.lon_err).log10())
+        .ceil()
+        .clamp(0.0, 5.0) as usize;
+    let ns = if point.lat < 0.0 { 'S' } else { 'N' };
+    let ew = if point.lon < 0.0 { 'W' } else { 'E' };
+    format!(
+        "{:.*}°{} {:.*}°{}",
+        decimals,
+        point.lat.abs(),
+        ns,
+        decimals,
+        point.lon.abs(),
+        ew
+    )
+}
+
+/// A link to a full map centered on the point
+pub fn map_url(point: &GeoPoint, zoom: u32) -> String {
+    format!(
+        "https://www.openstreetmap.org/?mlat={lat:.5}&mlon={lon:.5}#map={zoom}/{lat:.5}/{lon:.5}",
+        lat = point.lat,
+        lon = point.lon,
+        zoom = zoom
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_geohash() {
+        let point = decode_geohash("u4pruydqqvj").unwrap();
+        assert!((point.lat - 57.64911).abs() < 0.0001);
+        assert!((point.lon - 10.40744).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_decode_invalid_geohash() {
+        assert_eq!(decode_geohash(""), None);
+        assert_eq!(decode_geohash("u4pa"), None);
+    }
+
+    #[test]
+    fn test_format_point() {
+        let coarse = decode_geohash("u4p").unwrap();
+        assert_eq!(format_point(&coarse), "57.0°N 10.5°E");
+
+        let precise = decode_geohash("u4pruydqqvj").unwrap();
+        assert_eq!(format_point(&precise), "57.64911°N 10.40744°E");
+
+        let south_west = decode_geohash("6gkzw").unwrap();
+        assert_eq!(format_point(&south_west), "25.38°S 49.28°W");
+    }
+
+    #[test]
+    fn test_tile_coords() {
+        let point = decode_geohash("s0000").unwrap();
+        let (x, y) = tile_coords(&point, 1);
+        assert_eq!((x as u32, y as u32), (1, 0));
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use notedeck::{storage, DataPath};
+use serde::{Deserialize, Serialize};
+
+pub static SEEN_HELP_FILE: &str = "seen_help.json";
+
+/// When a [`HelpItem`] is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HelpTrigger {
+    /// The column has notes to show
+    FirstNote,
+    /// The user scrolled down the column
+    FirstScroll,
+    /// The column has no notes yet
+    EmptyColumn,
+}
+
+/// A tip shown in a column when its trigger fires, until the user
+/// dismisses it. See
+/// [`TimelineView::with_contextual_help`](crate::ui::TimelineView::with_contextual_help)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpItem {
+    pub trigger: HelpTrigger,
+    pub message: String,
+}
+
+impl HelpItem {
+    pub fn new(trigger: HelpTrigger, message: impl Into<String>) -> Self {
+        HelpItem {
+            trigger,
+            message: message.into(),
+        }
+    }
+}
+
+/// The help we show in timeline columns
+pub fn timeline_help() -> Vec<HelpItem> {
+    vec![
+        HelpItem::new(
+            HelpTrigger::EmptyColumn,
+            "Notes show up here as relays send them. This can take a moment.",
+        ),
+        HelpItem::new(
+            HelpTrigger::FirstNote,
+            "Click a note to open its thread, or its author's picture to see their profile.",
+        ),
+        HelpItem::new(
+            HelpTrigger::FirstScroll,
+            "New notes arrive at the top. A pill shows how many are waiting above you.",
+        ),
+    ]
+}
+
+/// Help the user dismissed. A dismissed trigger is never shown again.
+#[derive(Default)]
+pub struct SeenHelp {
+    seen: HashSet<HelpTrigger>,
+}
+
+impl SeenHelp {
+    pub fn mark_seen(&mut self, trigger: HelpTrigger) {
+        self.seen.insert(trigger);
+    }
+
+    pub fn is_seen(&self, trigger: HelpTrigger) -> bool {
+        self.seen.contains(&trigger)
+    }
+
+    /// The items the user hasn't dismissed yet
+    pub fn unseen(&self, items: Vec<HelpItem>) -> Vec<HelpItem> {
+        items
+            .into_iter()
+            .filter(|item| !self.is_seen(item.trigger))
+            .collect()
+    }
+
+    pub fn load(path: &DataPath) -> Self {
+        // nothing dismissed yet when there's no file
+        storage::load_json(path, SEEN_HELP_FILE)
+            .map(|seen| SeenHelp { seen })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &DataPath) {
+        storage::save_json(path, SEEN_HELP_FILE, &self.seen);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unseen() {
+        let mut seen = SeenHelp::default();
+        seen.mark_seen(HelpTrigger::FirstNote);
+
+        let triggers: Vec<HelpTrigger> = seen
+            .unseen(timeline_help())
+            .into_iter()
+            .map(|item| item.trigger)
+            .collect();
+        assert_eq!(
+            triggers,
+            vec![HelpTrigger::EmptyColumn, HelpTrigger::FirstScroll]
+        );
+    }
+}
@@ -1,10 +1,12 @@
 use egui::{pos2, Color32, ColorImage, Rect, Sense, SizeHint, TextureHandle};
+use image::error::{ImageFormatHint, UnsupportedError, UnsupportedErrorKind};
 use image::imageops::FilterType;
-use notedeck::ImageCache;
 use notedeck::Result;
+use notedeck::{GifUrls, ImageCache, ResolvedUrl};
 use poll_promise::Promise;
 use std::path;
 use tokio::fs;
+use tracing::error;
 
 //pub type ImageCacheKey = String;
 //pub type ImageCacheValue = Promise<Result<TextureHandle>>;
@@ -168,7 +170,14 @@ fn parse_img_response(response: ehttp::Response, imgtyp: ImageType) -> Result<Co
         let mut dyn_image = image::load_from_memory(&response.bytes)?;
         Ok(process_pfp_bitmap(imgtyp, &mut dyn_image))
     } else {
-        Err(format!("Expected image, found content-type {:?}", content_type).into())
+        // not something we can decode, eg. an html error page
+        let hint = ImageFormatHint::Name(content_type.to_owned());
+        Err(notedeck::Error::Image(image::ImageError::Unsupported(
+            UnsupportedError::from_format_and_kind(
+                hint.clone(),
+                UnsupportedErrorKind::Format(hint),
+            ),
+        )))
     }
 }
 
@@ -219,7 +228,7 @@ pub fn fetch_img(
     if path.exists() {
         fetch_img_from_disk(ctx, url, &path)
     } else {
-        fetch_img_from_net(&img_cache.cache_dir, ctx, url, imgtyp)
+        fetch_img_from_net(&img_cache.cache_dir, img_cache.gif_urls(), ctx, url, imgtyp)
     }
 
     // TODO: fetch image from local cache
@@ -227,6 +236,7 @@ pub fn fetch_img(
 
 fn fetch_img_from_net(
     cache_path: &path::Path,
+    gif_urls: GifUrls,
     ctx: &egui::Context,
     url: &str,
     imgtyp: ImageType,
@@ -239,6 +249,14 @@ fn fetch_img_from_net(
     ehttp::fetch(request, move |response| {
         let handle = response
             .map_err(notedeck::Error::Generic)
+            .map(|resp| {
+                if resp.content_type() == Some("image/gif") {
+                    if let Ok(mut gif_urls) = gif_urls.lock() {
+                        gif_urls.insert(cloned_url.clone());
+                    }
+                }
+                resp
+            })
             .and_then(|resp| parse_img_response(resp, imgtyp))
             .map(|img| {
                 let texture_handle = ctx.load_texture(&cloned_url, img.clone(), Default::default());
@@ -255,3 +273,41 @@ fn fetch_img_from_net(
 
     promise
 }
+
+/// Follow a shortened url's redirects to find where it really goes. The
+/// result lands in the image cache, see [`ImageCache::resolved_url`]
+pub fn resolve_url(img_cache: &mut ImageCache, ctx: &egui::Context, url: &str) {
+    let now = ctx.input(|i| i.time);
+    if !img_cache.should_resolve(url, now) {
+        return;
+    }
+    img_cache.start_resolving(url, now);
+
+    // we only want the location, not the page
+    let request = ehttp::Request {
+        method: "HEAD".to_owned(),
+        ..ehttp::Request::get(url)
+    };
+    let resolved_urls = img_cache.resolved_urls();
+    let ctx = ctx.clone();
+    let short_url = url.to_owned();
+    ehttp::fetch(request, move |response| match response {
+        Ok(resp) if resp.ok => {
+            if let Ok(mut resolved_urls) = resolved_urls.lock() {
+                resolved_urls.insert(
+                    short_url,
+                    ResolvedUrl {
+                        url: Some(resp.url),
+                        at: now,
+                    },
+                );
+            }
+            ctx.request_repaint();
+        }
+        Ok(resp) => error!(
+            "could not resolve {}: {} {}",
+            short_url, resp.status, resp.status_text
+        ),
+        Err(err) => error!("could not resolve {}: {}", short_url, err),
+    });
+}
@@ -15,6 +15,8 @@ mod deck_state;
 mod decks;
 mod draft;
 mod frame_history;
+mod geo;
+mod help;
 mod images;
 mod key_parsing;
 pub mod login_manager;
@@ -26,6 +28,8 @@ mod profile;
 mod profile_state;
 pub mod relay_pool_manager;
 mod route;
+mod scheduler;
+mod snooze;
 mod subscriptions;
 mod support;
 mod test_data;
@@ -2,18 +2,23 @@ use crate::{
     accounts::render_accounts_route,
     actionbar::NoteAction,
     app::{get_active_columns, get_active_columns_mut, get_decks_mut},
-    column::ColumnsAction,
+    column::{Columns, ColumnsAction},
     deck_state::DeckState,
     decks::{Deck, DecksAction, DecksCache},
+    images,
     notes_holder::NotesHolder,
     profile::{Profile, ProfileAction, SaveProfileChanges},
     profile_state::ProfileState,
     relay_pool_manager::RelayPoolManager,
     route::Route,
+    scheduler::ScheduledNote,
+    storage,
     thread::Thread,
     timeline::{
+        csv,
         route::{render_timeline_route, TimelineRoute},
-        Timeline,
+        send_initial_timeline_filter, setup_new_timeline, Timeline, TimelineId, TimelineKind,
+        TimelineSnapshot, ViewFilter,
     },
     ui::{
         self,
@@ -21,15 +26,17 @@ use crate::{
         column::NavTitle,
         configure_deck::ConfigureDeckView,
         edit_deck::{EditDeckResponse, EditDeckView},
+        media_viewer::MediaViewerState,
         note::{PostAction, PostType},
         profile::EditProfileView,
+        scheduled::ScheduledView,
         support::SupportView,
         RelayView, View,
     },
     Damus,
 };
 
-use notedeck::{AccountsAction, AppContext};
+use notedeck::{unix_now, AccountsAction, AppContext};
 
 use egui_nav::{Nav, NavAction, NavResponse, NavUiType};
 use nostrdb::{Ndb, Transaction};
@@ -39,8 +46,18 @@ use tracing::{error, info};
 pub enum RenderNavAction {
     Back,
     RemoveColumn,
+    SnapshotTimeline(TimelineId),
+    ExportTimelineCsv(TimelineId),
+    AddTimelineView(TimelineId, ViewFilter),
+    ExportAnalytics,
+    SaveWorkspace(String),
+    LoadWorkspace(String),
+    CloneColumn(TimelineId),
     PostAction(PostAction),
     NoteAction(NoteAction),
+    /// Several note actions from the same frame, see
+    /// [`TimelineResponse`](crate::ui::TimelineResponse)
+    NoteActions(Vec<NoteAction>),
     ProfileAction(ProfileAction),
     SwitchingAction(SwitchingAction),
 }
@@ -70,7 +87,11 @@ impl SwitchingAction {
             },
             SwitchingAction::Columns(columns_action) => match *columns_action {
                 ColumnsAction::Remove(index) => {
-                    get_active_columns_mut(ctx.accounts, decks_cache).delete_column(index)
+                    if let Err(err) =
+                        get_active_columns_mut(ctx.accounts, decks_cache).close_column(index)
+                    {
+                        error!("could not remove column {index}: {err}");
+                    }
                 }
             },
             SwitchingAction::Decks(decks_action) => match *decks_action {
@@ -112,7 +133,12 @@ impl RenderNavResponse {
     }
 
     #[must_use = "Make sure to save columns if result is true"]
-    pub fn process_render_nav_response(&self, app: &mut Damus, ctx: &mut AppContext<'_>) -> bool {
+    pub fn process_render_nav_response(
+        &self,
+        app: &mut Damus,
+        ctx: &mut AppContext<'_>,
+        egui_ctx: &egui::Context,
+    ) -> bool {
         let mut switching_occured: bool = false;
         let col = self.column;
 
@@ -132,39 +158,167 @@ impl RenderNavResponse {
                 }
 
                 RenderNavAction::RemoveColumn => {
-                    let tl = app
-                        .columns(ctx.accounts)
-                        .find_timeline_for_column_index(col);
-                    if let Some(timeline) = tl {
-                        unsubscribe_timeline(ctx.ndb, timeline);
+                    if app.columns(ctx.accounts).column(col).is_pinned() {
+                        error!("not removing pinned column {col}");
+                    } else {
+                        let tl = app
+                            .columns(ctx.accounts)
+                            .find_timeline_for_column_index(col);
+                        if let Some(timeline) = tl {
+                            unsubscribe_timeline(ctx.ndb, timeline);
+                        }
+
+                        if let Err(err) = app.columns_mut(ctx.accounts).close_column(col) {
+                            error!("could not remove column {col}: {err}");
+                        }
+                        switching_occured = true;
                     }
+                }
 
-                    app.columns_mut(ctx.accounts).delete_column(col);
-                    switching_occured = true;
+                RenderNavAction::SnapshotTimeline(timeline_id) => {
+                    if let Some(timeline) = app.columns(ctx.accounts).find_timeline(*timeline_id) {
+                        let relays = ctx.pool.urls().into_iter().collect();
+                        if let Err(err) = TimelineSnapshot::new(timeline, ctx.ndb, relays)
+                            .and_then(|snapshot| snapshot.save(ctx.path, timeline))
+                        {
+                            error!("failed to snapshot timeline {}: {}", timeline_id, err);
+                        }
+                    }
+                }
+
+                RenderNavAction::ExportTimelineCsv(timeline_id) => {
+                    let columns = get_active_columns_mut(ctx.accounts, &mut app.decks_cache);
+                    if let Some(timeline) = columns.find_timeline_mut(*timeline_id) {
+                        match csv::export_timeline_csv(
+                            timeline,
+                            ctx.ndb,
+                            ctx.note_cache,
+                            &app.relay_counts,
+                            ctx.path,
+                        ) {
+                            Ok(file) => timeline.csv_export = Some(file),
+                            Err(err) => {
+                                error!("failed to export timeline {}: {}", timeline_id, err)
+                            }
+                        }
+                    }
+                }
+
+                RenderNavAction::AddTimelineView(timeline_id, filter) => {
+                    let txn = Transaction::new(ctx.ndb).expect("txn");
+                    let columns = get_active_columns_mut(ctx.accounts, &mut app.decks_cache);
+                    if let Some(timeline) = columns.find_timeline_mut(*timeline_id) {
+                        timeline.add_view(*filter, ctx.ndb, ctx.note_cache, &txn);
+                    }
+                }
+
+                RenderNavAction::ExportAnalytics => {
+                    let report = app.columns(ctx.accounts).export_analytics();
+                    if let Err(err) = report.save(ctx.path) {
+                        error!("failed to export column analytics: {}", err);
+                    }
+                }
+
+                RenderNavAction::SaveWorkspace(name) => {
+                    let path = storage::workspace_path(ctx.path, name);
+                    if let Err(err) = app.columns(ctx.accounts).serialize_to_file(&path) {
+                        error!("failed to save workspace '{}': {}", name, err);
+                    }
+                }
+
+                RenderNavAction::LoadWorkspace(name) => {
+                    let path = storage::workspace_path(ctx.path, name);
+                    let deck_user = *ctx
+                        .accounts
+                        .get_selected_account()
+                        .map_or_else(|| app.decks_cache.get_fallback_pubkey(), |acc| &acc.pubkey)
+                        .bytes();
+
+                    match Columns::deserialize_from_file(&path, ctx.ndb, &deck_user) {
+                        Ok(mut columns) => {
+                            for timeline in columns.timelines_mut() {
+                                setup_new_timeline(
+                                    timeline,
+                                    ctx.ndb,
+                                    &mut app.subscriptions,
+                                    ctx.pool,
+                                    ctx.note_cache,
+                                    app.since_optimize,
+                                    ctx.accounts
+                                        .get_selected_account()
+                                        .as_ref()
+                                        .map(|sa| &sa.pubkey),
+                                );
+                            }
+
+                            let old = std::mem::replace(
+                                get_active_columns_mut(ctx.accounts, &mut app.decks_cache),
+                                columns,
+                            );
+                            for timeline in old.timelines() {
+                                unsubscribe_timeline(ctx.ndb, timeline);
+                            }
+                            switching_occured = true;
+                        }
+
+                        // keep the current layout and tell the user why
+                        Err(err) => {
+                            error!("failed to load workspace '{}': {}", name, err);
+                            app.view_state.workspace_error =
+                                Some(format!("Could not load workspace '{}': {}", name, err));
+                        }
+                    }
+                }
+
+                RenderNavAction::CloneColumn(timeline_id) => {
+                    let columns = get_active_columns_mut(ctx.accounts, &mut app.decks_cache);
+                    let timeline = columns
+                        .clone_column(*timeline_id)
+                        .and_then(|new_id| columns.find_timeline_mut(new_id));
+
+                    if let Some(timeline) = timeline {
+                        setup_new_timeline(
+                            timeline,
+                            ctx.ndb,
+                            &mut app.subscriptions,
+                            ctx.pool,
+                            ctx.note_cache,
+                            app.since_optimize,
+                            ctx.accounts
+                                .get_selected_account()
+                                .as_ref()
+                                .map(|sa| &sa.pubkey),
+                        );
+                        switching_occured = true;
+                    } else {
+                        error!("failed to clone column for timeline {}", timeline_id);
+                    }
                 }
 
                 RenderNavAction::PostAction(post_action) => {
                     let txn = Transaction::new(ctx.ndb).expect("txn");
-                    let _ = post_action.execute(ctx.ndb, &txn, ctx.pool, &mut app.drafts);
-                    get_active_columns_mut(ctx.accounts, &mut app.decks_cache)
+                    if let Err(err) = post_action.execute(ctx.ndb, &txn, ctx.pool, &mut app.drafts)
+                    {
+                        error!("failed to post: {err}");
+                    }
+
+                    // where the note landed is shown the next time the
+                    // composer is opened
+                    app.columns_mut(ctx.accounts)
                         .column_mut(col)
                         .router_mut()
                         .go_back();
                 }
 
                 RenderNavAction::NoteAction(note_action) => {
-                    let txn = Transaction::new(ctx.ndb).expect("txn");
+                    switching_occured = process_note_action(app, ctx, egui_ctx, col, note_action);
+                }
 
-                    note_action.execute_and_process_result(
-                        ctx.ndb,
-                        get_active_columns_mut(ctx.accounts, &mut app.decks_cache),
-                        col,
-                        &mut app.threads,
-                        &mut app.profiles,
-                        ctx.note_cache,
-                        ctx.pool,
-                        &txn,
-                    );
+                RenderNavAction::NoteActions(note_actions) => {
+                    for note_action in note_actions {
+                        switching_occured |=
+                            process_note_action(app, ctx, egui_ctx, col, note_action);
+                    }
                 }
 
                 RenderNavAction::SwitchingAction(switching_action) => {
@@ -265,7 +419,11 @@ fn render_nav_body(
             ctx.accounts,
             *tlr,
             col,
-            app.textmode,
+            app.note_options(),
+            app.debug,
+            &app.timeline_settings,
+            &app.snoozes,
+            &app.seen_help,
             ui,
         ),
         Route::Accounts(amr) => {
@@ -305,7 +463,10 @@ fn render_nav_body(
             )
             .ui(&txn, ui);
 
-            post_response.action.map(Into::into)
+            post_response
+                .schedule
+                .map(Into::into)
+                .or(post_response.action.map(Into::into))
         }
         Route::AddColumn(route) => {
             render_add_column_routes(ui, app, ctx, col, route);
@@ -316,6 +477,14 @@ fn render_nav_body(
             SupportView::new(&mut app.support).show(ui);
             None
         }
+        Route::Scheduled => {
+            let cancel = ScheduledView::new(&app.scheduler, unix_now()).show(ui);
+            if let Some(ind) = cancel {
+                app.scheduler.cancel(ind);
+                app.scheduler.save(ctx.path);
+            }
+            None
+        }
         Route::NewDeck => {
             let id = ui.id().with("new-deck");
             let new_deck_state = app.view_state.id_to_deck_state.entry(id).or_default();
@@ -410,6 +579,148 @@ fn render_nav_body(
     }
 }
 
+/// Handle a note action from column `col`, returning whether we switched
+/// columns because of it
+fn process_note_action(
+    app: &mut Damus,
+    ctx: &mut AppContext<'_>,
+    egui_ctx: &egui::Context,
+    col: usize,
+    note_action: &NoteAction,
+) -> bool {
+    let mut switching_occured = false;
+
+    match note_action {
+        NoteAction::AddRelay(relay_url) => {
+            app.view_state.pending_relay = Some(relay_url.clone());
+        }
+
+        NoteAction::PlayMedia(url) => {
+            ctx.img_cache.play(url.clone());
+        }
+
+        NoteAction::ShowMediaViewer(images) => {
+            app.view_state.media_viewer = Some(MediaViewerState::new(images.clone()));
+        }
+
+        NoteAction::SnoozeThread(note_id, secs) => {
+            let txn = Transaction::new(ctx.ndb).expect("txn");
+            let root = notedeck::note::root_note_id_from_selected_id(
+                ctx.ndb,
+                ctx.note_cache,
+                &txn,
+                note_id.bytes(),
+            );
+            app.snoozes.snooze(*root, *secs, unix_now());
+            app.snoozes.save(ctx.path);
+        }
+
+        NoteAction::ResolveUrl(url) => {
+            images::resolve_url(ctx.img_cache, egui_ctx, url);
+        }
+
+        NoteAction::CopyEventJson(note_key) => {
+            let txn = Transaction::new(ctx.ndb).expect("txn");
+            match ctx.ndb.get_note_by_key(&txn, *note_key) {
+                Ok(note) => match note.json() {
+                    Ok(json) => egui_ctx.copy_text(json),
+                    Err(err) => error!("error copying note json: {err}"),
+                },
+                Err(err) => error!("could not find note to copy: {err}"),
+            }
+        }
+
+        NoteAction::DismissHelp(trigger) => {
+            app.seen_help.mark_seen(*trigger);
+            app.seen_help.save(ctx.path);
+        }
+
+        NoteAction::ScheduleNote {
+            content,
+            publish_at,
+        } => {
+            if let Some(acc) = ctx.accounts.get_selected_account() {
+                app.scheduler.schedule(ScheduledNote {
+                    author: acc.pubkey,
+                    content: content.clone(),
+                    publish_at: *publish_at,
+                });
+                app.scheduler.save(ctx.path);
+                app.drafts.compose_mut().clear();
+            }
+        }
+
+        NoteAction::OpenHashtagColumn(hashtag) => {
+            let columns = get_active_columns_mut(ctx.accounts, &mut app.decks_cache);
+            let existing = columns
+                .timelines()
+                .into_iter()
+                .find(|tl| matches!(&tl.kind, TimelineKind::Hashtag(ht) if ht == hashtag))
+                .map(|tl| tl.id);
+
+            if let Some(timeline_id) = existing {
+                columns.focus_timeline(timeline_id);
+            } else {
+                let mut timeline = Timeline::hashtag(hashtag.clone());
+                setup_new_timeline(
+                    &mut timeline,
+                    ctx.ndb,
+                    &mut app.subscriptions,
+                    ctx.pool,
+                    ctx.note_cache,
+                    app.since_optimize,
+                    ctx.accounts
+                        .get_selected_account()
+                        .as_ref()
+                        .map(|sa| &sa.pubkey),
+                );
+                columns.add_new_timeline_column(timeline);
+                switching_occured = true;
+            }
+        }
+
+        NoteAction::FocusColumn(timeline_id) => {
+            get_active_columns_mut(ctx.accounts, &mut app.decks_cache).focus_timeline(*timeline_id);
+        }
+
+        NoteAction::RefreshTimeline(timeline_id) => {
+            if let Some(timeline) = get_active_columns_mut(ctx.accounts, &mut app.decks_cache)
+                .find_timeline_mut(*timeline_id)
+            {
+                for relay in &mut ctx.pool.relays {
+                    send_initial_timeline_filter(
+                        ctx.ndb,
+                        app.since_optimize,
+                        &mut app.subscriptions,
+                        relay,
+                        timeline,
+                    );
+                }
+            }
+        }
+
+        // emitted for every note as it scrolls in, nothing to do here
+        NoteAction::Seen(_note_key) => {}
+
+        _ => {
+            let txn = Transaction::new(ctx.ndb).expect("txn");
+
+            note_action.clone().execute_and_process_result(
+                ctx.ndb,
+                get_active_columns_mut(ctx.accounts, &mut app.decks_cache),
+                col,
+                &mut app.threads,
+                &mut app.profiles,
+                ctx.note_cache,
+                ctx.pool,
+                &txn,
+            );
+        }
+    }
+
+    switching_occured
+}
+
 #[must_use = "RenderNavResponse must be handled by calling .process_render_nav_response(..)"]
 pub fn render_nav(
     col: usize,
@@ -448,6 +759,7 @@ pub fn render_nav(
             ctx.accounts.get_selected_account().map(|a| &a.pubkey),
             nav.routes(),
         )
+        .advanced(app.debug)
         .show(ui),
         NavUiType::Body => render_nav_body(ui, app, ctx, nav.routes().last().expect("top"), col),
     });
@@ -456,7 +768,22 @@ pub fn render_nav(
 }
 
 fn unsubscribe_timeline(ndb: &mut Ndb, timeline: &Timeline) {
-    if let Some(sub_id) = timeline.subscription {
+    let mut subs: Vec<_> = timeline
+        .subscription
+        .into_iter()
+        .chain(
+            timeline
+                .views
+                .iter()
+                .filter_map(|view| view.subscription_id),
+        )
+        .collect();
+
+    // tabs usually share the timeline's subscription
+    subs.sort_by_key(|sub| sub.id());
+    subs.dedup_by_key(|sub| sub.id());
+
+    for sub_id in subs {
         if let Err(e) = ndb.unsubscribe(sub_id) {
             error!("unsubscribe error: {}", e);
         } else {
@@ -1,13 +1,14 @@
 use enostr::FullKeypair;
 use nostrdb::{Note, NoteBuilder, NoteReply};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
 
 pub struct NewPost {
     pub content: String,
     pub account: FullKeypair,
 }
 
-fn add_client_tag(builder: NoteBuilder<'_>) -> NoteBuilder<'_> {
+pub(crate) fn add_client_tag(builder: NoteBuilder<'_>) -> NoteBuilder<'_> {
     builder
         .start_tag()
         .tag_str("client")
@@ -120,3 +121,119 @@ impl NewPost {
             .expect("expected build to work")
     }
 }
+
+/// How long we wait for a relay to accept a note before warning the user
+pub const PUBLISH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RelayWriteStatus {
+    Pending,
+    Accepted,
+    Rejected(String),
+}
+
+/// Per-relay write results for a note we published, filled in as the
+/// relays' OK messages (NIP-20) come in
+#[derive(Debug)]
+pub struct PublishStatus {
+    note_id: String,
+    sent_at: Instant,
+    relays: BTreeMap<String, RelayWriteStatus>,
+}
+
+impl PublishStatus {
+    pub fn new(note_id: &[u8; 32], relays: impl IntoIterator<Item = String>) -> Self {
+        PublishStatus {
+            note_id: hex::encode(note_id),
+            sent_at: Instant::now(),
+            relays: relays
+                .into_iter()
+                .map(|relay| (relay, RelayWriteStatus::Pending))
+                .collect(),
+        }
+    }
+
+    /// Record a relay's OK response. Responses for other notes are ignored.
+    pub fn handle_ok(&mut self, relay: &str, event_id: &str, accepted: bool, message: &str) {
+        if event_id != self.note_id {
+            return;
+        }
+
+        let status = if accepted {
+            RelayWriteStatus::Accepted
+        } else {
+            RelayWriteStatus::Rejected(message.to_owned())
+        };
+
+        self.relays.insert(relay.to_owned(), status);
+    }
+
+    pub fn relays(&self) -> impl Iterator<Item = (&str, &RelayWriteStatus)> {
+        self.relays
+            .iter()
+            .map(|(relay, status)| (relay.as_str(), status))
+    }
+
+    pub fn num_accepted(&self) -> usize {
+        self.relays
+            .values()
+            .filter(|status| **status == RelayWriteStatus::Accepted)
+            .count()
+    }
+
+    /// No relay accepted our note within [`PUBLISH_TIMEOUT`]
+    pub fn timed_out(&self) -> bool {
+        self.num_accepted() == 0 && self.sent_at.elapsed() > PUBLISH_TIMEOUT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn publish_status() -> PublishStatus {
+        PublishStatus::new(
+            &[1; 32],
+            ["wss://a.relay".to_owned(), "wss://b.relay".to_owned()],
+        )
+    }
+
+    #[test]
+    fn test_publish_status_handle_ok() {
+        let mut status = publish_status();
+        let note_id = hex::encode([1; 32]);
+
+        // OKs for other notes are ignored
+        status.handle_ok("wss://a.relay", &hex::encode([2; 32]), true, "");
+        assert_eq!(status.num_accepted(), 0);
+
+        status.handle_ok("wss://a.relay", &note_id, true, "");
+        status.handle_ok("wss://b.relay", &note_id, false, "blocked: spam");
+        assert_eq!(status.num_accepted(), 1);
+
+        let relays: Vec<_> = status.relays().collect();
+        assert_eq!(
+            relays,
+            vec![
+                ("wss://a.relay", &RelayWriteStatus::Accepted),
+                (
+                    "wss://b.relay",
+                    &RelayWriteStatus::Rejected("blocked: spam".to_owned())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_publish_status_timeout() {
+        let mut status = publish_status();
+        assert!(!status.timed_out());
+
+        status.sent_at = Instant::now() - PUBLISH_TIMEOUT - Duration::from_secs(1);
+        assert!(status.timed_out());
+
+        // one accepting relay is enough
+        status.handle_ok("wss://a.relay", &hex::encode([1; 32]), true, "");
+        assert!(!status.timed_out());
+    }
+}
@@ -1,8 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use enostr::{Filter, FullKeypair, Pubkey, RelayPool};
 use nostrdb::{
-    FilterBuilder, Ndb, Note, NoteBuildOptions, NoteBuilder, ProfileRecord, Transaction,
+    FilterBuilder, Ndb, Note, NoteBuildOptions, NoteBuilder, ProfileRecord, Subscription,
+    Transaction,
 };
 
 use notedeck::{filter::default_limit, FilterState, NoteCache, NoteRef};
@@ -78,9 +79,109 @@ pub fn get_display_name<'a>(record: Option<&ProfileRecord<'a>>) -> NostrName<'a>
     }
 }
 
+/// Follow counts shown in the profile header
+#[derive(Debug, Clone, Copy)]
+pub struct FollowCounts {
+    /// How many accounts this profile follows, from their latest contact
+    /// list. `None` if we don't have their contact list
+    pub following: Option<u32>,
+
+    /// How many contact lists we have locally that follow this profile.
+    /// This is only what we've seen, so it's an estimate at best
+    pub followers_estimate: u32,
+}
+
+/// Keeps a profile's [`FollowCounts`] up to date. The local contact lists
+/// are counted once, after that we only look at the ones that arrive.
+struct FollowTracker {
+    /// When the contact list `following` was counted from was created
+    following: Option<(u64, u32)>,
+    followers: HashSet<[u8; 32]>,
+    /// Contact lists by or following the profile
+    sub: Option<Subscription>,
+}
+
+impl FollowTracker {
+    fn new(ndb: &Ndb, txn: &Transaction, pubkey: &[u8; 32]) -> Self {
+        let filters = follow_filters(pubkey);
+        let sub = ndb.subscribe(&filters).ok();
+
+        let mut tracker = FollowTracker {
+            following: None,
+            followers: HashSet::new(),
+            sub,
+        };
+
+        let limit = 10_000;
+        if let Ok(results) = ndb.query(txn, &filters, limit) {
+            for result in &results {
+                tracker.add_contact_list(&result.note, pubkey);
+            }
+        }
+
+        tracker
+    }
+
+    /// Count the contact lists that arrived since we last looked
+    fn poll(&mut self, ndb: &Ndb, txn: &Transaction, pubkey: &[u8; 32]) {
+        let sub = if let Some(sub) = self.sub {
+            sub
+        } else {
+            return;
+        };
+
+        for note_key in ndb.poll_for_notes(sub, 100) {
+            if let Ok(note) = ndb.get_note_by_key(txn, note_key) {
+                self.add_contact_list(&note, pubkey);
+            }
+        }
+    }
+
+    fn add_contact_list(&mut self, note: &Note, pubkey: &[u8; 32]) {
+        let mut count = 0;
+        let mut follows_pubkey = false;
+        for tag in note.tags() {
+            if tag.count() < 2 || tag.get_unchecked(0).variant().str() != Some("p") {
+                continue;
+            }
+            count += 1;
+            follows_pubkey |= tag.get_unchecked(1).variant().id() == Some(pubkey);
+        }
+
+        if note.pubkey() == pubkey {
+            let created_at = note.created_at();
+            if self.following.map_or(true, |(at, _)| at <= created_at) {
+                self.following = Some((created_at, count));
+            }
+        } else if follows_pubkey {
+            self.followers.insert(*note.pubkey());
+        }
+    }
+
+    fn counts(&self) -> FollowCounts {
+        FollowCounts {
+            following: self.following.map(|(_, count)| count),
+            followers_estimate: self.followers.len() as u32,
+        }
+    }
+}
+
+/// Contact lists (kind 3) by `pubkey`, and the ones that follow them
+fn follow_filters(pubkey: &[u8; 32]) -> Vec<Filter> {
+    vec![
+        Filter::new().authors([pubkey]).kinds([3]).limit(1).build(),
+        Filter::new()
+            .pubkeys([pubkey])
+            .kinds([3])
+            .limit(10_000)
+            .build(),
+    ]
+}
+
 pub struct Profile {
     pub timeline: Timeline,
     pub multi_subscriber: Option<MultiSubscriber>,
+    follow_counts: Option<FollowTracker>,
 }
 
 impl Profile {
@@ -103,9 +204,24 @@ impl Profile {
         Profile {
             timeline,
             multi_subscriber: None,
+            follow_counts: None,
         }
     }
 
+    /// Follow counts for this profile, updated as contact lists arrive
+    pub fn follow_counts(
+        &mut self,
+        ndb: &Ndb,
+        txn: &Transaction,
+        pubkey: &[u8; 32],
+    ) -> FollowCounts {
+        let tracker = self
+            .follow_counts
+            .get_or_insert_with(|| FollowTracker::new(ndb, txn, pubkey));
+        tracker.poll(ndb, txn, pubkey);
+        tracker.counts()
+    }
+
     fn filters_raw(pk: &[u8; 32]) -> Vec<FilterBuilder> {
         vec![Filter::new()
             .authors([pk])
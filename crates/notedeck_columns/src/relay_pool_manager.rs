@@ -28,6 +28,17 @@ impl<'a> RelayPoolManager<'a> {
             .collect()
     }
 
+    /// True when there are no relays we're connected or connecting to, so
+    /// nothing will show up in our columns
+    pub fn is_offline(&self) -> bool {
+        !self.pool.relays.iter().any(|relay| {
+            matches!(
+                relay.status(),
+                RelayStatus::Connected | RelayStatus::Connecting
+            )
+        })
+    }
+
     /// index of the Vec<RelayInfo> from get_relay_infos
     pub fn remove_relay(&mut self, index: usize) {
         if index < self.pool.relays.len() {
@@ -18,6 +18,7 @@ pub enum Route {
     AddColumn(AddColumnRoute),
     EditProfile(Pubkey),
     Support,
+    Scheduled,
     NewDeck,
     EditDeck(usize),
 }
@@ -103,6 +104,7 @@ impl Route {
                 }
             },
             Route::Support => ColumnTitle::simple("Damus Support"),
+            Route::Scheduled => ColumnTitle::simple("Scheduled"),
             Route::NewDeck => ColumnTitle::simple("Add Deck"),
             Route::EditDeck(_) => ColumnTitle::simple("Edit Deck"),
             Route::EditProfile(_) => ColumnTitle::simple("Edit Profile"),
@@ -215,6 +217,7 @@ impl fmt::Display for Route {
 
             Route::AddColumn(_) => write!(f, "Add Column"),
             Route::Support => write!(f, "Support"),
+            Route::Scheduled => write!(f, "Scheduled"),
             Route::NewDeck => write!(f, "Add Deck"),
             Route::EditDeck(_) => write!(f, "Edit Deck"),
             Route::EditProfile(_) => write!(f, "Edit Profile"),
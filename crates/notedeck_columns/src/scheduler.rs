@@ -0,0 +1,145 @@
+use enostr::{ClientMessage, Pubkey, RelayPool};
+use notedeck::{storage, Accounts, DataPath};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::post::NewPost;
+
+pub static SCHEDULED_NOTES_FILE: &str = "scheduled_notes.json";
+
+/// Delays offered when scheduling a note from the composer, in seconds
+pub const SCHEDULE_DELAYS: [(&str, u64); 4] = [
+    ("In 15 minutes", 15 * 60),
+    ("In 1 hour", 60 * 60),
+    ("In 8 hours", 8 * 60 * 60),
+    ("Tomorrow", 24 * 60 * 60),
+];
+
+/// A note waiting to be signed and published. We keep the content
+/// rather than a signed event so the note gets its real `created_at`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledNote {
+    pub author: Pubkey,
+    pub content: String,
+    /// Unix time to publish at
+    pub publish_at: u64,
+}
+
+/// Notes the user scheduled for later, soonest first
+#[derive(Default)]
+pub struct Scheduler {
+    queue: Vec<ScheduledNote>,
+}
+
+impl Scheduler {
+    pub fn schedule(&mut self, note: ScheduledNote) {
+        let ind = self
+            .queue
+            .partition_point(|n| n.publish_at <= note.publish_at);
+        self.queue.insert(ind, note);
+    }
+
+    pub fn cancel(&mut self, ind: usize) -> Option<ScheduledNote> {
+        (ind < self.queue.len()).then(|| self.queue.remove(ind))
+    }
+
+    pub fn notes(&self) -> &[ScheduledNote] {
+        &self.queue
+    }
+
+    /// When the next note is due, if any
+    pub fn next_due(&self) -> Option<u64> {
+        self.queue.first().map(|n| n.publish_at)
+    }
+
+    /// Remove and return the notes due at `now`
+    fn take_due(&mut self, now: u64) -> Vec<ScheduledNote> {
+        let due = self.queue.partition_point(|n| n.publish_at <= now);
+        self.queue.drain(..due).collect()
+    }
+
+    /// Sign and send every note that is due. Notes whose author is no
+    /// longer one of our accounts with a secret key are dropped.
+    pub fn publish_due(
+        &mut self,
+        accounts: &Accounts,
+        pool: &mut RelayPool,
+        path: &DataPath,
+        now: u64,
+    ) {
+        let due = self.take_due(now);
+        if due.is_empty() {
+            return;
+        }
+
+        for note in due {
+            let kp = if let Some(kp) = accounts.get_full(note.author.bytes()) {
+                kp
+            } else {
+                error!(
+                    "dropping scheduled note, no secret key for {}",
+                    note.author.hex()
+                );
+                continue;
+            };
+
+            let seckey = kp.secret_key.to_secret_bytes();
+            let event = NewPost::new(note.content, kp.to_full()).to_note(&seckey);
+            match ClientMessage::event(event) {
+                Ok(msg) => {
+                    info!("publishing scheduled note");
+                    pool.send(&msg);
+                }
+                Err(err) => error!("failed to publish scheduled note: {err}"),
+            }
+        }
+
+        self.save(path);
+    }
+
+    pub fn load(path: &DataPath) -> Self {
+        let mut scheduler = Scheduler::default();
+        // nothing scheduled yet when there's no file
+        let notes: Vec<ScheduledNote> =
+            storage::load_json(path, SCHEDULED_NOTES_FILE).unwrap_or_default();
+        for note in notes {
+            scheduler.schedule(note);
+        }
+        scheduler
+    }
+
+    pub fn save(&self, path: &DataPath) {
+        storage::save_json(path, SCHEDULED_NOTES_FILE, &self.queue);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduled(content: &str, publish_at: u64) -> ScheduledNote {
+        ScheduledNote {
+            author: Pubkey::new([1; 32]),
+            content: content.to_string(),
+            publish_at,
+        }
+    }
+
+    #[test]
+    fn test_take_due_in_order() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(scheduled("later", 200));
+        scheduler.schedule(scheduled("sooner", 100));
+        scheduler.schedule(scheduled("latest", 300));
+        assert_eq!(scheduler.next_due(), Some(100));
+
+        let due: Vec<String> = scheduler
+            .take_due(200)
+            .into_iter()
+            .map(|n| n.content)
+            .collect();
+        assert_eq!(due, vec!["sooner", "later"]);
+        assert_eq!(scheduler.notes(), &[scheduled("latest", 300)]);
+        assert!(scheduler.take_due(299).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use notedeck::{storage, unix_now, DataPath};
+
+pub static SNOOZES_FILE: &str = "snoozes.json";
+
+/// Durations offered when snoozing a thread, in seconds
+pub const SNOOZE_DURATIONS: [(&str, u64); 4] = [
+    ("1 hour", 60 * 60),
+    ("8 hours", 8 * 60 * 60),
+    ("1 day", 24 * 60 * 60),
+    ("1 week", 7 * 24 * 60 * 60),
+];
+
+/// Threads the user hid for a while. Unlike mutes, snoozes lapse on their
+/// own and the thread shows up again.
+#[derive(Default)]
+pub struct Snoozes {
+    /// Thread root id to the unix time the snooze lapses
+    expiries: HashMap<[u8; 32], u64>,
+}
+
+impl Snoozes {
+    /// Hide the thread with root `root` for `secs` seconds from `now`
+    pub fn snooze(&mut self, root: [u8; 32], secs: u64, now: u64) {
+        self.expiries.insert(root, now + secs);
+    }
+
+    pub fn is_snoozed(&self, root: &[u8; 32], now: u64) -> bool {
+        self.expiries
+            .get(root)
+            .map_or(false, |expiry| now < *expiry)
+    }
+
+    /// Forget snoozes that have lapsed
+    pub fn prune(&mut self, now: u64) {
+        self.expiries.retain(|_, expiry| now < *expiry);
+    }
+
+    pub fn load(path: &DataPath) -> Self {
+        let serialized: HashMap<String, u64> =
+            if let Some(serialized) = storage::load_json(path, SNOOZES_FILE) {
+                serialized
+            } else {
+                // no snoozes yet
+                return Snoozes::default();
+            };
+
+        let expiries = serialized
+            .into_iter()
+            .filter_map(|(root, expiry)| {
+                let root: [u8; 32] = hex::decode(root).ok()?.try_into().ok()?;
+                Some((root, expiry))
+            })
+            .collect();
+
+        let mut snoozes = Snoozes { expiries };
+        snoozes.prune(unix_now());
+        snoozes
+    }
+
+    pub fn save(&self, path: &DataPath) {
+        let serialized: HashMap<String, u64> = self
+            .expiries
+            .iter()
+            .map(|(root, expiry)| (hex::encode(root), *expiry))
+            .collect();
+
+        storage::save_json(path, SNOOZES_FILE, &serialized);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snooze_lapses() {
+        let mut snoozes = Snoozes::default();
+        let root = [1; 32];
+
+        snoozes.snooze(root, 60, 1000);
+        assert!(snoozes.is_snoozed(&root, 1000));
+        assert!(snoozes.is_snoozed(&root, 1059));
+        assert!(!snoozes.is_snoozed(&root, 1060));
+        assert!(!snoozes.is_snoozed(&[2; 32], 1000));
+
+        snoozes.prune(1030);
+        assert_eq!(snoozes.expiries.len(), 1);
+        snoozes.prune(1060);
+        assert!(snoozes.expiries.is_empty());
+    }
+}
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, fmt, str::FromStr};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use enostr::{NoteId, Pubkey};
 use nostrdb::Ndb;
@@ -65,6 +70,52 @@ pub fn save_decks_cache(path: &DataPath, decks_cache: &DecksCache) {
     }
 }
 
+/// Where the workspace called `name` is saved. Anything but letters,
+/// digits, spaces, `-` and `_` is dropped from the name so it can't
+/// escape the workspaces directory.
+pub fn workspace_path(path: &DataPath, name: &str) -> PathBuf {
+    let file_name: String = name
+        .trim()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+        .collect();
+
+    path.path(DataPathType::Setting)
+        .join("workspaces")
+        .join(format!("{}.json", file_name))
+}
+
+impl Columns {
+    /// Save this column layout as a workspace file
+    pub fn serialize_to_file(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string(&serialize_columns(self))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, json)?;
+        info!("saved workspace to {}", path.display());
+
+        Ok(())
+    }
+
+    /// Load a column layout saved with [`Columns::serialize_to_file`].
+    /// Columns are restored for `deck_user`, like decks are. A corrupt
+    /// file is an [`io::ErrorKind::InvalidData`] error.
+    pub fn deserialize_from_file(
+        path: &Path,
+        ndb: &Ndb,
+        deck_user: &[u8; 32],
+    ) -> io::Result<Columns> {
+        let json = fs::read_to_string(path)?;
+        let serialized = serde_json::from_str::<Vec<Vec<String>>>(&json)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        Ok(deserialize_columns(ndb, deck_user, serialized))
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct SerializableDecksCache {
     #[serde(serialize_with = "serialize_map", deserialize_with = "deserialize_map")]
@@ -277,11 +328,23 @@ impl SerializableDeck {
     }
 }
 
+/// Stored before a column's routes when the column is pinned, see
+/// [`Columns::pin_column`]. Older versions skip it as an unknown route.
+const PINNED_COLUMN: &str = "pinned";
+
 fn serialize_columns(columns: &Columns) -> Vec<Vec<String>> {
     let mut cols_serialized: Vec<Vec<String>> = Vec::new();
 
-    for column in columns.columns() {
+    // archived columns come back as regular columns on the next start
+    for column in columns
+        .columns()
+        .into_iter()
+        .chain(columns.archived_columns())
+    {
         let mut column_routes = Vec::new();
+        if column.is_pinned() {
+            column_routes.push(PINNED_COLUMN.to_owned());
+        }
         for route in column.router().routes() {
             if let Some(route_str) = serialize_route(route, columns) {
                 column_routes.push(route_str);
@@ -297,7 +360,13 @@ fn deserialize_columns(ndb: &Ndb, deck_user: &[u8; 32], serialized: Vec<Vec<Stri
     let mut cols = Columns::new();
     for serialized_routes in serialized {
         let mut cur_routes = Vec::new();
+        let mut pinned = false;
         for serialized_route in serialized_routes {
+            if serialized_route == PINNED_COLUMN {
+                pinned = true;
+                continue;
+            }
+
             let selections = Selection::from_serialized(&serialized_route);
             if let Some(route_intermediary) = selections_to_route(selections.clone()) {
                 if let Some(ir) = route_intermediary.intermediary_route(ndb, Some(deck_user)) {
@@ -319,6 +388,13 @@ fn deserialize_columns(ndb: &Ndb, deck_user: &[u8; 32], serialized: Vec<Vec<Stri
 
         if !cur_routes.is_empty() {
             cols.insert_intermediary_routes(cur_routes);
+
+            if pinned {
+                let last = cols.num_columns() - 1;
+                if let Some(id) = cols.find_timeline_for_column_index(last).map(|tl| tl.id) {
+                    cols.pin_column(id);
+                }
+            }
         }
     }
 
@@ -354,6 +430,7 @@ enum Keyword {
     ExternalNotifSelection,
     HashtagSelection,
     Support,
+    Scheduled,
     Deck,
     Edit,
     IndividualSelection,
@@ -391,6 +468,7 @@ impl Keyword {
         ),
         ("hashtag_selection", Keyword::HashtagSelection, false),
         ("support", Keyword::Support, false),
+        ("scheduled", Keyword::Scheduled, false),
         ("deck", Keyword::Deck, false),
         ("edit", Keyword::Edit, true),
     ];
@@ -532,6 +610,7 @@ fn serialize_route(route: &Route, columns: &Columns) -> Option<String> {
             }
         }
         Route::Support => selections.push(Selection::Keyword(Keyword::Support)),
+        Route::Scheduled => selections.push(Selection::Keyword(Keyword::Scheduled)),
         Route::NewDeck => {
             selections.push(Selection::Keyword(Keyword::Deck));
             selections.push(Selection::Keyword(Keyword::New));
@@ -749,6 +828,9 @@ fn selections_to_route(selections: Vec<Selection>) -> Option<CleanIntermediaryRo
         Selection::Keyword(Keyword::Support) => {
             Some(CleanIntermediaryRoute::ToRoute(Route::Support))
         }
+        Selection::Keyword(Keyword::Scheduled) => {
+            Some(CleanIntermediaryRoute::ToRoute(Route::Scheduled))
+        }
         Selection::Keyword(Keyword::Deck) => match selections.get(1)? {
             Selection::Keyword(Keyword::New) => {
                 Some(CleanIntermediaryRoute::ToRoute(Route::NewDeck))
@@ -790,6 +872,40 @@ impl fmt::Display for Selection {
 
 #[cfg(test)]
 mod tests {
+    use notedeck::{DataPath, DataPathType};
+
+    use super::{serialize_columns, workspace_path, PINNED_COLUMN};
+    use crate::{column::Columns, timeline::Timeline};
+
+    #[test]
+    fn test_workspace_path() {
+        let path = DataPath::new("/data");
+        let workspaces = path.path(DataPathType::Setting).join("workspaces");
+
+        assert_eq!(
+            workspace_path(&path, " My work-space_1 "),
+            workspaces.join("My work-space_1.json")
+        );
+        assert_eq!(
+            workspace_path(&path, "../../etc/passwd"),
+            workspaces.join("etcpasswd.json")
+        );
+    }
+
+    #[test]
+    fn test_serialize_pinned_column() {
+        let mut columns = Columns::new();
+        let pinned = Timeline::hashtag("nostr".to_string());
+        let pinned_id = pinned.id;
+        columns.add_new_timeline_column(pinned);
+        columns.add_new_timeline_column(Timeline::hashtag("zaps".to_string()));
+        columns.pin_column(pinned_id);
+
+        let serialized = serialize_columns(&columns);
+        assert_eq!(serialized[0][0], PINNED_COLUMN);
+        assert!(!serialized[1].iter().any(|route| route == PINNED_COLUMN));
+    }
+
     //use enostr::Pubkey;
 
     //use crate::{route::Route, timeline::TimelineRoute};
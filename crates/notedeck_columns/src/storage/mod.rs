@@ -1,5 +1,5 @@
 mod decks;
 mod migration;
 
-pub use decks::{load_decks_cache, save_decks_cache, DECKS_CACHE_FILE};
+pub use decks::{load_decks_cache, save_decks_cache, workspace_path, DECKS_CACHE_FILE};
 pub use migration::{deserialize_columns, COLUMNS_FILE};
@@ -6,7 +6,7 @@ use uuid::Uuid;
 pub enum SubKind {
     /// Initial subscription. This is the first time we do a remote subscription
     /// for a timeline
-    Initial,
+    Initial(TimelineId),
 
     /// One shot requests, we can just close after we receive EOSE
     OneShot,
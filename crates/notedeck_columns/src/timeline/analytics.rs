@@ -0,0 +1,130 @@
+use crate::{actionbar::NoteAction, Result};
+
+use nostrdb::NoteKey;
+use notedeck::{storage, DataPath, DataPathType};
+use serde::Serialize;
+use std::collections::HashSet;
+use tracing::info;
+
+/// How a user interacts with a column during this session, see
+/// [`TimelineView::with_column_analytics`](crate::ui::TimelineView::with_column_analytics).
+/// This is only ever written to local files.
+#[derive(Debug, Clone)]
+pub struct ColumnAnalytics {
+    /// Notes scrolled past the top of the column
+    read: HashSet<NoteKey>,
+    /// Notes opened as a thread
+    notes_clicked: u64,
+    /// Every other note action, eg. replies and profile opens
+    actions: u64,
+    /// When we started recording
+    started_at: u64,
+}
+
+impl ColumnAnalytics {
+    pub fn new(started_at: u64) -> Self {
+        ColumnAnalytics {
+            read: HashSet::new(),
+            notes_clicked: 0,
+            actions: 0,
+            started_at,
+        }
+    }
+
+    pub fn record_read(&mut self, notes: &[NoteKey]) {
+        self.read.extend(notes);
+    }
+
+    pub fn record_action(&mut self, action: &NoteAction) {
+        match action {
+            NoteAction::OpenThread(_) => self.notes_clicked += 1,
+            // not something the user did
+            NoteAction::Seen(_) => {}
+            _ => self.actions += 1,
+        }
+    }
+
+    pub fn notes_read(&self) -> usize {
+        self.read.len()
+    }
+
+    fn stats(&self, column: String, now: u64) -> ColumnStats {
+        ColumnStats {
+            column,
+            notes_read: self.notes_read(),
+            notes_clicked: self.notes_clicked,
+            actions: self.actions,
+            session_secs: now.saturating_sub(self.started_at),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnStats {
+    pub column: String,
+    pub notes_read: usize,
+    pub notes_clicked: u64,
+    pub actions: u64,
+    pub session_secs: u64,
+}
+
+/// The analytics of every column that records them, see
+/// [`Columns::export_analytics`](crate::column::Columns::export_analytics)
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsReport {
+    pub generated_at: u64,
+    pub columns: Vec<ColumnStats>,
+}
+
+impl AnalyticsReport {
+    pub fn new(generated_at: u64) -> Self {
+        AnalyticsReport {
+            generated_at,
+            columns: vec![],
+        }
+    }
+
+    pub fn add_column(&mut self, column: String, analytics: &ColumnAnalytics) {
+        self.columns
+            .push(analytics.stats(column, self.generated_at));
+    }
+
+    /// Write the report as JSON to the exports cache directory, returning
+    /// the name of the file that was written
+    pub fn save(&self, path: &DataPath) -> Result<String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        let directory = path.path(DataPathType::Cache).join("exports");
+        let file_name = format!("column-analytics-{}.json", self.generated_at);
+
+        storage::write_file(&directory, file_name.clone(), &json)?;
+        info!(
+            "exported column analytics to {}",
+            directory.join(&file_name).display()
+        );
+
+        Ok(file_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enostr::NoteId;
+
+    #[test]
+    fn test_column_stats() {
+        let mut analytics = ColumnAnalytics::new(100);
+        analytics.record_read(&[NoteKey::new(1), NoteKey::new(2)]);
+        analytics.record_read(&[NoteKey::new(2), NoteKey::new(3)]);
+        analytics.record_action(&NoteAction::OpenThread(NoteId::new([0; 32])));
+        analytics.record_action(&NoteAction::Reply(NoteId::new([0; 32])));
+        analytics.record_action(&NoteAction::Quote(NoteId::new([0; 32])));
+
+        let stats = analytics.stats("Home".to_string(), 160);
+        assert_eq!(stats.notes_read, 3);
+        assert_eq!(stats.notes_clicked, 1);
+        assert_eq!(stats.actions, 2);
+        assert_eq!(stats.session_secs, 60);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{timeline::Timeline, Result};
+
+use nostrdb::{Ndb, Transaction};
+use notedeck::{note, storage, DataPath, DataPathType, NoteCache};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// How many characters of each note's content we keep in the export
+const CONTENT_SNIPPET_LEN: usize = 140;
+
+const HEADER: &[&str] = &[
+    "id",
+    "pubkey",
+    "created_at",
+    "kind",
+    "content",
+    "reactions",
+    "zaps",
+    "relay_count",
+];
+
+/// How many notes [`RelayCounts`] remembers
+const RELAY_COUNTS_CAP: usize = 50_000;
+
+/// Which relays sent us each note, for the `relay_count` column. nostrdb
+/// doesn't keep track of this, so we count the notes that arrive while
+/// the app runs in debug mode.
+#[derive(Debug, Default)]
+pub struct RelayCounts {
+    /// Relay urls, indexed by the entries of `notes`
+    relays: Vec<String>,
+    notes: HashMap<[u8; 32], Vec<u16>>,
+    /// The order notes were first seen in, the oldest are forgotten first
+    order: VecDeque<[u8; 32]>,
+}
+
+impl RelayCounts {
+    /// Count the event in a relay's `EVENT` message
+    pub fn record(&mut self, relay: &str, msg: &str) {
+        let id = if let Some(id) = event_id(msg) {
+            id
+        } else {
+            return;
+        };
+
+        let relay = match self.relays.iter().position(|r| r == relay) {
+            Some(ind) => ind as u16,
+            None => {
+                self.relays.push(relay.to_owned());
+                (self.relays.len() - 1) as u16
+            }
+        };
+
+        let relays = self.notes.entry(id).or_insert_with(|| {
+            self.order.push_back(id);
+            vec![]
+        });
+        if !relays.contains(&relay) {
+            relays.push(relay);
+        }
+
+        if self.order.len() > RELAY_COUNTS_CAP {
+            if let Some(oldest) = self.order.pop_front() {
+                self.notes.remove(&oldest);
+            }
+        }
+    }
+
+    /// How many relays sent us the note, if we counted it
+    pub fn count(&self, id: &[u8; 32]) -> Option<usize> {
+        self.notes.get(id).map(|relays| relays.len())
+    }
+}
+
+/// The id of the event in an `EVENT` message, without parsing all of it
+fn event_id(msg: &str) -> Option<[u8; 32]> {
+    let key = msg.find("\"id\"")?;
+    let rest = msg[key + 4..].trim_start().strip_prefix(':')?;
+    let rest = rest.trim_start().strip_prefix('"')?;
+
+    let mut id = [0; 32];
+    hex::decode_to_slice(rest.get(..64)?, &mut id).ok()?;
+    Some(id)
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break,
+/// doubling any embedded quotes
+fn escape_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn write_row(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&escape_field(field));
+    }
+    out.push_str("\r\n");
+}
+
+fn content_snippet(content: &str) -> String {
+    content.chars().take(CONTENT_SNIPPET_LEN).collect()
+}
+
+/// Render the currently selected view of a timeline as CSV, one row per
+/// loaded note. Reaction and zap counts come from the [`NoteCache`].
+/// `relay_count` is empty for notes [`RelayCounts`] didn't see arrive.
+pub fn timeline_csv(
+    timeline: &Timeline,
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    relay_counts: &RelayCounts,
+    txn: &Transaction,
+) -> String {
+    let tab = timeline.current_view();
+    let mut out = String::new();
+    write_row(&mut out, HEADER);
+
+    for note_ref in &tab.notes {
+        let note = if let Ok(note) = ndb.get_note_by_key(txn, note_ref.key) {
+            note
+        } else {
+            warn!("csv: note {:?} missing from db", note_ref.key);
+            continue;
+        };
+
+        let reactions = note::note_reactions(ndb, note_cache, txn, note_ref.key, &note);
+        let zaps = note::note_zaps(ndb, note_cache, txn, note_ref.key, &note);
+        let relay_count = relay_counts
+            .count(note.id())
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+
+        write_row(
+            &mut out,
+            &[
+                &hex::encode(note.id()),
+                &hex::encode(note.pubkey()),
+                &note.created_at().to_string(),
+                &note.kind().to_string(),
+                &content_snippet(note.content()),
+                &reactions.to_string(),
+                &zaps.to_string(),
+                &relay_count,
+            ],
+        );
+    }
+
+    out
+}
+
+/// Export a timeline as CSV to the exports cache directory, returning the
+/// path of the file that was written
+pub fn export_timeline_csv(
+    timeline: &Timeline,
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    relay_counts: &RelayCounts,
+    path: &DataPath,
+) -> Result<PathBuf> {
+    let txn = Transaction::new(ndb)?;
+    let csv = timeline_csv(timeline, ndb, note_cache, relay_counts, &txn);
+
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let directory = path.path(DataPathType::Cache).join("exports");
+    let file_name = format!("column-{}-{}.csv", timeline.id, exported_at);
+
+    storage::write_file(&directory, file_name.clone(), &csv)?;
+
+    let file = directory.join(file_name);
+    info!("exported column to {}", file.display());
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_field() {
+        assert_eq!(escape_field("hello"), "hello");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"gm\""), "\"say \"\"gm\"\"\"");
+        assert_eq!(escape_field("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn test_relay_counts() {
+        let id = hex::encode([7; 32]);
+        let msg = format!(r#"["EVENT","sub",{{"id": "{id}","content":"\"id\":\"x\""}}]"#);
+
+        let mut counts = RelayCounts::default();
+        counts.record("wss://a.relay", &msg);
+        counts.record("wss://b.relay", &msg);
+        counts.record("wss://a.relay", &msg);
+        counts.record("wss://a.relay", r#"["EVENT","sub",{"content":"no id"}]"#);
+
+        assert_eq!(counts.count(&[7; 32]), Some(2));
+        assert_eq!(counts.count(&[8; 32]), None);
+    }
+
+    #[test]
+    fn test_write_row() {
+        let mut out = String::new();
+        write_row(&mut out, &["1", "a,b", ""]);
+        assert_eq!(out, "1,\"a,b\",\r\n");
+    }
+}
@@ -206,6 +206,19 @@ impl TimelineKind {
         }
     }
 
+    /// An emoji used to quickly tell column types apart in the column
+    /// header
+    pub fn column_type_icon(&self) -> &'static str {
+        match self {
+            TimelineKind::List(ListKind::Contact(_)) => "🏠",
+            TimelineKind::Notifications(_) => "🔔",
+            TimelineKind::Profile(_) => "👤",
+            TimelineKind::Universe => "🌐",
+            TimelineKind::Generic => "📜",
+            TimelineKind::Hashtag(_) => "#",
+        }
+    }
+
     pub fn to_title(&self) -> ColumnTitle<'_> {
         match self {
             TimelineKind::List(list_kind) => match list_kind {
@@ -3,30 +3,51 @@ use crate::{
     decks::DecksCache,
     error::Error,
     subscriptions::{self, SubKind, Subscriptions},
+    ui::{column::ColumnDivider, note::content_warning::ContentRating},
     Result,
 };
 
 use notedeck::{
-    filter, CachedNote, FilterError, FilterState, FilterStates, NoteCache, NoteRef, UnknownIds,
+    filter, note::root_note_id_from_selected_id, CachedNote, FilterError, FilterState,
+    FilterStates, MuteFun, NoteCache, NoteRef, UnknownIds,
 };
 
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use egui_virtual_list::VirtualList;
 use enostr::{PoolRelay, Pubkey, RelayPool};
-use nostrdb::{Filter, Ndb, Note, Subscription, Transaction};
+use nostrdb::{Filter, Ndb, Note, NoteKey, Subscription, Transaction};
 use std::cell::RefCell;
 use std::hash::Hash;
 use std::rc::Rc;
 
 use tracing::{debug, error, info, warn};
 
+pub mod analytics;
+pub mod csv;
 pub mod kind;
+pub mod ordering;
+pub mod read_markers;
+pub mod recent_notes;
 pub mod route;
+pub mod search;
+pub mod settings;
+pub mod snapshot;
 
+pub use analytics::{AnalyticsReport, ColumnAnalytics};
 pub use kind::{ColumnTitle, PubkeySource, TimelineKind};
+pub use ordering::FeedOrder;
+pub use read_markers::ReadMarkers;
+pub use recent_notes::RecentNotes;
 pub use route::TimelineRoute;
+pub use search::TimelineSearch;
+pub use settings::TimelineSettings;
+pub use snapshot::TimelineSnapshot;
 
 #[derive(Debug, Hash, Copy, Clone, Eq, PartialEq)]
 pub struct TimelineId(u32);
@@ -43,12 +64,42 @@ impl fmt::Display for TimelineId {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
 pub enum ViewFilter {
     Notes,
 
     #[default]
     NotesAndReplies,
+
+    /// Only notes with images or videos
+    Media,
+
+    /// Only notes created at or after this timestamp
+    Since(u64),
+
+    /// Only notes created at or before this timestamp
+    Until(u64),
+}
+
+/// File extensions of the media shown in [`ViewFilter::Media`] tabs
+const MEDIA_EXTENSIONS: [&str; 11] = [
+    "jpg", "jpeg", "png", "gif", "webp", "avif", "svg", "mp4", "mov", "webm", "m4v",
+];
+
+/// Whether `word` is a link to an image or video
+pub(crate) fn is_media_url(word: &str) -> bool {
+    if !word.starts_with("https://") && !word.starts_with("http://") {
+        return false;
+    }
+
+    let path = word.split(['?', '#']).next().unwrap_or(word).to_lowercase();
+    path.rsplit_once('.')
+        .map_or(false, |(_, ext)| MEDIA_EXTENSIONS.contains(&ext))
+}
+
+/// Whether some text links to an image or video
+fn has_media_link(content: &str) -> bool {
+    content.split_whitespace().any(is_media_url)
 }
 
 impl ViewFilter {
@@ -56,21 +107,53 @@ impl ViewFilter {
         match self {
             ViewFilter::Notes => "Notes",
             ViewFilter::NotesAndReplies => "Notes & Replies",
+            ViewFilter::Media => "Media",
+            ViewFilter::Since(_) => "Since",
+            ViewFilter::Until(_) => "Until",
         }
     }
 
-    pub fn filter_notes(cache: &CachedNote, note: &Note) -> bool {
-        !cache.reply.borrow(note.tags()).is_reply()
+    /// What an empty tab with this filter shows instead of the loading
+    /// spinner. The other tabs can already have notes when these have
+    /// none, so they aren't still loading.
+    pub fn empty_message(&self) -> Option<&'static str> {
+        match self {
+            ViewFilter::Media => Some("No media yet"),
+            ViewFilter::Since(_) | ViewFilter::Until(_) => Some("No notes in this time range"),
+            ViewFilter::Notes | ViewFilter::NotesAndReplies => None,
+        }
+    }
+
+    /// Whether a note created at `created_at` is within our time bounds.
+    /// Only [`ViewFilter::Since`] and [`ViewFilter::Until`] have any.
+    pub fn in_time_range(&self, created_at: u64) -> bool {
+        match self {
+            ViewFilter::Since(ts) => created_at >= *ts,
+            ViewFilter::Until(ts) => created_at <= *ts,
+            _ => true,
+        }
+    }
+
+    /// Notes with media links in their content or NIP-92 `imeta` tags
+    pub fn filter_media(_cache: &CachedNote, note: &Note) -> bool {
+        let has_imeta = note.tags().iter().any(|tag| {
+            tag.count() >= 2 && tag.get(0).and_then(|t| t.variant().str()) == Some("imeta")
+        });
+
+        has_imeta || has_media_link(note.content())
     }
 
-    fn identity(_cache: &CachedNote, _note: &Note) -> bool {
-        true
+    pub fn filter_notes(cache: &CachedNote, note: &Note) -> bool {
+        !cache.reply.borrow(note.tags()).is_reply()
     }
 
-    pub fn filter(&self) -> fn(&CachedNote, &Note) -> bool {
+    /// Whether a note belongs in tabs with this filter
+    pub fn filter(&self, cache: &CachedNote, note: &Note) -> bool {
         match self {
-            ViewFilter::Notes => ViewFilter::filter_notes,
-            ViewFilter::NotesAndReplies => ViewFilter::identity,
+            ViewFilter::Notes => ViewFilter::filter_notes(cache, note),
+            ViewFilter::NotesAndReplies => true,
+            ViewFilter::Media => ViewFilter::filter_media(cache, note),
+            ViewFilter::Since(_) | ViewFilter::Until(_) => self.in_time_range(note.created_at()),
         }
     }
 }
@@ -82,9 +165,42 @@ impl ViewFilter {
 #[derive(Default, Debug)]
 pub struct TimelineTab {
     pub notes: Vec<NoteRef>,
-    pub selection: i32,
+    /// The row of the note selected with the keyboard or the last click,
+    /// in display order
+    pub selection: Option<usize>,
     pub filter: ViewFilter,
+    /// Left out of the tab bar, see [`Timeline::set_view_visible`]. The
+    /// tab still receives notes.
+    pub hidden: bool,
     pub list: Rc<RefCell<VirtualList>>,
+    /// The nostrdb subscription feeding this tab. Tabs usually share
+    /// their timeline's, see [`Timeline::set_subscription`]. It should be
+    /// unsubscribed when the tab goes away.
+    pub subscription_id: Option<Subscription>,
+    stats: InsertionStats,
+    /// Bumped whenever notes are inserted
+    revision: u64,
+    /// Our notes in display order, when not chronological. See
+    /// [`TimelineTab::apply_ordering`]
+    ordered: Option<Vec<NoteRef>>,
+    ordered_for: Option<(FeedOrder, u64)>,
+    /// What the ordering knows about our notes, in the same order. Kept
+    /// so only newly inserted notes are looked up when ordering again.
+    feed: Vec<ordering::FeedNote>,
+}
+
+/// Counters for the notes offered to a [`TimelineTab`], useful for
+/// debugging subscription issues
+#[derive(Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub struct InsertionStats {
+    /// notes offered to the tab
+    pub total: usize,
+    /// notes that passed the tab's [`ViewFilter`]
+    pub filtered: usize,
+    /// notes rejected by the mute function
+    pub muted: usize,
+    /// notes that were already in the tab
+    pub duplicate: usize,
 }
 
 impl TimelineTab {
@@ -104,11 +220,12 @@ impl TimelineTab {
         vec![
             TimelineTab::new(ViewFilter::Notes),
             TimelineTab::new(ViewFilter::NotesAndReplies),
+            TimelineTab::new(ViewFilter::Media),
         ]
     }
 
     pub fn new_with_capacity(filter: ViewFilter, cap: usize) -> Self {
-        let selection = 0i32;
+        let selection = None;
         let mut list = VirtualList::new();
         list.hide_on_resize(None);
         list.over_scan(1000.0);
@@ -119,22 +236,106 @@ impl TimelineTab {
             notes,
             selection,
             filter,
+            hidden: false,
             list,
+            subscription_id: None,
+            stats: InsertionStats::default(),
+            revision: 0,
+            ordered: None,
+            ordered_for: None,
+            feed: vec![],
         }
     }
 
+    /// Associate the nostrdb subscription that feeds this tab
+    pub fn with_subscription_id(mut self, sub_id: Subscription) -> Self {
+        self.subscription_id = Some(sub_id);
+        self
+    }
+
+    /// Whether this tab is still receiving notes from its own
+    /// subscription, rather than holding a fixed set of notes
+    pub fn is_live(&self) -> bool {
+        self.subscription_id.is_some()
+    }
+
+    pub fn note_insertion_stats(&self) -> InsertionStats {
+        self.stats
+    }
+
+    /// Changes whenever notes are added to or removed from the tab, so
+    /// views can tell when what they derived from our notes is stale
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn clear_stats(&mut self) {
+        self.stats = InsertionStats::default();
+    }
+
+    /// Record notes that were offered to this tab but filtered out by the
+    /// view filter, along with how many of the offered notes are muted
+    pub fn record_rejected(&mut self, filtered_out: usize, muted: usize) {
+        self.stats.total += filtered_out;
+        self.stats.muted += muted;
+    }
+
     pub fn insert(&mut self, new_refs: &[NoteRef], reversed: bool) {
         if new_refs.is_empty() {
             return;
         }
+
+        // our notes are always sorted, so we can binary search for dupes
+        let unique_refs: Vec<NoteRef> = new_refs
+            .iter()
+            .filter(|nr| self.notes.binary_search(nr).is_err())
+            .copied()
+            .collect();
+
+        self.stats.total += new_refs.len();
+        self.stats.filtered += new_refs.len();
+        self.stats.duplicate += new_refs.len() - unique_refs.len();
+
         let num_prev_items = self.notes.len();
-        let (notes, merge_kind) = crate::timeline::merge_sorted_vecs(&self.notes, new_refs);
+        let (notes, merge_kind) = crate::timeline::merge_sorted_vecs(&self.notes, &unique_refs);
 
         self.notes = notes;
         let new_items = self.notes.len() - num_prev_items;
+        self.notes_inserted(new_items, merge_kind, reversed);
+    }
 
-        // TODO: technically items could have been added inbetween
+    /// Insert notes in any order. They are sorted and then merged with
+    /// our notes in a single pass, skipping notes we already have, which
+    /// is much cheaper than [`TimelineTab::insert`] for large batches.
+    pub fn batch_insert_notes(&mut self, notes: &[NoteRef], reversed: bool) {
+        if notes.is_empty() {
+            return;
+        }
+
+        let mut new_refs = notes.to_vec();
+        new_refs.sort_unstable();
+        new_refs.dedup();
+
+        let num_prev_items = self.notes.len();
+        let (merged, merge_kind) = merge_sorted_dedup_vecs(&self.notes, &new_refs);
+        let new_items = merged.len() - num_prev_items;
+
+        self.stats.total += notes.len();
+        self.stats.filtered += notes.len();
+        self.stats.duplicate += notes.len() - new_items;
+
+        self.notes = merged;
+        self.notes_inserted(new_items, merge_kind, reversed);
+    }
+
+    fn notes_inserted(&mut self, new_items: usize, merge_kind: MergeKind, reversed: bool) {
         if new_items > 0 {
+            self.revision += 1;
+        }
+
+        // TODO: technically items could have been added inbetween
+        // when we have a custom order, `apply_ordering` updates the list
+        if new_items > 0 && self.ordered.is_none() {
             let mut list = self.list.borrow_mut();
 
             match merge_kind {
@@ -142,7 +343,7 @@ impl TimelineTab {
                 MergeKind::Spliced => {
                     debug!(
                         "spliced when inserting {} new notes, resetting virtual list",
-                        new_refs.len()
+                        new_items
                     );
                     list.reset();
                 }
@@ -158,25 +359,234 @@ impl TimelineTab {
         }
     }
 
+    /// The notes created at or after `since`. Notes are sorted newest
+    /// first, so this is always a prefix of our notes.
+    pub fn notes_since(&self, since: Option<u64>) -> &[NoteRef] {
+        if let Some(since) = since {
+            let end = self.notes.partition_point(|n| n.created_at >= since);
+            &self.notes[..end]
+        } else {
+            &self.notes
+        }
+    }
+
+    /// The notes to show, in display order. Like
+    /// [`TimelineTab::notes_since`] these are the notes created at or
+    /// after `since`.
+    pub fn display_notes(&self, since: Option<u64>) -> Cow<'_, [NoteRef]> {
+        match (&self.ordered, since) {
+            (None, _) => Cow::Borrowed(self.notes_since(since)),
+            (Some(ordered), None) => Cow::Borrowed(ordered),
+            (Some(ordered), Some(since)) => Cow::Owned(
+                ordered
+                    .iter()
+                    .filter(|n| n.created_at >= since)
+                    .copied()
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Show our notes in `order`. The order is only recomputed when notes
+    /// were inserted or the order changed, so this is cheap to call every
+    /// frame. Our `notes` stay sorted newest first either way.
+    pub fn apply_ordering(&mut self, order: FeedOrder, ndb: &Ndb, txn: &Transaction) {
+        if order == FeedOrder::Chronological {
+            if self.ordered.take().is_some() {
+                self.ordered_for = None;
+                self.feed.clear();
+                self.list.borrow_mut().reset();
+            }
+            return;
+        }
+
+        if self.ordered_for == Some((order, self.revision)) {
+            return;
+        }
+
+        self.update_feed(ndb, txn);
+        let ordered = order.strategy().order(&self.feed);
+
+        // strategies put new notes in front where they can, so usually
+        // only the top of the order changed. Keeping the rows below it
+        // where they were keeps our place.
+        let mut list = self.list.borrow_mut();
+        let unchanged = match &self.ordered {
+            Some(prev) if self.ordered_for.map(|(o, _)| o) == Some(order) => {
+                common_suffix_len(prev, &ordered)
+            }
+            _ => 0,
+        };
+        if unchanged == 0 {
+            list.reset();
+        } else if let Some(prev) = &self.ordered {
+            if ordered.len() > prev.len() {
+                list.items_inserted_at_start(ordered.len() - prev.len());
+            }
+        }
+
+        self.ordered = Some(ordered);
+        self.ordered_for = Some((order, self.revision));
+    }
+
+    /// Bring [`TimelineTab::feed`] up to date with our notes. Notes are
+    /// usually inserted at the front, so only those are looked up.
+    fn update_feed(&mut self, ndb: &Ndb, txn: &Transaction) {
+        let known = self.feed.len();
+        let reusable = known <= self.notes.len()
+            && self.notes[self.notes.len() - known..]
+                .iter()
+                .zip(&self.feed)
+                .all(|(nr, feed)| *nr == feed.note_ref);
+        if !reusable {
+            self.feed.clear();
+        }
+
+        let new = self.notes.len() - self.feed.len();
+        let mut feed: Vec<ordering::FeedNote> = self.notes[..new]
+            .iter()
+            .filter_map(|nr| {
+                let note = ndb.get_note_by_key(txn, nr.key).ok()?;
+                Some(ordering::FeedNote::new(&note, *nr))
+            })
+            .collect();
+        feed.append(&mut self.feed);
+        self.feed = feed;
+    }
+
+    /// Switch to `filter`, dropping our notes that don't pass it instead
+    /// of refetching them. Filters can only narrow what we already have,
+    /// eg. notes that were filtered out before won't come back. Returns
+    /// how many notes were removed.
+    pub fn apply_filter_inplace(
+        &mut self,
+        filter: &ViewFilter,
+        ndb: &Ndb,
+        note_cache: &mut NoteCache,
+        txn: &Transaction,
+    ) -> usize {
+        self.filter = *filter;
+
+        let before = self.notes.len();
+        self.notes.retain(|nr| {
+            // keep notes we can't check rather than silently losing them
+            ndb.get_note_by_key(txn, nr.key).map_or(true, |note| {
+                filter.filter(note_cache.cached_note_or_insert(nr.key, &note), &note)
+            })
+        });
+
+        let removed = before - self.notes.len();
+        if removed > 0 {
+            self.revision += 1;
+            self.ordered_for = None;
+            self.list.borrow_mut().reset();
+        }
+        removed
+    }
+
+    /// Split our notes into those created before `ts` and those created
+    /// at or after it, as two new tabs with our filter. Neither tab gets
+    /// our subscription or scroll state. Useful for archiving, where the
+    /// recent tab stays live and the older one is saved.
+    pub fn split_at_timestamp(&self, ts: u64) -> (TimelineTab, TimelineTab) {
+        // notes are sorted newest first, so the recent ones are a prefix
+        let split = self.notes.partition_point(|n| n.created_at >= ts);
+
+        let mut before = TimelineTab::new_with_capacity(self.filter, self.notes.len() - split);
+        before.notes.extend_from_slice(&self.notes[split..]);
+
+        let mut after = TimelineTab::new_with_capacity(self.filter, split);
+        after.notes.extend_from_slice(&self.notes[..split]);
+
+        (before, after)
+    }
+
+    /// Our notes grouped by the hour they were created in, newest hour
+    /// first. Hours are unix timestamps floored to the hour. Notes are
+    /// already sorted, so this is a single pass.
+    pub fn group_by_hour(&self) -> Vec<(u64, Vec<&NoteRef>)> {
+        let mut groups: Vec<(u64, Vec<&NoteRef>)> = vec![];
+
+        for note in &self.notes {
+            let hour = note.created_at - note.created_at % 3600;
+            match groups.last_mut() {
+                Some((last, notes)) if *last == hour => notes.push(note),
+                _ => groups.push((hour, vec![note])),
+            }
+        }
+
+        groups
+    }
+
+    /// The most recently created note in this tab.
+    ///
+    /// This relies on [`TimelineTab::insert`] keeping notes sorted newest
+    /// first (see the [`Ord`] impl on [`NoteRef`]). Pushing to `notes`
+    /// directly breaks it.
+    pub fn newest_note(&self) -> Option<&NoteRef> {
+        self.notes.first()
+    }
+
+    /// The earliest created note in this tab. Like
+    /// [`TimelineTab::newest_note`], this relies on notes being sorted
+    /// newest first.
+    pub fn oldest_note(&self) -> Option<&NoteRef> {
+        self.notes.last()
+    }
+
+    /// The raw events for our loaded notes, looked up from nostrdb. Notes
+    /// that are no longer in the database are skipped.
+    pub fn snapshot(&self, ndb: &Ndb, txn: &Transaction) -> Vec<serde_json::Value> {
+        let mut events = Vec::with_capacity(self.notes.len());
+
+        for note_ref in &self.notes {
+            let note = if let Ok(note) = ndb.get_note_by_key(txn, note_ref.key) {
+                note
+            } else {
+                warn!("snapshot: note {:?} missing from db", note_ref.key);
+                continue;
+            };
+
+            match note.json().map(|json| serde_json::from_str(&json)) {
+                Ok(Ok(event)) => events.push(event),
+                _ => warn!("snapshot: could not serialize note {:?}", note_ref.key),
+            }
+        }
+
+        events
+    }
+
+    /// Select the next row down, or the first one when nothing is
+    /// selected yet
     pub fn select_down(&mut self) {
-        debug!("select_down {}", self.selection + 1);
-        if self.selection + 1 > self.notes.len() as i32 {
+        if self.notes.is_empty() {
             return;
         }
 
-        self.selection += 1;
+        let last = self.notes.len() - 1;
+        self.selection = Some(self.selection.map_or(0, |row| (row + 1).min(last)));
+        debug!("select_down {:?}", self.selection);
     }
 
+    /// Select the next row up, or the first one when nothing is selected
+    /// yet
     pub fn select_up(&mut self) {
-        debug!("select_up {}", self.selection - 1);
-        if self.selection - 1 < 0 {
+        if self.notes.is_empty() {
             return;
         }
 
-        self.selection -= 1;
+        self.selection = Some(self.selection.map_or(0, |row| row.saturating_sub(1)));
+        debug!("select_up {:?}", self.selection);
     }
 }
 
+/// How many seen notes a timeline remembers, so notes scrolled past a
+/// long time ago may be reported as seen again
+const SEEN_NOTES_CAP: usize = 4096;
+
+/// How many revealed muted notes a timeline remembers
+const REVEALED_NOTES_CAP: usize = 256;
+
 /// A column in a deck. Holds navigation state, loaded notes, column kind, etc.
 #[derive(Debug)]
 pub struct Timeline {
@@ -190,6 +600,113 @@ pub struct Timeline {
 
     /// Our nostrdb subscription
     pub subscription: Option<Subscription>,
+
+    /// Overrides the column type icon shown in the column header. See
+    /// [`TimelineKind::column_type_icon`]
+    pub icon: Option<&'static str>,
+
+    /// Overrides the app's divider between this column and the next, see
+    /// [`TimelineView::with_column_divider`](crate::ui::TimelineView::with_column_divider)
+    pub divider: Option<ColumnDivider>,
+
+    /// The width the user dragged this column to, otherwise it shares
+    /// the deck's width with the other columns
+    pub width: Option<f32>,
+
+    /// The grid resizing this column snaps to, see
+    /// [`TimelineView::with_column_snap`](crate::ui::TimelineView::with_column_snap)
+    pub column_snap: Option<f32>,
+
+    /// When this timeline was created, ie. when the app was started or
+    /// when the column was added
+    pub opened_at: u64,
+
+    /// Move the column to the archive after this long without new notes
+    /// or scrolling, see
+    /// [`TimelineView::with_column_archive`](crate::ui::TimelineView::with_column_archive)
+    pub archive_after: Option<Duration>,
+
+    /// When notes last arrived or the column was last scrolled
+    pub last_active: u64,
+
+    /// Only show notes created since [`Timeline::opened_at`], hiding any
+    /// historical backfill
+    pub live_tail: bool,
+
+    /// Draw lines between adjacent notes that reply to each other
+    pub thread_connectors: bool,
+
+    /// A note the user asked to keep above the feed for this session
+    pub kept_at_top: Option<NoteKey>,
+
+    /// Slowly scroll through the timeline while it's hovered, for
+    /// monitoring setups
+    pub auto_scroll: bool,
+
+    /// Scroll through the timeline on its own, pausing while it's
+    /// hovered. The opposite of [`Timeline::auto_scroll`], at the same
+    /// speed, see
+    /// [`TimelineView::with_auto_scroll`](crate::ui::TimelineView::with_auto_scroll)
+    pub teleprompter: bool,
+
+    /// How fast to auto scroll, in points per second
+    pub auto_scroll_speed: f32,
+
+    /// Render notes in compact density, with their action bars hidden until
+    /// hovered, while another column is focused
+    pub compact_when_inactive: bool,
+
+    /// A key that focuses this column when pressed, see
+    /// [`TimelineView::with_focus_mode_shortcut`](crate::ui::TimelineView::with_focus_mode_shortcut)
+    pub focus_key: Option<egui::Key>,
+
+    /// Hide notes whose id has less proof of work than this, in bits
+    pub min_pow: u8,
+
+    /// Hide notes whose content warning rates them above this
+    pub max_rating: ContentRating,
+
+    /// Hide notes covered by the account's mutes. Moderation columns
+    /// can turn this off to see everything, muted notes get a marker
+    pub apply_mutes: bool,
+
+    /// The order notes are shown in, see [`TimelineTab::apply_ordering`]
+    pub ordering: FeedOrder,
+
+    /// Whether the column header is hidden because we're scrolling down
+    /// through the timeline
+    pub header_collapsed: bool,
+    last_scroll_offset: f32,
+
+    /// Interaction metrics for this column, when it records them
+    pub analytics: Option<ColumnAnalytics>,
+
+    /// Count muted notes as they arrive, for the debug overlay's
+    /// [`InsertionStats`]. Checking mutes isn't free, so it's off
+    /// unless the overlay is shown.
+    pub count_muted: bool,
+
+    /// Notes read in this column waiting to be published, when it syncs
+    /// read markers
+    pub read_markers: Option<ReadMarkers>,
+
+    /// Searching through our notes, when the column has a search box
+    pub search: Option<TimelineSearch>,
+
+    /// Notes already reported with
+    /// [`NoteAction::Seen`](crate::actionbar::NoteAction::Seen)
+    pub seen_notes: RecentNotes,
+
+    /// Muted notes the user tapped to show anyway
+    pub revealed_notes: RecentNotes,
+
+    /// The file this column was last exported to, see
+    /// [`csv::export_timeline_csv`]
+    pub csv_export: Option<PathBuf>,
+
+    /// Relays we sent our initial filter to, and whether they've
+    /// finished sending stored notes
+    pub backfill: HashMap<String, bool>,
 }
 
 impl Timeline {
@@ -223,12 +740,64 @@ impl Timeline {
         )
     }
 
-    pub fn make_view_id(id: TimelineId, selected_view: usize) -> egui::Id {
-        egui::Id::new((id, selected_view))
+    /// Feed our notes and every tab's from `sub`, so the tabs are live
+    pub fn set_subscription(&mut self, sub: Subscription) {
+        self.subscription = Some(sub);
+        for view in &mut self.views {
+            view.subscription_id = Some(sub);
+        }
+    }
+
+    /// Ids are per filter rather than per index, so a view keeps its
+    /// scroll state when it's moved
+    pub fn make_view_id(id: TimelineId, filter: ViewFilter) -> egui::Id {
+        egui::Id::new((id, filter))
     }
 
     pub fn view_id(&self) -> egui::Id {
-        Timeline::make_view_id(self.id, self.selected_view)
+        Timeline::make_view_id(self.id, self.current_view().filter)
+    }
+
+    /// Move the view at `from` to `to`, keeping the same view selected.
+    /// Out of range indices are ignored.
+    pub fn move_view(&mut self, from: usize, to: usize) {
+        if from >= self.views.len() || to >= self.views.len() {
+            return;
+        }
+
+        let view = self.views.remove(from);
+        self.views.insert(to, view);
+        self.selected_view = moved_index(self.selected_view, from, to);
+    }
+
+    /// Show or hide the tab of the view at `idx`. Hiding the selected
+    /// view selects the nearest visible one, and the last visible view
+    /// can't be hidden.
+    pub fn set_view_visible(&mut self, idx: usize, visible: bool) {
+        let hidden = if let Some(view) = self.views.get(idx) {
+            view.hidden
+        } else {
+            return;
+        };
+
+        if !visible && !hidden && self.views.iter().filter(|v| !v.hidden).count() <= 1 {
+            return;
+        }
+
+        self.views[idx].hidden = !visible;
+
+        if !visible && self.selected_view == idx {
+            if let Some(nearest) = self
+                .views
+                .iter()
+                .enumerate()
+                .filter(|(_, view)| !view.hidden)
+                .min_by_key(|(i, _)| i.abs_diff(idx))
+                .map(|(i, _)| i)
+            {
+                self.selected_view = nearest;
+            }
+        }
     }
 
     pub fn new(kind: TimelineKind, filter_state: FilterState, views: Vec<TimelineTab>) -> Self {
@@ -239,6 +808,10 @@ impl Timeline {
         let subscription: Option<Subscription> = None;
         let selected_view = 0;
         let id = TimelineId::new(UIDS.fetch_add(1, Ordering::Relaxed));
+        let opened_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
 
         Timeline {
             id,
@@ -247,7 +820,114 @@ impl Timeline {
             views,
             subscription,
             selected_view,
+            icon: None,
+            divider: None,
+            width: None,
+            column_snap: None,
+            opened_at,
+            archive_after: None,
+            last_active: opened_at,
+            live_tail: false,
+            thread_connectors: false,
+            kept_at_top: None,
+            auto_scroll: false,
+            teleprompter: false,
+            auto_scroll_speed: 20.0,
+            compact_when_inactive: false,
+            focus_key: None,
+            min_pow: 0,
+            max_rating: ContentRating::default(),
+            apply_mutes: true,
+            ordering: FeedOrder::default(),
+            header_collapsed: false,
+            last_scroll_offset: 0.0,
+            analytics: None,
+            count_muted: false,
+            read_markers: None,
+            search: None,
+            seen_notes: RecentNotes::new(SEEN_NOTES_CAP),
+            revealed_notes: RecentNotes::new(REVEALED_NOTES_CAP),
+            csv_export: None,
+            backfill: HashMap::new(),
+        }
+    }
+
+    /// How far along fetching stored notes from our relays is, from 0.0
+    /// to 1.0, or None if we haven't asked any relay yet
+    pub fn loading_progress(&self) -> Option<f32> {
+        if self.backfill.is_empty() {
+            return None;
+        }
+
+        let done = self.backfill.values().filter(|done| **done).count();
+        Some(done as f32 / self.backfill.len() as f32)
+    }
+
+    /// A copy of this timeline with the same kind, filter, views and
+    /// display options, but with a fresh note list and scroll state and no
+    /// subscription. See [`Columns::clone_column`]
+    pub fn duplicate(&self) -> Self {
+        let filter_state = if let Some(filters) = self.filter.get_any_ready() {
+            FilterState::ready(filters.clone())
+        } else {
+            self.filter.initial_state.clone()
+        };
+
+        let views = self
+            .views
+            .iter()
+            .map(|view| {
+                let mut tab = TimelineTab::new(view.filter);
+                tab.hidden = view.hidden;
+                tab
+            })
+            .collect();
+
+        let mut timeline = Timeline::new(self.kind.clone(), filter_state, views);
+        timeline.selected_view = self.selected_view;
+        timeline.icon = self.icon;
+        timeline.divider = self.divider;
+        timeline.width = self.width;
+        timeline.column_snap = self.column_snap;
+        timeline.archive_after = self.archive_after;
+        timeline.live_tail = self.live_tail;
+        timeline.thread_connectors = self.thread_connectors;
+        timeline.auto_scroll = self.auto_scroll;
+        timeline.teleprompter = self.teleprompter;
+        timeline.auto_scroll_speed = self.auto_scroll_speed;
+        timeline.compact_when_inactive = self.compact_when_inactive;
+        timeline.min_pow = self.min_pow;
+        timeline.max_rating = self.max_rating;
+        timeline.apply_mutes = self.apply_mutes;
+        timeline.ordering = self.ordering;
+        timeline
+    }
+
+    /// Collapse the header when scrolling down, and restore it when
+    /// scrolling back up or reaching the top
+    pub fn update_header_collapse(&mut self, scroll_offset: f32) {
+        let delta = scroll_offset - self.last_scroll_offset;
+
+        if scroll_offset <= 0.0 || delta < 0.0 {
+            self.header_collapsed = false;
+        } else if delta > 0.0 {
+            self.header_collapsed = true;
         }
+
+        self.last_scroll_offset = scroll_offset;
+    }
+
+    /// The earliest `created_at` we should show in this timeline, if any
+    pub fn since(&self) -> Option<u64> {
+        if self.live_tail {
+            Some(self.opened_at)
+        } else {
+            None
+        }
+    }
+
+    pub fn column_type_icon(&self) -> &'static str {
+        self.icon.unwrap_or_else(|| self.kind.column_type_icon())
     }
 
     pub fn current_view(&self) -> &TimelineTab {
@@ -279,6 +959,45 @@ impl Timeline {
         self.views.iter_mut().find(|tab| tab.filter == view)
     }
 
+    /// Add and select a tab with `filter`, filled with the notes we
+    /// already have that pass it. New notes reach it like any other tab.
+    /// Does nothing if we already have a tab with this filter.
+    pub fn add_view(
+        &mut self,
+        filter: ViewFilter,
+        ndb: &Ndb,
+        note_cache: &mut NoteCache,
+        txn: &Transaction,
+    ) {
+        if self.view(filter).is_some() {
+            return;
+        }
+
+        // every other filter narrows notes & replies
+        let source = self
+            .view(ViewFilter::NotesAndReplies)
+            .unwrap_or_else(|| self.current_view());
+
+        // notes are sorted, so time bounds don't need to look at them
+        let mut view = match filter {
+            ViewFilter::Since(ts) => source.split_at_timestamp(ts).1,
+            ViewFilter::Until(ts) => source.split_at_timestamp(ts.saturating_add(1)).0,
+            _ => {
+                let mut view = TimelineTab::new_with_capacity(filter, source.notes.len());
+                view.notes.extend_from_slice(&source.notes);
+                view.apply_filter_inplace(&filter, ndb, note_cache, txn);
+                view
+            }
+        };
+        view.filter = filter;
+        if let Some(sub) = self.subscription {
+            view = view.with_subscription_id(sub);
+        }
+
+        self.views.push(view);
+        self.selected_view = self.views.len() - 1;
+    }
+
     pub fn poll_notes_into_view(
         timeline_idx: usize,
         mut timelines: Vec<&mut Timeline>,
@@ -286,6 +1005,7 @@ impl Timeline {
         txn: &Transaction,
         unknown_ids: &mut UnknownIds,
         note_cache: &mut NoteCache,
+        is_muted: &MuteFun,
     ) -> Result<()> {
         let timeline = timelines
             .get_mut(timeline_idx)
@@ -301,6 +1021,10 @@ impl Timeline {
             debug!("{} new notes! {:?}", new_note_ids.len(), new_note_ids);
         }
 
+        timeline.last_active = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(timeline.last_active, |d| d.as_secs());
+
         let mut new_refs: Vec<(Note, NoteRef)> = Vec::with_capacity(new_note_ids.len());
 
         for key in new_note_ids {
@@ -324,35 +1048,85 @@ impl Timeline {
         // optimization doesn't make sense in those situations.
         let reversed = false;
 
+        // muting happens when rendering, we only count muted notes here
+        let muted = if timeline.count_muted {
+            new_refs
+                .iter()
+                .filter(|(note, _nr)| {
+                    is_muted(
+                        note,
+                        root_note_id_from_selected_id(ndb, note_cache, txn, note.id()),
+                    )
+                })
+                .count()
+        } else {
+            0
+        };
+
         // ViewFilter::NotesAndReplies
         if let Some(view) = timeline.view_mut(ViewFilter::NotesAndReplies) {
             let refs: Vec<NoteRef> = new_refs.iter().map(|(_note, nr)| *nr).collect();
 
-            view.insert(&refs, reversed);
+            view.record_rejected(0, muted);
+            view.batch_insert_notes(&refs, reversed);
         }
 
         //
-        // handle the filtered case (ViewFilter::Notes, no replies)
+        // handle the filtered cases (no replies, media only, time bounded)
         //
-        // TODO(jb55): this is mostly just copied from above, let's just use a loop
-        //             I initially tried this but ran into borrow checker issues
-        if let Some(view) = timeline.view_mut(ViewFilter::Notes) {
+        let filters: Vec<ViewFilter> = timeline
+            .views
+            .iter()
+            .map(|view| view.filter)
+            .filter(|filter| *filter != ViewFilter::NotesAndReplies)
+            .collect();
+        for filter in filters {
+            let view = if let Some(view) = timeline.view_mut(filter) {
+                view
+            } else {
+                continue;
+            };
+
             let mut filtered_refs = Vec::with_capacity(new_refs.len());
             for (note, nr) in &new_refs {
                 let cached_note = note_cache.cached_note_or_insert(nr.key, note);
 
-                if ViewFilter::filter_notes(cached_note, note) {
+                if filter.filter(cached_note, note) {
                     filtered_refs.push(*nr);
                 }
             }
 
-            view.insert(&filtered_refs, reversed);
+            view.record_rejected(new_refs.len() - filtered_refs.len(), muted);
+            view.batch_insert_notes(&filtered_refs, reversed);
         }
 
         Ok(())
     }
 }
 
+/// Where the item at `index` ends up after the item at `from` is moved
+/// to `to`
+fn moved_index(index: usize, from: usize, to: usize) -> usize {
+    if index == from {
+        to
+    } else if from < index && index <= to {
+        index - 1
+    } else if to <= index && index < from {
+        index + 1
+    } else {
+        index
+    }
+}
+
+/// How many items at the end of `a` and `b` are the same
+fn common_suffix_len<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    a.iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
 pub enum MergeKind {
     FrontInsert,
     Spliced,
@@ -390,6 +1164,41 @@ pub fn merge_sorted_vecs<T: Ord + Copy>(vec1: &[T], vec2: &[T]) -> (Vec<T>, Merg
     (merged, result.unwrap_or(MergeKind::FrontInsert))
 }
 
+/// Like [`merge_sorted_vecs`], but elements in both vecs are only kept
+/// once. Both vecs must be sorted and free of duplicates.
+pub fn merge_sorted_dedup_vecs<T: Ord + Copy>(vec1: &[T], vec2: &[T]) -> (Vec<T>, MergeKind) {
+    let mut merged = Vec::with_capacity(vec1.len() + vec2.len());
+    let mut i = 0;
+    let mut j = 0;
+    let mut result: Option<MergeKind> = None;
+
+    while i < vec1.len() && j < vec2.len() {
+        match vec1[i].cmp(&vec2[j]) {
+            std::cmp::Ordering::Less => {
+                if result.is_none() {
+                    result = Some(MergeKind::Spliced);
+                }
+                merged.push(vec1[i]);
+                i += 1;
+            }
+            std::cmp::Ordering::Equal => {
+                merged.push(vec1[i]);
+                i += 1;
+                j += 1;
+            }
+            std::cmp::Ordering::Greater => {
+                merged.push(vec2[j]);
+                j += 1;
+            }
+        }
+    }
+
+    merged.extend_from_slice(&vec1[i..]);
+    merged.extend_from_slice(&vec2[j..]);
+
+    (merged, result.unwrap_or(MergeKind::FrontInsert))
+}
+
 /// When adding a new timeline, we may have a situation where the
 /// FilterState is NeedsRemote. This can happen if we don't yet have the
 /// contact list, etc. For these situations, we query all of the relays
@@ -498,7 +1307,9 @@ pub fn send_initial_timeline_filter(
 
             //let sub_id = damus.gen_subid(&SubKind::Initial);
             let sub_id = subscriptions::new_sub_id();
-            subs.subs.insert(sub_id.clone(), SubKind::Initial);
+            subs.subs
+                .insert(sub_id.clone(), SubKind::Initial(timeline.id));
+            timeline.backfill.insert(relay.url().to_owned(), false);
 
             if let Err(err) = relay.subscribe(sub_id, new_filters) {
                 error!("error subscribing: {err}");
@@ -542,7 +1353,7 @@ fn setup_initial_timeline(
     note_cache: &mut NoteCache,
     filters: &[Filter],
 ) -> Result<()> {
-    timeline.subscription = Some(ndb.subscribe(filters)?);
+    timeline.set_subscription(ndb.subscribe(filters)?);
     let txn = Transaction::new(ndb)?;
     debug!(
         "querying nostrdb sub {:?} {:?}",
@@ -567,25 +1378,25 @@ pub fn copy_notes_into_timeline(
     note_cache: &mut NoteCache,
     notes: Vec<NoteRef>,
 ) {
-    let filters = {
-        let views = &timeline.views;
-        let filters: Vec<fn(&CachedNote, &Note) -> bool> =
-            views.iter().map(|v| v.filter.filter()).collect();
-        filters
-    };
+    let filters: Vec<ViewFilter> = timeline.views.iter().map(|v| v.filter).collect();
 
+    let mut view_refs: Vec<Vec<NoteRef>> = vec![Vec::with_capacity(notes.len()); filters.len()];
     for note_ref in notes {
         for (view, filter) in filters.iter().enumerate() {
             if let Ok(note) = ndb.get_note_by_key(txn, note_ref.key) {
-                if filter(
+                if filter.filter(
                     note_cache.cached_note_or_insert_mut(note_ref.key, &note),
                     &note,
                 ) {
-                    timeline.views[view].notes.push(note_ref)
+                    view_refs[view].push(note_ref)
                 }
             }
         }
     }
+
+    for (view, refs) in timeline.views.iter_mut().zip(view_refs) {
+        view.batch_insert_notes(&refs, false);
+    }
 }
 
 pub fn setup_initial_nostrdb_subs(
@@ -704,3 +1515,155 @@ pub fn is_timeline_ready(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_and_hide_views() {
+        let mut timeline = Timeline::new(
+            TimelineKind::Universe,
+            FilterState::ready(vec![]),
+            TimelineTab::full_tabs(),
+        );
+        timeline.selected_view = 1;
+
+        // Notes, Notes & Replies, Media -> Media, Notes, Notes & Replies
+        timeline.move_view(2, 0);
+        assert_eq!(timeline.views[0].filter, ViewFilter::Media);
+        assert_eq!(timeline.current_view().filter, ViewFilter::NotesAndReplies);
+
+        timeline.set_view_visible(2, false);
+        assert_eq!(timeline.current_view().filter, ViewFilter::Notes);
+
+        // the last visible view stays
+        timeline.set_view_visible(0, false);
+        timeline.set_view_visible(1, false);
+        assert!(!timeline.views[1].hidden);
+        assert_eq!(timeline.current_view().filter, ViewFilter::Notes);
+    }
+
+    #[test]
+    fn test_common_suffix_len() {
+        assert_eq!(common_suffix_len(&[1, 2, 3], &[0, 2, 3]), 2);
+        assert_eq!(common_suffix_len(&[1, 2, 3], &[5, 4, 1, 2, 3]), 3);
+        assert_eq!(common_suffix_len(&[1, 2, 3], &[3, 2]), 0);
+        assert_eq!(common_suffix_len::<u8>(&[], &[1]), 0);
+    }
+
+    #[test]
+    fn test_view_filter_time_range() {
+        assert!(ViewFilter::Since(100).in_time_range(100));
+        assert!(!ViewFilter::Since(100).in_time_range(99));
+        assert!(ViewFilter::Until(100).in_time_range(100));
+        assert!(!ViewFilter::Until(100).in_time_range(101));
+        assert!(ViewFilter::Notes.in_time_range(0));
+    }
+
+    #[test]
+    fn test_subscribed_tabs_are_live() {
+        let mut timeline = Timeline::hashtag("nostr".to_string());
+        assert!(!timeline.current_view().is_live());
+
+        timeline.set_subscription(Subscription::new(1));
+        assert!(timeline.views.iter().all(|view| view.is_live()));
+
+        let tab = TimelineTab::new(ViewFilter::Media).with_subscription_id(Subscription::new(1));
+        assert!(tab.is_live());
+    }
+
+    #[test]
+    fn test_split_at_timestamp() {
+        let mut tab = TimelineTab::new(ViewFilter::Notes);
+        let refs: Vec<NoteRef> = [40, 30, 20, 10]
+            .into_iter()
+            .enumerate()
+            .map(|(key, created_at)| NoteRef {
+                key: NoteKey::new(key as u64 + 1),
+                created_at,
+            })
+            .collect();
+        tab.insert(&refs, false);
+
+        let (before, after) = tab.split_at_timestamp(30);
+        let times =
+            |tab: &TimelineTab| -> Vec<u64> { tab.notes.iter().map(|n| n.created_at).collect() };
+        assert_eq!(times(&before), vec![20, 10]);
+        assert_eq!(times(&after), vec![40, 30]);
+
+        let (before, after) = tab.split_at_timestamp(100);
+        assert_eq!(times(&before), vec![40, 30, 20, 10]);
+        assert!(after.notes.is_empty());
+    }
+
+    #[test]
+    fn test_select_up_down() {
+        let mut tab = TimelineTab::new(ViewFilter::Notes);
+        tab.select_down();
+        assert_eq!(tab.selection, None);
+
+        let refs: Vec<NoteRef> = (1..=2)
+            .map(|key| NoteRef {
+                key: NoteKey::new(key),
+                created_at: key,
+            })
+            .collect();
+        tab.insert(&refs, false);
+
+        tab.select_down();
+        assert_eq!(tab.selection, Some(0));
+        tab.select_down();
+        tab.select_down();
+        assert_eq!(tab.selection, Some(1));
+        tab.select_up();
+        tab.select_up();
+        assert_eq!(tab.selection, Some(0));
+    }
+
+    #[test]
+    fn test_batch_insert_notes() {
+        let note = |key: u64, created_at: u64| NoteRef {
+            key: NoteKey::new(key),
+            created_at,
+        };
+
+        let mut tab = TimelineTab::new(ViewFilter::Notes);
+        tab.insert(&[note(1, 40), note(2, 20)], false);
+        tab.batch_insert_notes(&[note(3, 10), note(2, 20), note(4, 30), note(4, 30)], false);
+
+        let times: Vec<u64> = tab.notes.iter().map(|n| n.created_at).collect();
+        assert_eq!(times, vec![40, 30, 20, 10]);
+        assert_eq!(tab.note_insertion_stats().duplicate, 2);
+    }
+
+    #[test]
+    fn test_has_media_link() {
+        assert!(has_media_link("look https://example.com/cat.JPG"));
+        assert!(has_media_link("https://example.com/clip.mp4?t=10 wow"));
+        assert!(!has_media_link("https://example.com/article.html"));
+        assert!(!has_media_link("I like .png files"));
+        assert!(!has_media_link("https://example.com"));
+    }
+
+    #[test]
+    fn test_group_by_hour() {
+        let mut tab = TimelineTab::new(ViewFilter::Notes);
+        let refs: Vec<NoteRef> = [7300, 7200, 3599, 10]
+            .into_iter()
+            .enumerate()
+            .map(|(key, created_at)| NoteRef {
+                key: NoteKey::new(key as u64 + 1),
+                created_at,
+            })
+            .collect();
+        tab.insert(&refs, false);
+
+        let groups: Vec<(u64, Vec<u64>)> = tab
+            .group_by_hour()
+            .into_iter()
+            .map(|(hour, notes)| (hour, notes.iter().map(|n| n.created_at).collect()))
+            .collect();
+        assert_eq!(groups, vec![(7200, vec![7300, 7200]), (0, vec![3599, 10])]);
+    }
+}
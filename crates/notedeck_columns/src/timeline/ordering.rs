@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use nostrdb::Note;
+use notedeck::NoteRef;
+
+/// What an [`OrderingStrategy`] knows about a note
+#[derive(Debug, Clone, Copy)]
+pub struct FeedNote {
+    pub note_ref: NoteRef,
+    pub id: [u8; 32],
+    pub author: [u8; 32],
+    /// The id of the reposted note, if this is a repost
+    pub repost_of: Option<[u8; 32]>,
+}
+
+impl FeedNote {
+    pub fn new(note: &Note, note_ref: NoteRef) -> Self {
+        let repost_of = if note.kind() == 6 {
+            note.tags()
+                .iter()
+                .find(|tag| tag.count() >= 2 && tag.get_unchecked(0).variant().str() == Some("e"))
+                .and_then(|tag| tag.get_unchecked(1).variant().id())
+                .copied()
+        } else {
+            None
+        };
+
+        FeedNote {
+            note_ref,
+            id: *note.id(),
+            author: *note.pubkey(),
+            repost_of,
+        }
+    }
+}
+
+/// Decides the order notes are shown in. Strategies are given notes
+/// newest first and must be deterministic, so that the same notes always
+/// come out in the same order. New notes should end up in front of the
+/// previous result where possible. Rows after the first one that moved
+/// keep their place in the virtual list, see
+/// [`TimelineTab::apply_ordering`](super::TimelineTab::apply_ordering).
+pub trait OrderingStrategy {
+    fn order(&self, notes: &[FeedNote]) -> Vec<NoteRef>;
+}
+
+/// Newest first, the way notes are stored
+pub struct Chronological;
+
+impl OrderingStrategy for Chronological {
+    fn order(&self, notes: &[FeedNote]) -> Vec<NoteRef> {
+        notes.iter().map(|note| note.note_ref).collect()
+    }
+}
+
+/// Newest first, but within each window of `window` seconds notes by the
+/// same author are grouped together, authors ordered by their newest
+/// note. This cuts down on context switching when catching up.
+pub struct CatchUp {
+    pub window: u64,
+}
+
+impl OrderingStrategy for CatchUp {
+    fn order(&self, notes: &[FeedNote]) -> Vec<NoteRef> {
+        let mut ordered = Vec::with_capacity(notes.len());
+        let bucket = |note: &FeedNote| note.note_ref.created_at / self.window.max(1);
+
+        let mut start = 0;
+        while start < notes.len() {
+            let current = bucket(&notes[start]);
+            let end = notes[start..]
+                .iter()
+                .position(|note| bucket(note) != current)
+                .map_or(notes.len(), |len| start + len);
+            let window = &notes[start..end];
+
+            let mut authors: Vec<&[u8; 32]> = vec![];
+            for note in window {
+                if !authors.contains(&&note.author) {
+                    authors.push(&note.author);
+                }
+            }
+
+            for author in authors {
+                ordered.extend(
+                    window
+                        .iter()
+                        .filter(|note| &note.author == author)
+                        .map(|note| note.note_ref),
+                );
+            }
+
+            start = end;
+        }
+
+        ordered
+    }
+}
+
+/// Newest first, showing each note only once: reposts of a note we
+/// already have, either the original or an earlier repost, are dropped.
+/// Keeping the earliest copy means new reposts never move anything.
+pub struct DedupedReposts;
+
+impl OrderingStrategy for DedupedReposts {
+    fn order(&self, notes: &[FeedNote]) -> Vec<NoteRef> {
+        let mut seen: HashSet<[u8; 32]> = HashSet::new();
+        let mut ordered: Vec<NoteRef> = notes
+            .iter()
+            .rev()
+            .filter(|note| seen.insert(note.repost_of.unwrap_or(note.id)))
+            .map(|note| note.note_ref)
+            .collect();
+
+        ordered.reverse();
+        ordered
+    }
+}
+
+/// The home feed orderings users can pick from
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum FeedOrder {
+    #[default]
+    Chronological,
+    CatchUp,
+    DedupedReposts,
+}
+
+impl FeedOrder {
+    pub const ALL: [FeedOrder; 3] = [
+        FeedOrder::Chronological,
+        FeedOrder::CatchUp,
+        FeedOrder::DedupedReposts,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FeedOrder::Chronological => "Chronological",
+            FeedOrder::CatchUp => "Catch up",
+            FeedOrder::DedupedReposts => "No repeat reposts",
+        }
+    }
+
+    pub fn strategy(&self) -> &'static dyn OrderingStrategy {
+        match self {
+            FeedOrder::Chronological => &Chronological,
+            FeedOrder::CatchUp => &CatchUp { window: 60 * 60 },
+            FeedOrder::DedupedReposts => &DedupedReposts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostrdb::NoteKey;
+
+    fn feed_note(key: u64, created_at: u64, author: u8, repost_of: Option<u8>) -> FeedNote {
+        FeedNote {
+            note_ref: NoteRef {
+                key: NoteKey::new(key),
+                created_at,
+            },
+            id: [key as u8; 32],
+            author: [author; 32],
+            repost_of: repost_of.map(|id| [id; 32]),
+        }
+    }
+
+    fn keys(refs: &[NoteRef]) -> Vec<u64> {
+        refs.iter().map(|nr| nr.key.as_u64()).collect()
+    }
+
+    #[test]
+    fn test_chronological() {
+        let notes = [feed_note(3, 30, 1, None), feed_note(2, 20, 2, None)];
+        assert_eq!(keys(&Chronological.order(&notes)), vec![3, 2]);
+    }
+
+    #[test]
+    fn test_catch_up_groups_authors_within_window() {
+        let notes = [
+            feed_note(5, 250, 1, None),
+            feed_note(4, 240, 2, None),
+            feed_note(3, 230, 1, None),
+            // a new window
+            feed_note(2, 150, 2, None),
+            feed_note(1, 140, 1, None),
+        ];
+
+        let catch_up = CatchUp { window: 100 };
+        assert_eq!(keys(&catch_up.order(&notes)), vec![5, 3, 4, 2, 1]);
+
+        // stable: the same notes always come out the same way
+        assert_eq!(catch_up.order(&notes), catch_up.order(&notes));
+    }
+
+    #[test]
+    fn test_deduped_reposts_keep_earliest_copy() {
+        let notes = [
+            feed_note(4, 40, 3, Some(1)),
+            feed_note(3, 30, 2, Some(9)),
+            feed_note(2, 20, 2, Some(1)),
+            feed_note(1, 10, 1, None),
+        ];
+
+        assert_eq!(keys(&DedupedReposts.order(&notes)), vec![3, 1]);
+
+        // new notes only ever show up in front
+        let older = DedupedReposts.order(&notes[1..]);
+        let newer = DedupedReposts.order(&notes);
+        assert!(newer.ends_with(&older));
+    }
+}
@@ -0,0 +1,227 @@
+use enostr::{ClientMessage, RelayPool, RelayStatus};
+use nostrdb::{Filter, Ndb, Note, NoteBuilder, NoteKey, Transaction};
+use notedeck::Accounts;
+use std::collections::HashSet;
+use tracing::{error, info};
+
+use crate::column::Columns;
+use crate::post::add_client_tag;
+
+/// The kind our read markers are published as
+pub const READ_MARKER_KIND: u32 = 15;
+
+/// How long notes scrolled past are collected before they're published
+/// together in a single read marker
+pub const READ_MARKER_BATCH_SECS: u64 = 5;
+
+/// The tag holding when the newest note a read marker covers was created,
+/// everything before it counts as read
+pub const READ_UNTIL_TAG: &str = "read_until";
+
+static READ_MARKERS_SUBID: &str = "read-markers";
+
+/// Notes read in a column, waiting to be published as a read marker, see
+/// [`TimelineView::with_read_marker_sync`](crate::ui::TimelineView::with_read_marker_sync)
+#[derive(Debug, Clone, Default)]
+pub struct ReadMarkers {
+    /// Every note we marked read, so scrolling back and forth doesn't
+    /// publish them again
+    marked: HashSet<NoteKey>,
+    /// Notes marked read since the last published marker
+    pending: Vec<NoteKey>,
+    /// When the oldest pending note was marked read
+    pending_since: Option<u64>,
+    /// Whether we asked relays for the markers our other devices published
+    subscribed: bool,
+}
+
+impl ReadMarkers {
+    pub fn mark_read(&mut self, notes: &[NoteKey], now: u64) {
+        for key in notes {
+            if self.marked.insert(*key) {
+                self.pending.push(*key);
+            }
+        }
+
+        if !self.pending.is_empty() && self.pending_since.is_none() {
+            self.pending_since = Some(now);
+        }
+    }
+
+    /// When the pending notes should be published
+    pub fn next_due(&self) -> Option<u64> {
+        self.pending_since
+            .map(|since| since + READ_MARKER_BATCH_SECS)
+    }
+
+    /// The pending notes, once they've been collected for a full batch
+    fn take_due(&mut self, now: u64) -> Vec<NoteKey> {
+        match self.next_due() {
+            Some(due) if due <= now => {
+                self.pending_since = None;
+                std::mem::take(&mut self.pending)
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Publish the pending notes as one read marker from the selected
+    /// account. Notes older than the newest marker we have, which may
+    /// come from another device, were already read and are left out.
+    pub fn publish_due(
+        &mut self,
+        ndb: &Ndb,
+        txn: &Transaction,
+        accounts: &Accounts,
+        pool: &mut RelayPool,
+        now: u64,
+    ) {
+        let kp = if let Some(kp) = accounts
+            .get_selected_account()
+            .and_then(|acc| accounts.get_full(acc.pubkey.bytes()))
+        {
+            kp
+        } else {
+            // nothing to sign with, drop what we collected
+            self.take_due(now);
+            return;
+        };
+
+        // relays that connect later get the subscription when they open,
+        // see [`send_initial_filter`]
+        let connected = pool
+            .relays
+            .iter()
+            .any(|relay| relay.status() == RelayStatus::Connected);
+        if !self.subscribed && connected {
+            pool.subscribe(
+                READ_MARKERS_SUBID.to_owned(),
+                vec![read_markers_filter(kp.pubkey.bytes())],
+            );
+            self.subscribed = true;
+        }
+
+        let due = self.take_due(now);
+        if due.is_empty() {
+            return;
+        }
+
+        let last_seen = last_seen(ndb, txn, kp.pubkey.bytes());
+        let notes: Vec<Note> = due
+            .iter()
+            .filter_map(|key| ndb.get_note_by_key(txn, *key).ok())
+            .filter(|note| note.created_at() > last_seen)
+            .collect();
+
+        let read_until = if let Some(newest) = notes.iter().map(|note| note.created_at()).max() {
+            newest
+        } else {
+            return;
+        };
+        let ids: Vec<String> = notes.iter().map(|note| hex::encode(note.id())).collect();
+
+        let mut builder = add_client_tag(NoteBuilder::new())
+            .kind(READ_MARKER_KIND)
+            .content("")
+            .start_tag()
+            .tag_str(READ_UNTIL_TAG)
+            .tag_str(&read_until.to_string());
+        for id in &ids {
+            builder = builder.start_tag().tag_str("e").tag_str(id);
+        }
+
+        let seckey = kp.secret_key.to_secret_bytes();
+        let note = if let Some(note) = builder.sign(&seckey).build() {
+            note
+        } else {
+            error!("failed to build read marker");
+            return;
+        };
+
+        match ClientMessage::event(note) {
+            Ok(msg) => {
+                info!("publishing read marker for {} notes", ids.len());
+                pool.send(&msg);
+            }
+            Err(err) => error!("failed to publish read marker: {err}"),
+        }
+    }
+}
+
+/// Our latest read marker, so we hear about the ones our other devices
+/// publish
+pub fn read_markers_filter(pubkey: &[u8; 32]) -> Filter {
+    Filter::new()
+        .authors([pubkey])
+        .kinds([READ_MARKER_KIND as u64])
+        .limit(1)
+        .build()
+}
+
+/// Ask a relay that just connected for our read markers, when a column
+/// syncs them. Relays forget our subscriptions when we disconnect.
+pub fn send_initial_filter(
+    columns: &Columns,
+    accounts: &Accounts,
+    pool: &mut RelayPool,
+    relay_url: &str,
+) {
+    if !columns
+        .timelines()
+        .iter()
+        .any(|timeline| timeline.read_markers.is_some())
+    {
+        return;
+    }
+
+    if let Some(acc) = accounts.get_selected_account() {
+        pool.send_to(
+            &ClientMessage::req(
+                READ_MARKERS_SUBID.to_owned(),
+                vec![read_markers_filter(acc.pubkey.bytes())],
+            ),
+            relay_url,
+        );
+    }
+}
+
+/// The creation time of the newest note `pubkey` marked read from any
+/// device, or 0
+pub fn last_seen(ndb: &Ndb, txn: &Transaction, pubkey: &[u8; 32]) -> u64 {
+    ndb.query(txn, &[read_markers_filter(pubkey)], 1)
+        .ok()
+        .and_then(|results| results.iter().filter_map(|r| read_until(&r.note)).max())
+        .unwrap_or(0)
+}
+
+/// The [`READ_UNTIL_TAG`] of a read marker
+fn read_until(note: &Note) -> Option<u64> {
+    note.tags()
+        .iter()
+        .find(|tag| tag.get(0).and_then(|t| t.variant().str()) == Some(READ_UNTIL_TAG))
+        .and_then(|tag| tag.get(1)?.variant().str()?.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_markers_batch() {
+        let mut markers = ReadMarkers::default();
+        markers.mark_read(&[NoteKey::new(1), NoteKey::new(2)], 100);
+        markers.mark_read(&[NoteKey::new(2), NoteKey::new(3)], 103);
+
+        assert_eq!(markers.next_due(), Some(105));
+        assert!(markers.take_due(104).is_empty());
+        assert_eq!(
+            markers.take_due(105),
+            vec![NoteKey::new(1), NoteKey::new(2), NoteKey::new(3)]
+        );
+        assert_eq!(markers.next_due(), None);
+
+        // already marked notes don't start a new batch
+        markers.mark_read(&[NoteKey::new(1)], 110);
+        assert_eq!(markers.next_due(), None);
+    }
+}
@@ -0,0 +1,69 @@
+use nostrdb::NoteKey;
+use std::collections::{HashSet, VecDeque};
+
+/// A set of note keys that forgets its oldest entries past a capacity, so
+/// per-session bookkeeping doesn't grow for as long as the app is open
+#[derive(Debug)]
+pub struct RecentNotes {
+    keys: HashSet<NoteKey>,
+    order: VecDeque<NoteKey>,
+    cap: usize,
+}
+
+impl RecentNotes {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            keys: HashSet::new(),
+            order: VecDeque::new(),
+            cap,
+        }
+    }
+
+    pub fn contains(&self, key: &NoteKey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// Add a key, forgetting the oldest one if we're full. Returns whether
+    /// the key was new.
+    pub fn insert(&mut self, key: NoteKey) -> bool {
+        if !self.keys.insert(key) {
+            return false;
+        }
+
+        self.order.push_back(key);
+        if self.order.len() > self.cap {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_notes_forgets_oldest() {
+        let mut recent = RecentNotes::new(2);
+        assert!(recent.insert(NoteKey::new(1)));
+        assert!(!recent.insert(NoteKey::new(1)));
+        assert!(recent.insert(NoteKey::new(2)));
+        assert!(recent.insert(NoteKey::new(3)));
+
+        assert_eq!(recent.len(), 2);
+        assert!(!recent.contains(&NoteKey::new(1)));
+        assert!(recent.contains(&NoteKey::new(2)));
+        assert!(recent.contains(&NoteKey::new(3)));
+    }
+}
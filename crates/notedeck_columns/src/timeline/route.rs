@@ -1,11 +1,13 @@
 use crate::{
     column::Columns,
     draft::Drafts,
+    help::{self, SeenHelp},
     nav::RenderNavAction,
     notes_holder::NotesHolderStorage,
     profile::{Profile, ProfileAction},
+    snooze::Snoozes,
     thread::Thread,
-    timeline::{TimelineId, TimelineKind},
+    timeline::{kind::ListKind, TimelineId, TimelineKind, TimelineSettings},
     ui::{
         self,
         note::{NoteOptions, QuoteRepostView},
@@ -14,8 +16,8 @@ use crate::{
 };
 
 use enostr::{NoteId, Pubkey};
-use nostrdb::{Ndb, Transaction};
-use notedeck::{Accounts, ImageCache, MuteFun, NoteCache, UnknownIds};
+use nostrdb::{Ndb, Note, Transaction};
+use notedeck::{unix_now, Accounts, ImageCache, MuteFun, NoteCache, UnknownIds};
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 pub enum TimelineRoute {
@@ -39,35 +41,114 @@ pub fn render_timeline_route(
     accounts: &mut Accounts,
     route: TimelineRoute,
     col: usize,
-    textmode: bool,
+    note_options: NoteOptions,
+    debug: bool,
+    settings: &TimelineSettings,
+    snoozes: &Snoozes,
+    seen_help: &SeenHelp,
     ui: &mut egui::Ui,
 ) -> Option<RenderNavAction> {
     match route {
         TimelineRoute::Timeline(timeline_id) => {
+            let kind = columns.find_timeline(timeline_id).map(|tl| &tl.kind);
+
             let note_options = {
-                let is_universe = if let Some(timeline) = columns.find_timeline(timeline_id) {
-                    timeline.kind == TimelineKind::Universe
-                } else {
-                    false
-                };
-
-                let mut options = NoteOptions::new(is_universe);
-                options.set_textmode(textmode);
+                let is_universe = kind == Some(&TimelineKind::Universe);
+
+                let mut options = note_options;
+                options.set_hide_media(is_universe);
                 options
             };
 
-            let note_action = ui::TimelineView::new(
+            // our own reposts are redundant in our home feed
+            let is_home = matches!(kind, Some(TimelineKind::List(ListKind::Contact(_))));
+            let own_pubkey = if settings.hide_own_reposts && is_home {
+                accounts.get_selected_account().map(|a| a.pubkey)
+            } else {
+                None
+            };
+
+            let mutefun = accounts.mutefun();
+            let now = unix_now();
+            let is_hidden = move |note: &Note, thread: &[u8; 32]| {
+                snoozes.is_snoozed(thread, now)
+                    || own_pubkey
+                        .map_or(false, |pk| note.kind() == 6 && note.pubkey() == pk.bytes())
+            };
+
+            let (min_pow, max_rating, loading_progress, empty_message, teleprompter) = columns
+                .find_timeline(timeline_id)
+                .map_or((0, Default::default(), None, None, 0.0), |tl| {
+                    (
+                        tl.min_pow,
+                        tl.max_rating,
+                        tl.loading_progress(),
+                        tl.current_view().filter.empty_message(),
+                        if tl.teleprompter {
+                            tl.auto_scroll_speed
+                        } else {
+                            0.0
+                        },
+                    )
+                });
+
+            // inactive columns are already dense, so give the action bar
+            // row back too until a note is hovered
+            let compact = columns
+                .find_timeline(timeline_id)
+                .map_or(false, |tl| tl.compact_when_inactive)
+                && !columns.is_timeline_focused(timeline_id);
+
+            let mut view = ui::TimelineView::new(
                 timeline_id,
                 columns,
                 ndb,
                 note_cache,
                 img_cache,
                 note_options,
-                &accounts.mutefun(),
+                &is_hidden,
             )
-            .ui(ui);
+            .with_debug_overlay(debug)
+            .with_column_analytics(debug)
+            .with_read_marker_sync(settings.read_markers)
+            .with_search_box(settings.timeline_search)
+            .with_tab_drag_to_reorder(settings.tab_drag_reorder)
+            .with_column_snap(settings.column_snap.is_some())
+            .with_header_collapse_on_scroll(settings.collapse_header)
+            .with_min_pow_threshold(min_pow)
+            .with_content_rating_filter(max_rating)
+            .with_loading_progress(loading_progress)
+            .with_compact_action_bar(compact)
+            .with_auto_scroll(teleprompter)
+            .with_mute_scope(&*mutefun)
+            .with_contextual_help(seen_help.unseen(help::timeline_help()));
+
+            if let Some(limit) = settings.reply_depth_limit {
+                view = view.with_reply_depth_limit(limit);
+            }
+
+            if let Some(grid) = settings.column_snap {
+                view = view.with_column_snap_grid(grid);
+            }
 
-            note_action.map(RenderNavAction::NoteAction)
+            if let Some(after) = settings.archive_after {
+                view = view.with_column_archive(after);
+            }
+
+            if let Some(watermark) = &settings.watermark {
+                view = view.with_watermark_overlay(watermark);
+            }
+
+            if let Some(msg) = empty_message {
+                view = view.with_empty_state(msg);
+            }
+
+            let mut actions = view.ui(ui).actions;
+            if actions.len() > 1 {
+                Some(RenderNavAction::NoteActions(actions))
+            } else {
+                actions.pop().map(RenderNavAction::NoteAction)
+            }
         }
 
         TimelineRoute::Thread(id) => ui::ThreadView::new(
@@ -77,10 +158,11 @@ pub fn render_timeline_route(
             unknown_ids,
             img_cache,
             id.bytes(),
-            textmode,
+            note_options,
             &accounts.mutefun(),
         )
         .id_source(egui::Id::new(("threadscroll", col)))
+        .collapse_replies(settings.reply_collapse_threshold)
         .ui(ui)
         .map(Into::into),
 
@@ -127,6 +209,7 @@ pub fn render_timeline_route(
             col,
             ui,
             &accounts.mutefun(),
+            note_options,
         ),
 
         TimelineRoute::Quote(id) => {
@@ -166,6 +249,7 @@ pub fn render_profile_route(
     col: usize,
     ui: &mut egui::Ui,
     is_muted: &MuteFun,
+    note_options: NoteOptions,
 ) -> Option<RenderNavAction> {
     let action = ProfileView::new(
         pubkey,
@@ -176,7 +260,7 @@ pub fn render_profile_route(
         note_cache,
         img_cache,
         is_muted,
-        NoteOptions::default(),
+        note_options,
     )
     .ui(ui);
 
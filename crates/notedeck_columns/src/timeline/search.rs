@@ -0,0 +1,85 @@
+use egui_virtual_list::VirtualList;
+use nostrdb::{Ndb, Transaction};
+use notedeck::NoteRef;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::profile::get_display_name;
+
+/// A search through the notes a timeline already has, see
+/// [`TimelineView::with_search_box`](crate::ui::TimelineView::with_search_box)
+#[derive(Debug, Default)]
+pub struct TimelineSearch {
+    pub query: String,
+    /// The layout of the matching notes. It's kept apart from the tab's,
+    /// so clearing the search returns to where we were.
+    pub list: Rc<RefCell<VirtualList>>,
+    matches: Vec<NoteRef>,
+    /// The query, view and note count `matches` were found for
+    matched_for: Option<(String, egui::Id, usize)>,
+}
+
+impl TimelineSearch {
+    /// Whether there's a query to filter notes with
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+    }
+
+    /// The notes matching the query, as of the last
+    /// [`TimelineSearch::update_matches`]
+    pub fn matches(&self) -> &[NoteRef] {
+        &self.matches
+    }
+
+    /// Find the notes of the view `view_id` matching the query. This only
+    /// searches again when the query, the view or its notes changed.
+    pub fn update_matches(
+        &mut self,
+        ndb: &Ndb,
+        txn: &Transaction,
+        view_id: egui::Id,
+        notes: &[NoteRef],
+    ) {
+        let key = (self.query.trim().to_lowercase(), view_id, notes.len());
+        if self.matched_for.as_ref() == Some(&key) {
+            return;
+        }
+
+        self.matches = notes
+            .iter()
+            .filter(|nr| {
+                ndb.get_note_by_key(txn, nr.key).map_or(false, |note| {
+                    let profile = ndb.get_profile_by_pubkey(txn, note.pubkey()).ok();
+                    let name = get_display_name(profile.as_ref());
+                    note_matches(&key.0, note.content(), &[name.username, name.display_name])
+                })
+            })
+            .copied()
+            .collect();
+
+        self.list.borrow_mut().reset();
+        self.matched_for = Some(key);
+    }
+}
+
+/// Whether a note's content or one of its author's names contains
+/// `query`, which should be lowercase
+fn note_matches(query: &str, content: &str, names: &[Option<&str>]) -> bool {
+    content.to_lowercase().contains(query)
+        || names
+            .iter()
+            .flatten()
+            .any(|name| name.to_lowercase().contains(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_matches() {
+        assert!(note_matches("gm", "GM nostr", &[]));
+        assert!(note_matches("jack", "hello", &[None, Some("Jack")]));
+        assert!(!note_matches("bitcoin", "hello", &[Some("jack"), None]));
+    }
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+/// How timeline columns behave, from the command line. Passed to
+/// [`render_timeline_route`](crate::timeline::route::render_timeline_route)
+/// as a whole rather than one argument per option.
+#[derive(Debug, Clone, Default)]
+pub struct TimelineSettings {
+    /// Collapse thread replies with this many or fewer reactions and zaps
+    pub reply_collapse_threshold: Option<u32>,
+
+    /// Collapse timeline replies deeper than this in their thread
+    pub reply_depth_limit: Option<u8>,
+
+    /// Hide column headers while scrolling down through timelines
+    pub collapse_header: bool,
+
+    /// Hide the selected account's own reposts from their home feed
+    pub hide_own_reposts: bool,
+
+    /// Publish and sync read markers for the notes scrolled past
+    pub read_markers: bool,
+
+    /// Show a box above timelines for searching their notes
+    pub timeline_search: bool,
+
+    /// Whether timeline tabs can be reordered by dragging them
+    pub tab_drag_reorder: bool,
+
+    /// The grid resized columns snap to, if any
+    pub column_snap: Option<f32>,
+
+    /// How long a column can go without new notes or scrolling before
+    /// it's archived, if ever
+    pub archive_after: Option<Duration>,
+
+    /// Text drawn faintly across every timeline column, for branded
+    /// deployments
+    pub watermark: Option<String>,
+}
@@ -0,0 +1,76 @@
+use crate::{timeline::Timeline, Result};
+
+use nostrdb::{Ndb, Transaction};
+use notedeck::{storage, DataPath, DataPathType};
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+/// A point-in-time dump of a column's loaded notes, along with enough
+/// metadata to make sense of it later
+#[derive(Serialize)]
+pub struct TimelineSnapshot {
+    /// unix timestamp of when the snapshot was taken
+    pub taken_at: u64,
+    pub kind: String,
+    pub view: String,
+    pub filters: Vec<serde_json::Value>,
+    pub relays: Vec<String>,
+    pub notes: Vec<serde_json::Value>,
+}
+
+impl TimelineSnapshot {
+    /// Snapshot the currently selected view of a timeline. `relays` are the
+    /// relays we were connected to when the notes were loaded.
+    pub fn new(timeline: &Timeline, ndb: &Ndb, relays: Vec<String>) -> Result<Self> {
+        let txn = Transaction::new(ndb)?;
+        let tab = timeline.current_view();
+
+        let filters = timeline
+            .filter
+            .get_any_ready()
+            .map(|filters| {
+                filters
+                    .iter()
+                    .filter_map(|f| f.json().ok())
+                    .filter_map(|json| serde_json::from_str(&json).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let taken_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Ok(TimelineSnapshot {
+            taken_at,
+            kind: timeline.kind.to_string(),
+            view: tab.filter.name().to_owned(),
+            filters,
+            relays,
+            notes: tab.snapshot(ndb, &txn),
+        })
+    }
+
+    fn file_name(&self, timeline: &Timeline) -> String {
+        format!("snapshot-{}-{}.json", timeline.id, self.taken_at)
+    }
+
+    /// Write the snapshot to the snapshots cache directory, returning the
+    /// name of the file that was written
+    pub fn save(&self, path: &DataPath, timeline: &Timeline) -> Result<String> {
+        let directory = path.path(DataPathType::Cache).join("snapshots");
+        let file_name = self.file_name(timeline);
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        storage::write_file(&directory, file_name.clone(), &json)?;
+        info!(
+            "wrote {} notes to snapshot {}",
+            self.notes.len(),
+            directory.join(&file_name).display()
+        );
+
+        Ok(file_name)
+    }
+}
@@ -26,6 +26,7 @@ pub enum AddColumnResponse {
     Hashtag,
     UndecidedIndividual,
     ExternalIndividual,
+    Scheduled,
 }
 
 pub enum NotificationColumnType {
@@ -45,6 +46,7 @@ enum AddColumnOption {
     UndecidedIndividual,
     ExternalIndividual,
     Individual(PubkeySource),
+    Scheduled,
 }
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
@@ -90,6 +92,7 @@ impl AddColumnOption {
                 tlk.into_timeline(ndb, cur_account.map(|a| a.pubkey.bytes()))
                     .map(AddColumnResponse::Timeline)
             }
+            AddColumnOption::Scheduled => Some(AddColumnResponse::Scheduled),
         }
     }
 }
@@ -377,6 +380,18 @@ impl<'a> AddColumnView<'a> {
             option: AddColumnOption::UndecidedIndividual,
         });
 
+        if self
+            .cur_account
+            .map_or(false, |acc| acc.secret_key.is_some())
+        {
+            vec.push(ColumnOptionData {
+                title: "Scheduled",
+                description: "Notes you scheduled to publish later",
+                icon: egui::include_image!("../../../../assets/icons/edit_icon_4x_dark.png"),
+                option: AddColumnOption::Scheduled,
+            });
+        }
+
         vec
     }
 
@@ -549,6 +564,12 @@ pub fn render_add_column_routes(
                         AddColumnRoute::ExternalIndividual,
                     ));
             }
+            AddColumnResponse::Scheduled => {
+                app.columns_mut(ctx.accounts)
+                    .column_mut(col)
+                    .router_mut()
+                    .route_to_replaced(crate::route::Route::Scheduled);
+            }
         };
     }
 }
@@ -0,0 +1,42 @@
+use crate::column::Columns;
+use crate::timeline::TimelineId;
+use egui::{Align2, CollapsingHeader, Frame, Id};
+
+/// A drawer in the bottom right corner listing the archived columns, see
+/// [`Columns::archive_column`]. Returns the timeline whose column the
+/// user asked to restore.
+pub fn archived_drawer(ctx: &egui::Context, columns: &Columns) -> Option<TimelineId> {
+    let archived = columns.archived_timelines();
+    if archived.is_empty() {
+        return None;
+    }
+
+    egui::Area::new(Id::new("archived_columns"))
+        .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0))
+        .show(ctx, |ui| {
+            Frame::popup(ui.style())
+                .show(ui, |ui| {
+                    CollapsingHeader::new(format!("Archived ({})", archived.len()))
+                        .id_salt("archived_columns_drawer")
+                        .show(ui, |ui| {
+                            let mut restore = None;
+                            for timeline in archived {
+                                ui.horizontal(|ui| {
+                                    let icon = timeline
+                                        .icon
+                                        .unwrap_or_else(|| timeline.kind.column_type_icon());
+                                    ui.label(format!("{} {}", icon, timeline.kind));
+                                    if ui.small_button("Restore").clicked() {
+                                        restore = Some(timeline.id);
+                                    }
+                                });
+                            }
+                            restore
+                        })
+                        .body_returned
+                        .flatten()
+                })
+                .inner
+        })
+        .inner
+}
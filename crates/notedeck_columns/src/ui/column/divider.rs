@@ -0,0 +1,84 @@
+use egui::{Color32, Rect};
+
+/// How adjacent columns are separated
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColumnDivider {
+    /// A 1px vertical line
+    #[default]
+    Line,
+    /// This many points of empty space
+    Gap(f32),
+    /// A soft shadow along the column's right edge
+    Shadow,
+}
+
+/// How wide the [`ColumnDivider::Shadow`] gradient is, in points
+const SHADOW_WIDTH: f32 = 8.0;
+
+impl ColumnDivider {
+    /// Parse a divider from the command line: `line`, `shadow`, or a gap
+    /// width in points
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "line" => Some(ColumnDivider::Line),
+            "shadow" => Some(ColumnDivider::Shadow),
+            gap => gap
+                .parse::<f32>()
+                .ok()
+                .filter(|gap| *gap >= 0.0)
+                .map(ColumnDivider::Gap),
+        }
+    }
+
+    /// The space to leave empty on a column's right edge
+    pub fn gap(&self) -> f32 {
+        match self {
+            ColumnDivider::Gap(gap) => *gap,
+            ColumnDivider::Line | ColumnDivider::Shadow => 0.0,
+        }
+    }
+
+    /// Draw the divider on the right edge of the column at `rect`
+    pub fn paint(&self, ui: &egui::Ui, rect: Rect) {
+        match self {
+            ColumnDivider::Line => {
+                ui.painter().vline(
+                    rect.right(),
+                    rect.y_range(),
+                    ui.visuals().widgets.noninteractive.bg_stroke,
+                );
+            }
+
+            ColumnDivider::Gap(_) => {}
+
+            ColumnDivider::Shadow => {
+                // egui has no gradients, so approximate one with thin
+                // strips that fade out away from the edge
+                let steps = SHADOW_WIDTH as usize;
+                let max_alpha = if ui.visuals().dark_mode { 90.0 } else { 40.0 };
+                for step in 0..steps {
+                    let x = rect.right() - step as f32 - 1.0;
+                    let strip = Rect::from_x_y_ranges(x..=x + 1.0, rect.y_range());
+                    let fade = 1.0 - step as f32 / steps as f32;
+                    let alpha = (max_alpha * fade * fade) as u8;
+                    ui.painter()
+                        .rect_filled(strip, 0.0, Color32::from_black_alpha(alpha));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_divider() {
+        assert_eq!(ColumnDivider::parse("line"), Some(ColumnDivider::Line));
+        assert_eq!(ColumnDivider::parse("shadow"), Some(ColumnDivider::Shadow));
+        assert_eq!(ColumnDivider::parse("12"), Some(ColumnDivider::Gap(12.0)));
+        assert_eq!(ColumnDivider::parse("-1"), None);
+        assert_eq!(ColumnDivider::parse("dotted"), None);
+    }
+}
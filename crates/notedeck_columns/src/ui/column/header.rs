@@ -2,31 +2,35 @@ use crate::{
     column::Columns,
     nav::RenderNavAction,
     route::Route,
-    timeline::{ColumnTitle, TimelineId, TimelineKind, TimelineRoute},
+    timeline::{
+        kind::ListKind, ColumnTitle, FeedOrder, TimelineId, TimelineKind, TimelineRoute, ViewFilter,
+    },
     ui::{
         self,
         anim::{AnimationHelper, ICON_EXPANSION_MULTIPLE},
+        note::content_warning::ContentRating,
     },
 };
 
 use egui::{RichText, Stroke, UiBuilder};
 use enostr::Pubkey;
 use nostrdb::{Ndb, Transaction};
-use notedeck::{ImageCache, NotedeckTextStyle};
+use notedeck::{unix_now, ImageCache, NotedeckTextStyle};
 
 pub struct NavTitle<'a> {
     ndb: &'a Ndb,
     img_cache: &'a mut ImageCache,
-    columns: &'a Columns,
+    columns: &'a mut Columns,
     deck_author: Option<&'a Pubkey>,
     routes: &'a [Route],
+    advanced: bool,
 }
 
 impl<'a> NavTitle<'a> {
     pub fn new(
         ndb: &'a Ndb,
         img_cache: &'a mut ImageCache,
-        columns: &'a Columns,
+        columns: &'a mut Columns,
         deck_author: Option<&'a Pubkey>,
         routes: &'a [Route],
     ) -> Self {
@@ -36,19 +40,32 @@ impl<'a> NavTitle<'a> {
             columns,
             deck_author,
             routes,
+            advanced: false,
         }
     }
 
+    /// Show advanced column actions, such as snapshotting a column to disk
+    pub fn advanced(mut self, enable: bool) -> Self {
+        self.advanced = enable;
+        self
+    }
+
     pub fn show(&mut self, ui: &mut egui::Ui) -> Option<RenderNavAction> {
-        ui::padding(8.0, ui, |ui| {
+        let visible = self.header_visibility(ui);
+        if visible <= 0.0 {
+            return None;
+        }
+
+        ui::padding(8.0 * visible, ui, |ui| {
             let mut rect = ui.available_rect_before_wrap();
-            rect.set_height(48.0);
+            rect.set_height(48.0 * visible);
 
             let mut child_ui = ui.new_child(
                 UiBuilder::new()
                     .max_rect(rect)
                     .layout(egui::Layout::left_to_right(egui::Align::Center)),
             );
+            child_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
 
             let r = self.title_bar(&mut child_ui);
 
@@ -59,6 +76,23 @@ impl<'a> NavTitle<'a> {
         .inner
     }
 
+    /// How much of the header to show, animating between hidden (0.0)
+    /// and fully visible (1.0) when a timeline collapses its header
+    fn header_visibility(&self, ui: &egui::Ui) -> f32 {
+        let collapsed =
+            if let Some(Route::Timeline(TimelineRoute::Timeline(tlid))) = self.routes.last() {
+                self.columns
+                    .find_timeline(*tlid)
+                    .map_or(false, |tl| tl.header_collapsed)
+            } else {
+                false
+            };
+
+        let target = if collapsed { 0.0 } else { 1.0 };
+        ui.ctx()
+            .animate_value_with_time(ui.id().with("header_collapse"), target, 0.2)
+    }
+
     fn title_bar(&mut self, ui: &mut egui::Ui) -> Option<RenderNavAction> {
         let item_spacing = 8.0;
         ui.spacing_mut().item_spacing.x = item_spacing;
@@ -77,10 +111,10 @@ impl<'a> NavTitle<'a> {
             ui.add_space(chev_x + item_spacing);
         }
 
-        let remove_column = self.title(ui, self.routes.last().unwrap(), back_button_resp.is_some());
+        let title_action = self.title(ui, self.routes.last().unwrap(), back_button_resp.is_some());
 
-        if remove_column {
-            Some(RenderNavAction::RemoveColumn)
+        if title_action.is_some() {
+            title_action
         } else if back_button_resp.map_or(false, |r| r.clicked()) {
             Some(RenderNavAction::Back)
         } else {
@@ -253,6 +287,7 @@ impl<'a> NavTitle<'a> {
             Route::ComposeNote => {}
             Route::AddColumn(_add_col_route) => {}
             Route::Support => {}
+            Route::Scheduled => {}
             Route::Relays => {}
             Route::NewDeck => {}
             Route::EditDeck(_) => {}
@@ -294,25 +329,385 @@ impl<'a> NavTitle<'a> {
         };
     }
 
-    fn title(&mut self, ui: &mut egui::Ui, top: &Route, navigating: bool) -> bool {
+    fn column_type_icon(&self, ui: &mut egui::Ui, top: &Route) {
+        let icon = if let Route::Timeline(TimelineRoute::Timeline(tlid)) = top {
+            self.columns
+                .find_timeline(*tlid)
+                .map(|tl| tl.column_type_icon())
+        } else {
+            None
+        };
+
+        if let Some(icon) = icon {
+            ui.add(Self::title_label_value(icon));
+        }
+    }
+
+    /// Whether this column holds a pinned timeline
+    fn pinned(&self) -> bool {
+        self.routes
+            .iter()
+            .find_map(|route| route.timeline_id())
+            .map_or(false, |id| self.columns.is_pinned(*id))
+    }
+
+    /// A small badge with the column's focus key, if it has one
+    fn focus_key_badge(&self, ui: &mut egui::Ui, top: &Route) {
+        let key = if let Route::Timeline(TimelineRoute::Timeline(tlid)) = top {
+            self.columns
+                .find_timeline(*tlid)
+                .and_then(|tl| tl.focus_key)
+        } else {
+            None
+        };
+
+        if let Some(key) = key {
+            egui::Frame::none()
+                .rounding(4.0)
+                .inner_margin(egui::Margin::symmetric(4.0, 1.0))
+                .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::Label::new(RichText::new(key.symbol_or_name()).small().weak())
+                            .selectable(false),
+                    )
+                })
+                .response
+                .on_hover_text(format!("Press {} to focus this column", key.name()));
+        }
+    }
+
+    fn title(
+        &mut self,
+        ui: &mut egui::Ui,
+        top: &Route,
+        navigating: bool,
+    ) -> Option<RenderNavAction> {
         if !navigating {
             self.title_pfp(ui, top, 32.0);
+            self.column_type_icon(ui, top);
             self.title_label(ui, top);
+            self.focus_key_badge(ui, top);
+            if self.pinned() {
+                ui.add(egui::Label::new("📌").selectable(false))
+                    .on_hover_text("Pinned column");
+            }
         }
 
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             if navigating {
                 self.title_label(ui, top);
+                self.column_type_icon(ui, top);
                 self.title_pfp(ui, top, 32.0);
-                false
+                None
             } else {
-                self.delete_button_section(ui)
+                // pinned columns can't be closed
+                let remove_column = !self.pinned() && self.delete_button_section(ui);
+                let settings_action = self.column_settings(ui, top);
+                if remove_column {
+                    Some(RenderNavAction::RemoveColumn)
+                } else {
+                    settings_action
+                }
             }
         })
         .inner
     }
+
+    /// Per-column toggles for timeline columns
+    fn column_settings(&mut self, ui: &mut egui::Ui, top: &Route) -> Option<RenderNavAction> {
+        let timeline_id = if let Route::Timeline(TimelineRoute::Timeline(tlid)) = top {
+            *tlid
+        } else {
+            return None;
+        };
+
+        let advanced = self.advanced;
+        let mut action = None;
+        let mut pinned = self.columns.is_pinned(timeline_id);
+        let timeline = self.columns.find_timeline_mut(timeline_id)?;
+
+        ui.menu_button("⚙", |ui| {
+            let live_resp = ui
+                .checkbox(&mut timeline.live_tail, "Live only")
+                .on_hover_text("Only show notes created since this column was opened");
+
+            if live_resp.changed() {
+                // our visible notes changed out from under the virtual list
+                for view in &timeline.views {
+                    view.list.borrow_mut().reset();
+                }
+            }
+
+            ui.checkbox(&mut timeline.thread_connectors, "Thread lines")
+                .on_hover_text("Connect neighboring notes that reply to each other");
+
+            if ui
+                .checkbox(&mut timeline.auto_scroll, "Auto-scroll on hover")
+                .on_hover_text("Slowly scroll through this column while the mouse is over it")
+                .changed()
+                && timeline.auto_scroll
+            {
+                timeline.teleprompter = false;
+            }
+
+            if ui
+                .checkbox(&mut timeline.teleprompter, "Teleprompter")
+                .on_hover_text("Slowly scroll through this column until the mouse is over it")
+                .changed()
+                && timeline.teleprompter
+            {
+                timeline.auto_scroll = false;
+            }
+
+            if timeline.auto_scroll || timeline.teleprompter {
+                ui.add(
+                    egui::Slider::new(&mut timeline.auto_scroll_speed, 5.0..=200.0)
+                        .text("pt/s")
+                        .logarithmic(true),
+                );
+            }
+
+            ui.checkbox(&mut timeline.compact_when_inactive, "Compact when inactive")
+                .on_hover_text("Pack notes tighter and hide their actions until hovered");
+
+            ui.add(egui::Slider::new(&mut timeline.min_pow, 0..=32).text("Min PoW bits"))
+                .on_hover_text("Hide notes whose id has less proof of work than this");
+
+            egui::ComboBox::from_label("Max rating")
+                .selected_text(timeline.max_rating.name())
+                .show_ui(ui, |ui| {
+                    for rating in ContentRating::ALL {
+                        ui.selectable_value(&mut timeline.max_rating, rating, rating.name());
+                    }
+                })
+                .response
+                .on_hover_text("Hide notes with content warnings above this rating");
+
+            ui.checkbox(&mut timeline.apply_mutes, "Apply mutes")
+                .on_hover_text("Turn off to show muted notes in this column, with a marker");
+
+            if matches!(timeline.kind, TimelineKind::List(ListKind::Contact(_))) {
+                egui::ComboBox::from_label("Order")
+                    .selected_text(timeline.ordering.name())
+                    .show_ui(ui, |ui| {
+                        for order in FeedOrder::ALL {
+                            ui.selectable_value(&mut timeline.ordering, order, order.name());
+                        }
+                    });
+            }
+
+            let focus_key_label = timeline
+                .focus_key
+                .map_or_else(|| "None".to_owned(), |key| key.name().to_owned());
+            egui::ComboBox::from_label("Focus key")
+                .selected_text(focus_key_label)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut timeline.focus_key, None, "None");
+                    for key in FOCUS_KEYS {
+                        ui.selectable_value(&mut timeline.focus_key, Some(key), key.name());
+                    }
+                });
+
+            ui.checkbox(&mut pinned, "Pin column")
+                .on_hover_text("Keep this column from being closed");
+
+            ui.menu_button("Tabs", |ui| {
+                for idx in 0..timeline.views.len() {
+                    let mut visible = !timeline.views[idx].hidden;
+                    let name = timeline.views[idx].filter.name();
+                    if ui.checkbox(&mut visible, name).changed() {
+                        timeline.set_view_visible(idx, visible);
+                    }
+                }
+            });
+
+            ui.menu_button("Add tab", |ui| {
+                // eg. hashtag columns start with only notes & replies
+                for filter in [ViewFilter::Notes, ViewFilter::Media] {
+                    if timeline.view(filter).is_none() && ui.button(filter.name()).clicked() {
+                        action = Some(RenderNavAction::AddTimelineView(timeline.id, filter));
+                        ui.close_menu();
+                    }
+                }
+
+                if let Some(filter) = time_range_menu(ui) {
+                    action = Some(RenderNavAction::AddTimelineView(timeline.id, filter));
+                    ui.close_menu();
+                }
+            });
+
+            if ui
+                .button("Duplicate column")
+                .on_hover_text("Open a copy of this column next to it")
+                .clicked()
+            {
+                action = Some(RenderNavAction::CloneColumn(timeline.id));
+                ui.close_menu();
+            }
+
+            ui.menu_button("Workspace", |ui| {
+                if let Some(workspace_action) = workspace_menu(ui) {
+                    action = Some(workspace_action);
+                    ui.close_menu();
+                }
+            });
+
+            if advanced {
+                ui.separator();
+
+                if ui
+                    .button("Snapshot notes")
+                    .on_hover_text("Save this column's loaded notes to disk")
+                    .clicked()
+                {
+                    action = Some(RenderNavAction::SnapshotTimeline(timeline.id));
+                    ui.close_menu();
+                }
+
+                if ui
+                    .button("Export CSV")
+                    .on_hover_text("Save this column's notes as CSV for analysis")
+                    .clicked()
+                {
+                    action = Some(RenderNavAction::ExportTimelineCsv(timeline.id));
+                    ui.close_menu();
+                }
+
+                if let Some(file) = &timeline.csv_export {
+                    let file = file.display().to_string();
+                    if ui
+                        .add(
+                            egui::Label::new(
+                                RichText::new(format!("Saved to {file}")).small().weak(),
+                            )
+                            .sense(egui::Sense::click()),
+                        )
+                        .on_hover_text("Copy the path")
+                        .clicked()
+                    {
+                        ui.output_mut(|w| w.copied_text = file);
+                        ui.close_menu();
+                    }
+                }
+
+                if timeline.analytics.is_some()
+                    && ui
+                        .button("Export analytics")
+                        .on_hover_text("Save how the columns have been used to disk")
+                        .clicked()
+                {
+                    action = Some(RenderNavAction::ExportAnalytics);
+                    ui.close_menu();
+                }
+            }
+        });
+
+        if pinned {
+            self.columns.pin_column(timeline_id);
+        } else {
+            self.columns.unpin_column(timeline_id);
+        }
+
+        action
+    }
+}
+
+/// Tabs that only show notes from a time range, counted from now
+fn time_range_menu(ui: &mut egui::Ui) -> Option<ViewFilter> {
+    let now = unix_now();
+    let day = 86_400;
+    let ranges = [
+        ("Last 24 hours", ViewFilter::Since(now.saturating_sub(day))),
+        (
+            "Last 7 days",
+            ViewFilter::Since(now.saturating_sub(7 * day)),
+        ),
+        (
+            "Older than 7 days",
+            ViewFilter::Until(now.saturating_sub(7 * day)),
+        ),
+    ];
+
+    let mut picked = None;
+    for (label, filter) in ranges {
+        if ui.button(label).clicked() {
+            picked = Some(filter);
+        }
+    }
+    picked
+}
+
+/// Save or load the deck's column layout by name
+fn workspace_menu(ui: &mut egui::Ui) -> Option<RenderNavAction> {
+    let id = egui::Id::new("workspace_name");
+    let mut name = ui
+        .data_mut(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| "Default".to_owned());
+
+    ui.add(egui::TextEdit::singleline(&mut name).hint_text("Workspace name"));
+    let has_name = !name.trim().is_empty();
+
+    let mut action = None;
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(has_name, egui::Button::new("Save workspace"))
+            .on_hover_text("Save these columns to open again later")
+            .clicked()
+        {
+            action = Some(RenderNavAction::SaveWorkspace(name.clone()));
+        }
+
+        if ui
+            .add_enabled(has_name, egui::Button::new("Load workspace"))
+            .on_hover_text("Replace these columns with a saved workspace")
+            .clicked()
+        {
+            action = Some(RenderNavAction::LoadWorkspace(name.clone()));
+        }
+    });
+
+    ui.data_mut(|d| d.insert_temp(id, name));
+
+    action
 }
 
+/// Tell the user a workspace couldn't be loaded. Returns true once they
+/// dismiss it.
+pub fn workspace_error_dialog(ctx: &egui::Context, message: &str) -> bool {
+    let mut dismissed = false;
+
+    egui::Window::new("Workspace not loaded")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(message);
+            ui.label("Your current columns were kept.");
+
+            ui.add_space(8.0);
+
+            if ui.button("OK").clicked() {
+                dismissed = true;
+            }
+        });
+
+    dismissed
+}
+
+/// Keys that can be picked as a column's focus key
+const FOCUS_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
 fn prev<R>(xs: &[R]) -> Option<&R> {
     xs.get(xs.len().checked_sub(2)?)
 }
@@ -1,3 +1,9 @@
+mod archive;
+mod divider;
 mod header;
+mod resize;
 
-pub use header::NavTitle;
+pub use archive::archived_drawer;
+pub use divider::ColumnDivider;
+pub use header::{workspace_error_dialog, NavTitle};
+pub use resize::{resize_handle, snap_width, COLUMN_SNAP_GRID};
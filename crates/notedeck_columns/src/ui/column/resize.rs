@@ -0,0 +1,63 @@
+use egui::{CursorIcon, Id, Rect, Sense};
+
+/// The grid column widths snap to unless another one is set, in points,
+/// see [`TimelineView::with_column_snap`](crate::ui::TimelineView::with_column_snap)
+pub const COLUMN_SNAP_GRID: f32 = 50.0;
+
+/// Columns can't be dragged narrower than this
+const MIN_COLUMN_WIDTH: f32 = 200.0;
+
+/// How far into the column its resize handle reaches from the right edge
+const HANDLE_WIDTH: f32 = 6.0;
+
+/// Round `width` to the nearest multiple of `grid`
+pub fn snap_width(width: f32, grid: f32) -> f32 {
+    if grid <= 0.0 {
+        return width;
+    }
+
+    (width / grid).round() * grid
+}
+
+/// A drag handle on the right edge of the column at `rect`. Returns the
+/// column's new width while it's being dragged. With a `snap` grid the
+/// width snaps to multiples of it, unless Shift is held.
+pub fn resize_handle(ui: &egui::Ui, id: Id, rect: Rect, snap: Option<f32>) -> Option<f32> {
+    let handle = Rect::from_x_y_ranges(rect.right() - HANDLE_WIDTH..=rect.right(), rect.y_range());
+    let response = ui.interact(handle, id, Sense::drag());
+    if response.hovered() || response.dragged() {
+        ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+    }
+
+    if !response.dragged() {
+        return None;
+    }
+
+    // we track the unsnapped width, so small drags add up to a grid step
+    let raw_id = id.with("raw_width");
+    let raw = if response.drag_started() {
+        rect.width()
+    } else {
+        ui.data(|d| d.get_temp::<f32>(raw_id))
+            .unwrap_or(rect.width())
+    } + response.drag_delta().x;
+    ui.data_mut(|d| d.insert_temp(raw_id, raw));
+
+    let width = match snap {
+        Some(grid) if !ui.input(|i| i.modifiers.shift) => snap_width(raw, grid),
+        _ => raw,
+    };
+    Some(width.max(MIN_COLUMN_WIDTH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_width() {
+        assert_eq!(snap_width(374.0, 50.0), 350.0);
+        assert_eq!(snap_width(376.0, 50.0), 400.0);
+        assert_eq!(snap_width(376.0, 0.0), 376.0);
+    }
+}
@@ -0,0 +1,173 @@
+use egui::{Color32, Image, RichText, Vec2};
+use notedeck::ImageCache;
+
+use crate::ui::note::contents::cached_texture;
+
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 8.0;
+
+/// The images of a note shown fullscreen, see
+/// [`NoteAction::ShowMediaViewer`](crate::actionbar::NoteAction::ShowMediaViewer)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaViewerState {
+    images: Vec<String>,
+    index: usize,
+    zoom: f32,
+}
+
+impl MediaViewerState {
+    /// Show `images`, starting with the first one
+    pub fn new(images: Vec<String>) -> Self {
+        MediaViewerState {
+            images,
+            index: 0,
+            zoom: MIN_ZOOM,
+        }
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.images.get(self.index).map(|s| s.as_str())
+    }
+
+    /// Show the next image, wrapping around after the last one
+    pub fn next(&mut self) {
+        if !self.images.is_empty() {
+            self.index = (self.index + 1) % self.images.len();
+            self.zoom = MIN_ZOOM;
+        }
+    }
+
+    /// Show the previous image, wrapping around before the first one
+    pub fn prev(&mut self) {
+        if !self.images.is_empty() {
+            self.index = (self.index + self.images.len() - 1) % self.images.len();
+            self.zoom = MIN_ZOOM;
+        }
+    }
+
+    fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+/// A fullscreen overlay showing the current image of `state`, with
+/// buttons and the arrow keys to page through them and the scroll wheel
+/// to zoom. Returns true when the user closes it, with the close button
+/// or Escape.
+pub fn media_viewer(
+    ctx: &egui::Context,
+    img_cache: &mut ImageCache,
+    state: &mut MediaViewerState,
+) -> bool {
+    let mut close = false;
+
+    ctx.input(|i| {
+        if i.key_pressed(egui::Key::Escape) {
+            close = true;
+        }
+        if i.key_pressed(egui::Key::ArrowLeft) {
+            state.prev();
+        }
+        if i.key_pressed(egui::Key::ArrowRight) {
+            state.next();
+        }
+
+        let zoom = i.zoom_delta() * (i.smooth_scroll_delta.y / 200.0).exp();
+        if zoom != 1.0 {
+            state.zoom_by(zoom);
+        }
+    });
+
+    let url = if let Some(url) = state.current() {
+        url.to_owned()
+    } else {
+        return true;
+    };
+
+    egui::Window::new("Media")
+        .id(egui::Id::new("media_viewer"))
+        .title_bar(false)
+        .resizable(false)
+        .movable(false)
+        .fixed_rect(ctx.screen_rect())
+        .frame(egui::Frame::none().fill(Color32::from_black_alpha(230)))
+        .show(ctx, |ui| {
+            let rect = ui.max_rect();
+
+            if let Some(texture) = cached_texture(ui, img_cache, &url, rect.width(), rect.height())
+            {
+                let size = texture.size_vec2();
+                let fit = (rect.width() / size.x).min(rect.height() / size.y);
+                let image_rect =
+                    egui::Rect::from_center_size(rect.center(), size * fit * state.zoom);
+                Image::new(&texture).paint_at(ui, image_rect);
+            } else {
+                ui.put(
+                    egui::Rect::from_center_size(rect.center(), Vec2::splat(32.0)),
+                    egui::Spinner::new(),
+                );
+            }
+
+            let button_size = Vec2::splat(32.0);
+            let close_rect = egui::Rect::from_min_size(
+                rect.right_top() + egui::vec2(-16.0 - button_size.x, 16.0),
+                button_size,
+            );
+            if ui.put(close_rect, egui::Button::new("✕")).clicked() {
+                close = true;
+            }
+
+            let count = state.images.len();
+            if count > 1 {
+                let prev_rect = egui::Rect::from_center_size(
+                    egui::pos2(rect.left() + 32.0, rect.center().y),
+                    button_size,
+                );
+                if ui.put(prev_rect, egui::Button::new("⏴")).clicked() {
+                    state.prev();
+                }
+
+                let next_rect = egui::Rect::from_center_size(
+                    egui::pos2(rect.right() - 32.0, rect.center().y),
+                    button_size,
+                );
+                if ui.put(next_rect, egui::Button::new("⏵")).clicked() {
+                    state.next();
+                }
+
+                let counter_rect = egui::Rect::from_center_size(
+                    egui::pos2(rect.center().x, rect.bottom() - 24.0),
+                    egui::vec2(80.0, 20.0),
+                );
+                ui.put(
+                    counter_rect,
+                    egui::Label::new(
+                        RichText::new(format!("{} / {}", state.index + 1, count))
+                            .color(Color32::WHITE),
+                    )
+                    .selectable(false),
+                );
+            }
+        });
+
+    close
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_viewer_wraps() {
+        let mut state = MediaViewerState::new(vec!["a".to_owned(), "b".to_owned()]);
+        state.prev();
+        assert_eq!(state.current(), Some("b"));
+        state.next();
+        assert_eq!(state.current(), Some("a"));
+
+        state.zoom_by(100.0);
+        assert_eq!(state.zoom, MAX_ZOOM);
+        state.next();
+        assert_eq!(state.zoom, MIN_ZOOM);
+    }
+}
@@ -5,12 +5,14 @@ pub mod anim;
 pub mod column;
 pub mod configure_deck;
 pub mod edit_deck;
+pub mod media_viewer;
 pub mod mention;
 pub mod note;
 pub mod preview;
 pub mod profile;
 pub mod relay;
 pub mod relay_debug;
+pub mod scheduled;
 pub mod side_panel;
 pub mod support;
 pub mod thread;
@@ -25,7 +27,7 @@ pub use profile::{ProfilePic, ProfilePreview};
 pub use relay::RelayView;
 pub use side_panel::{DesktopSidePanel, SidePanelAction};
 pub use thread::ThreadView;
-pub use timeline::TimelineView;
+pub use timeline::{TimelineResponse, TimelineView};
 pub use username::Username;
 
 use egui::Margin;
@@ -54,13 +56,50 @@ pub fn padding<R>(
 }
 
 pub fn hline(ui: &egui::Ui) {
+    hline_with_width(
+        ui,
+        ui.style().visuals.widgets.noninteractive.bg_stroke.width,
+    );
+}
+
+/// Like [`hline`], `width` points thick
+pub fn hline_with_width(ui: &egui::Ui, width: f32) {
     // pixel perfect horizontal line
     let rect = ui.available_rect_before_wrap();
     let resize_y = ui.painter().round_to_pixel(rect.top()) - 0.5;
-    let stroke = ui.style().visuals.widgets.noninteractive.bg_stroke;
+    let mut stroke = ui.style().visuals.widgets.noninteractive.bg_stroke;
+    stroke.width = width;
     ui.painter().hline(rect.x_range(), resize_y, stroke);
 }
 
 pub fn show_pointer(ui: &egui::Ui) {
     ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
 }
+
+/// The minimum size of a tap target in touch mode. Roughly the size of a
+/// fingertip, as recommended by most mobile platform guidelines.
+pub const TOUCH_TARGET_SIZE: f32 = 44.0;
+
+/// Expand the hit area of a response so that it is at least
+/// [`TOUCH_TARGET_SIZE`] in each dimension. The visual size of the widget
+/// is unchanged. This is a no-op when `touch_mode` is false.
+pub fn touch_target(ui: &egui::Ui, resp: egui::Response, touch_mode: bool) -> egui::Response {
+    if !touch_mode {
+        return resp;
+    }
+
+    let rect = resp.rect;
+    let size = egui::vec2(
+        rect.width().max(TOUCH_TARGET_SIZE),
+        rect.height().max(TOUCH_TARGET_SIZE),
+    );
+
+    if size == rect.size() {
+        return resp;
+    }
+
+    let hit_rect = egui::Rect::from_center_size(rect.center(), size);
+    let hit_resp = ui.interact(hit_rect, resp.id.with("touch_target"), resp.sense);
+
+    resp.union(hit_resp)
+}
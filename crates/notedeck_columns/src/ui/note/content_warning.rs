@@ -0,0 +1,149 @@
+use crate::timeline::is_media_url;
+use nostrdb::Note;
+
+/// The reason given in a NIP-36 `content-warning` tag, or an empty string
+/// when the tag has no reason. `None` if the note has no content warning.
+pub fn content_warning(note: &Note) -> Option<String> {
+    for tag in note.tags().iter() {
+        if tag.get(0).and_then(|t| t.variant().str()) != Some("content-warning") {
+            continue;
+        }
+
+        let reason = tag
+            .get(1)
+            .and_then(|t| t.variant().str())
+            .unwrap_or_default();
+
+        return Some(reason.to_owned());
+    }
+
+    None
+}
+
+/// How mature a note's content is, from its NIP-36 content warning.
+/// Ordered from least to most mature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ContentRating {
+    /// No content warning
+    Sfw,
+    /// A content warning for anything else, eg. spoilers or violence
+    #[default]
+    Sensitive,
+    /// A content warning for sexual content
+    Adult,
+}
+
+impl ContentRating {
+    pub const ALL: [ContentRating; 3] = [
+        ContentRating::Sfw,
+        ContentRating::Sensitive,
+        ContentRating::Adult,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ContentRating::Sfw => "Safe for work",
+            ContentRating::Sensitive => "Sensitive",
+            ContentRating::Adult => "Adult",
+        }
+    }
+}
+
+/// Content warning reasons that mean the note is adult content
+const ADULT_REASONS: [&str; 6] = ["nsfw", "nud", "sex", "porn", "adult", "18+"];
+
+fn rating_for_reason(reason: &str) -> ContentRating {
+    let reason = reason.to_lowercase();
+    if ADULT_REASONS.iter().any(|word| reason.contains(word)) {
+        ContentRating::Adult
+    } else {
+        ContentRating::Sensitive
+    }
+}
+
+/// Rate a note by its content warning. NIP-36 only gives a free form
+/// reason, so adult content is recognized by common words in it.
+pub fn content_rating(note: &Note) -> ContentRating {
+    content_warning(note).map_or(ContentRating::Sfw, |reason| rating_for_reason(&reason))
+}
+
+/// Whether note content has no text of its own, ie. it's empty or only
+/// contains links to media
+fn is_textless(content: &str) -> bool {
+    content.split_whitespace().all(is_media_url)
+}
+
+/// Notes that are only a content warning with media and no text get a
+/// tailored layout. Returns the warning's reason for these notes.
+pub fn content_warning_only(note: &Note) -> Option<String> {
+    if !is_textless(note.content()) {
+        return None;
+    }
+
+    content_warning(note)
+}
+
+/// Show the content warning in place of the note's media. Returns true
+/// once the user has chosen to reveal the media.
+pub fn content_warning_only_ui(ui: &mut egui::Ui, note_id: &[u8; 32], reason: &str) -> bool {
+    let id = egui::Id::new(("content_warning_revealed", note_id));
+    let revealed = ui.data(|d| d.get_temp::<bool>(id)).unwrap_or(false);
+    if revealed {
+        return true;
+    }
+
+    egui::Frame::none()
+        .fill(ui.visuals().noninteractive().weak_bg_fill)
+        .inner_margin(egui::Margin::same(16.0))
+        .outer_margin(egui::Margin::symmetric(0.0, 8.0))
+        .rounding(egui::Rounding::same(10.0))
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading("⚠ Content warning");
+
+                if !reason.is_empty() {
+                    ui.add(egui::Label::new(reason).selectable(false));
+                }
+
+                ui.add_space(8.0);
+
+                if ui.button("Show media").clicked() {
+                    ui.data_mut(|d| d.insert_temp(id, true));
+                }
+            });
+        });
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_only_content_is_textless() {
+        assert!(is_textless(""));
+        assert!(is_textless("  \n"));
+        assert!(is_textless(
+            "https://example.com/a.jpg\nhttps://example.com/b.png"
+        ));
+    }
+
+    #[test]
+    fn test_rating_for_reason() {
+        assert_eq!(rating_for_reason(""), ContentRating::Sensitive);
+        assert_eq!(rating_for_reason("spoilers"), ContentRating::Sensitive);
+        assert_eq!(rating_for_reason("NSFW"), ContentRating::Adult);
+        assert_eq!(rating_for_reason("nudity"), ContentRating::Adult);
+        assert!(ContentRating::Sfw < ContentRating::Sensitive);
+        assert!(ContentRating::Sensitive < ContentRating::Adult);
+    }
+
+    #[test]
+    fn test_text_content_is_not_textless() {
+        assert!(!is_textless("spoilers ahead"));
+        assert!(!is_textless("look https://example.com/a.jpg"));
+        // a bare link to an article is text, not media
+        assert!(!is_textless("https://example.com/article.html"));
+    }
+}
@@ -1,15 +1,20 @@
 use crate::actionbar::NoteAction;
+use crate::geo;
 use crate::images::ImageType;
+use crate::profile::get_display_name;
 use crate::ui::{
     self,
-    note::{NoteOptions, NoteResponse},
-    ProfilePic,
+    note::{
+        emoji::{self, CustomEmojiMap, EmojiSegment},
+        DesktopNetwork, ImageLayout, LinkifyMode, NoteOptions, NoteResponse,
+    },
 };
 use egui::{Color32, Hyperlink, Image, RichText};
 use nostrdb::{BlockType, Mention, Ndb, Note, NoteKey, Transaction};
 use tracing::warn;
+use url::Url;
 
-use notedeck::{ImageCache, NoteCache};
+use notedeck::{ImageCache, ImageFailure, NoteCache};
 
 pub struct NoteContents<'a> {
     ndb: &'a Ndb,
@@ -19,6 +24,8 @@ pub struct NoteContents<'a> {
     note: &'a Note<'a>,
     note_key: NoteKey,
     options: NoteOptions,
+    custom_emoji: Option<&'a CustomEmojiMap>,
+    quote_chain: Option<&'a [[u8; 32]]>,
     action: Option<NoteAction>,
 }
 
@@ -40,10 +47,23 @@ impl<'a> NoteContents<'a> {
             note,
             note_key,
             options,
+            custom_emoji: None,
+            quote_chain: None,
             action: None,
         }
     }
 
+    /// Expand nested quotes, see [`render_note_preview`]
+    pub fn quote_chain(mut self, quote_chain: Option<&'a [[u8; 32]]>) -> Self {
+        self.quote_chain = quote_chain;
+        self
+    }
+
+    pub fn custom_emoji(mut self, emojis: Option<&'a CustomEmojiMap>) -> Self {
+        self.custom_emoji = emojis;
+        self
+    }
+
     pub fn action(&self) -> &Option<NoteAction> {
         &self.action
     }
@@ -60,14 +80,29 @@ impl egui::Widget for &mut NoteContents<'_> {
             self.note,
             self.note_key,
             self.options,
+            self.custom_emoji,
+            self.quote_chain,
         );
         self.action = result.action;
         result.response
     }
 }
 
+/// Nested quotes are never expanded past this depth, even on request
+const MAX_QUOTE_DEPTH: usize = 16;
+
+/// Where we remember which notes the user asked to expand all quotes for
+pub fn expand_quotes_id(note_key: NoteKey) -> egui::Id {
+    egui::Id::new(("expand_quotes", note_key))
+}
+
 /// Render an inline note preview with a border. These are used when
-/// notes are references within a note
+/// notes are references within a note.
+///
+/// Previews don't show their own quotes unless we're expanding a quote
+/// chain. `quote_chain` holds the ids of the notes quoting this one, so
+/// that we can stop at cycles.
+#[allow(clippy::too_many_arguments)]
 pub fn render_note_preview(
     ui: &mut egui::Ui,
     ndb: &Ndb,
@@ -76,10 +111,17 @@ pub fn render_note_preview(
     txn: &Transaction,
     id: &[u8; 32],
     parent: NoteKey,
+    quote_chain: Option<&[[u8; 32]]>,
 ) -> NoteResponse {
     #[cfg(feature = "profiling")]
     puffin::profile_function!();
 
+    if let Some(chain) = quote_chain {
+        if chain.contains(id) || chain.len() >= MAX_QUOTE_DEPTH {
+            return NoteResponse::new(ui.weak("↻ quote chain continues above"));
+        }
+    }
+
     let note = if let Ok(note) = ndb.get_note_by_id(txn, id) {
         // TODO: support other preview kinds
         if note.kind() == 1 {
@@ -113,20 +155,135 @@ pub fn render_note_preview(
             ui.visuals().noninteractive().bg_stroke.color,
         ))
         .show(ui, |ui| {
-            ui::NoteView::new(ndb, note_cache, img_cache, &note)
+            let mut view = ui::NoteView::new(ndb, note_cache, img_cache, &note)
                 .actionbar(false)
                 .small_pfp(true)
                 .wide(true)
                 .note_previews(false)
                 .options_button(true)
-                .parent(parent)
-                .show(ui)
+                .parent(parent);
+
+            if let Some(chain) = quote_chain {
+                view = view.quote_chain(chain.to_vec());
+            }
+
+            view.show(ui)
         })
         .inner
 }
 
+/// Note content as plain prose for sharing outside of nostr. Profile
+/// mentions become `@name`, note mentions are dropped, and links are
+/// dropped too when `strip_urls` is set.
+pub fn clean_note_text(ndb: &Ndb, txn: &Transaction, note: &Note, strip_urls: bool) -> String {
+    let blocks = if let Some(blocks) = note
+        .key()
+        .and_then(|note_key| ndb.get_blocks_by_key(txn, note_key).ok())
+    {
+        blocks
+    } else {
+        return note.content().to_owned();
+    };
+
+    let mut text = String::new();
+
+    for block in blocks.iter(note) {
+        match block.blocktype() {
+            BlockType::MentionBech32 => {
+                let pubkey = match block.as_mention() {
+                    Some(Mention::Profile(profile)) => profile.pubkey(),
+                    Some(Mention::Pubkey(npub)) => npub.pubkey(),
+                    _ => continue,
+                };
+
+                let profile = ndb.get_profile_by_pubkey(txn, pubkey).ok();
+                text.push('@');
+                text.push_str(get_display_name(profile.as_ref()).name());
+            }
+
+            BlockType::Hashtag => {
+                text.push('#');
+                text.push_str(block.as_str());
+            }
+
+            BlockType::Url if strip_urls => {}
+
+            _ => text.push_str(block.as_str()),
+        }
+    }
+
+    tidy_whitespace(&text)
+}
+
+/// Clean up the gaps left behind by removed mentions and links
+fn tidy_whitespace(text: &str) -> String {
+    let mut lines: Vec<String> = vec![];
+
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        // no more than one blank line in a row
+        if line.is_empty() && lines.last().map_or(true, |l| l.is_empty()) {
+            continue;
+        }
+
+        lines.push(line);
+    }
+
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
 fn is_image_link(url: &str) -> bool {
-    url.ends_with("png") || url.ends_with("jpg") || url.ends_with("jpeg")
+    url.ends_with("png") || url.ends_with("jpg") || url.ends_with("jpeg") || is_gif_link(url)
+}
+
+/// Relay urls are websocket urls, eg: wss://relay.damus.io
+fn relay_link(url: &str) -> Option<Url> {
+    let lower_url = url.to_lowercase();
+    if !(lower_url.starts_with("wss://") || lower_url.starts_with("ws://")) {
+        return None;
+    }
+
+    Url::parse(url).ok()
+}
+
+/// Hosts of url shorteners, which hide where their links go
+const URL_SHORTENERS: [&str; 10] = [
+    "t.co",
+    "bit.ly",
+    "tinyurl.com",
+    "goo.gl",
+    "ow.ly",
+    "buff.ly",
+    "is.gd",
+    "rebrand.ly",
+    "cutt.ly",
+    "shorturl.at",
+];
+
+fn is_shortened_url(url: &str) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_lowercase()))
+        .map_or(false, |host| {
+            let host = host.strip_prefix("www.").unwrap_or(&host);
+            URL_SHORTENERS.contains(&host)
+        })
+}
+
+fn is_video_link(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    [".mp4", ".webm", ".mov", ".m4v"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+fn is_gif_link(url: &str) -> bool {
+    url.ends_with("gif")
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -139,12 +296,17 @@ fn render_note_contents(
     note: &Note,
     note_key: NoteKey,
     options: NoteOptions,
+    custom_emoji: Option<&CustomEmojiMap>,
+    quote_chain: Option<&[[u8; 32]]>,
 ) -> NoteResponse {
     #[cfg(feature = "profiling")]
     puffin::profile_function!();
 
     let selectable = options.has_selectable_text();
+    let wrap_mode = options.word_wrap_mode();
     let mut images: Vec<String> = vec![];
+    let mut links: Vec<String> = vec![];
+    let mut videos: Vec<String> = vec![];
     let mut note_action: Option<NoteAction> = None;
     let mut inline_note: Option<(&[u8; 32], &str)> = None;
     let hide_media = options.has_hide_media();
@@ -155,7 +317,7 @@ fn render_note_contents(
             blocks
         } else {
             warn!("missing note content blocks? '{}'", note.content());
-            ui.weak(note.content());
+            ui.add(egui::Label::new(RichText::new(note.content()).weak()).wrap_mode(wrap_mode));
             return;
         };
 
@@ -198,27 +360,91 @@ fn render_note_contents(
                 BlockType::Hashtag => {
                     #[cfg(feature = "profiling")]
                     puffin::profile_scope!("hashtag contents");
-                    ui.colored_label(link_color, format!("#{}", block.as_str()));
+                    let resp = ui
+                        .add(
+                            egui::Label::new(
+                                RichText::new(format!("#{}", block.as_str())).color(link_color),
+                            )
+                            .selectable(false)
+                            .sense(egui::Sense::click()),
+                        )
+                        .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+                    if resp.clicked() {
+                        note_action =
+                            Some(NoteAction::OpenHashtagColumn(block.as_str().to_string()));
+                    }
                 }
 
                 BlockType::Url => {
                     let lower_url = block.as_str().to_lowercase();
+                    let resolved_url = if options.has_expand_shortened_urls()
+                        && is_shortened_url(block.as_str())
+                    {
+                        let now = ui.input(|i| i.time);
+                        // a click elsewhere in the note wins, we can resolve next frame
+                        if note_action.is_none() && img_cache.should_resolve(block.as_str(), now) {
+                            note_action = Some(NoteAction::ResolveUrl(block.as_str().to_string()));
+                        }
+                        img_cache.resolved_url(block.as_str(), now)
+                    } else {
+                        None
+                    };
+                    let url = resolved_url.as_deref().unwrap_or(block.as_str());
+
                     if !hide_media && is_image_link(&lower_url) {
                         images.push(block.as_str().to_string());
-                    } else {
+                    } else if !hide_media && is_video_link(&lower_url) {
+                        videos.push(block.as_str().to_string());
+                    } else if let Some(relay_url) = relay_link(block.as_str()) {
+                        let resp = ui
+                            .add(
+                                egui::Label::new(
+                                    RichText::new(block.as_str()).color(link_color).underline(),
+                                )
+                                .selectable(false)
+                                .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text("Right click to add this relay");
+
+                        resp.context_menu(|ui| {
+                            if ui.button("Add to relay pool").clicked() {
+                                note_action = Some(NoteAction::AddRelay(relay_url));
+                                ui.close_menu();
+                            }
+                        });
+                    } else if options.has_hide_urls_in_content() {
+                        links.push(url.to_string());
+                    } else if options.linkify_mode().linkifies(url) {
                         #[cfg(feature = "profiling")]
                         puffin::profile_scope!("url contents");
-                        ui.add(Hyperlink::from_label_and_url(
-                            RichText::new(block.as_str()).color(link_color),
-                            block.as_str(),
+                        let resp = ui.add(Hyperlink::from_label_and_url(
+                            RichText::new(url).color(link_color),
+                            url,
                         ));
+                        if resolved_url.is_some() {
+                            resp.on_hover_text(format!("Shortened as {}", block.as_str()));
+                        }
+                    } else {
+                        ui.label(url);
                     }
                 }
 
                 BlockType::Text => {
                     #[cfg(feature = "profiling")]
                     puffin::profile_scope!("text contents");
-                    ui.add(egui::Label::new(block.as_str()).selectable(selectable));
+                    match custom_emoji {
+                        Some(emojis) if !emojis.is_empty() => {
+                            text_with_emoji(ui, block.as_str(), emojis, selectable, wrap_mode)
+                        }
+                        _ => {
+                            ui.add(
+                                egui::Label::new(block.as_str())
+                                    .selectable(selectable)
+                                    .wrap_mode(wrap_mode),
+                            );
+                        }
+                    }
                 }
 
                 _ => {
@@ -229,7 +455,17 @@ fn render_note_contents(
     });
 
     let preview_note_action = if let Some((id, _block_str)) = inline_note {
-        render_note_preview(ui, ndb, note_cache, img_cache, txn, id, note_key).action
+        render_note_preview(
+            ui,
+            ndb,
+            note_cache,
+            img_cache,
+            txn,
+            id,
+            note_key,
+            quote_chain,
+        )
+        .action
     } else {
         None
     };
@@ -237,81 +473,246 @@ fn render_note_contents(
     if !images.is_empty() && !options.has_textmode() {
         ui.add_space(2.0);
         let carousel_id = egui::Id::new(("carousel", note.key().expect("expected tx note")));
-        image_carousel(ui, img_cache, images, carousel_id);
+        let carousel_action = if images.len() > 1 && options.image_layout() == ImageLayout::Grid {
+            image_gallery(ui, img_cache, images, carousel_id, options)
+        } else {
+            image_carousel(ui, img_cache, images, carousel_id, options)
+        };
+        if carousel_action.is_some() {
+            note_action = carousel_action;
+        }
         ui.add_space(2.0);
     }
 
+    if !videos.is_empty() && !options.has_textmode() {
+        // TODO: ask the platform about its network on mobile
+        let autoplay = options.videos_autoplay(&DesktopNetwork);
+        for video in &videos {
+            let thumbnail = video_thumbnail(note, video);
+            if let Some(action) = video_card(ui, img_cache, video, thumbnail, autoplay) {
+                note_action = Some(action);
+            }
+        }
+    }
+
+    for link in &links {
+        link_card(ui, link, options.linkify_mode());
+    }
+
+    if options.has_show_geo_map() && !options.has_textmode() {
+        if let Some(geohash) = geo::note_geohash(note) {
+            geo_map(ui, img_cache, geohash);
+        }
+    }
+
     let note_action = preview_note_action.or(note_action);
 
     NoteResponse::new(response.response).with_action(note_action)
 }
 
+const VIDEO_CARD_HEIGHT: f32 = 200.0;
+
+/// A video link with a play button that opens it. Its NIP-92 thumbnail
+/// only loads once it's played, unless it `autoplay`s. See
+/// [`NoteOptions::videos_autoplay`]
+fn video_card(
+    ui: &mut egui::Ui,
+    img_cache: &mut ImageCache,
+    url: &str,
+    thumbnail: Option<String>,
+    autoplay: bool,
+) -> Option<NoteAction> {
+    let mut action = None;
+    let playing = autoplay || img_cache.is_playing(url);
+
+    ui.add_space(2.0);
+    let (rect, resp) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), VIDEO_CARD_HEIGHT),
+        egui::Sense::click(),
+    );
+    ui.painter()
+        .rect_filled(rect, 5.0, ui.visuals().extreme_bg_color);
+
+    if let Some(thumbnail) = thumbnail.filter(|_| playing) {
+        if let Some(texture) =
+            cached_texture(ui, img_cache, &thumbnail, rect.width(), rect.height())
+        {
+            Image::new(&texture)
+                .rounding(5.0)
+                .maintain_aspect_ratio(true)
+                .paint_at(ui, rect);
+        }
+    }
+
+    ui.painter().text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        "▶",
+        egui::FontId::proportional(48.0),
+        Color32::WHITE,
+    );
+
+    let resp = resp
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .on_hover_text(url);
+    if resp.clicked() {
+        if !playing {
+            action = Some(NoteAction::PlayMedia(url.to_owned()));
+        }
+        ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+    }
+    ui.add_space(2.0);
+
+    action
+}
+
+/// The thumbnail of a video from the note's NIP-92 `imeta` tags
+fn video_thumbnail(note: &Note, url: &str) -> Option<String> {
+    for tag in note.tags().iter() {
+        if tag.get(0).and_then(|t| t.variant().str()) != Some("imeta") {
+            continue;
+        }
+
+        let entries: Vec<&str> = (1..tag.count())
+            .filter_map(|i| tag.get(i).and_then(|t| t.variant().str()))
+            .collect();
+        if let Some(thumbnail) = imeta_thumbnail(&entries, url) {
+            return Some(thumbnail.to_owned());
+        }
+    }
+
+    None
+}
+
+/// The `thumb` or `image` of an `imeta` tag's entries, if they describe `url`
+fn imeta_thumbnail<'a>(entries: &[&'a str], url: &str) -> Option<&'a str> {
+    let value = |key: &str| {
+        entries
+            .iter()
+            .find_map(|entry| entry.strip_prefix(key)?.strip_prefix(' '))
+    };
+
+    if value("url")? != url {
+        return None;
+    }
+
+    value("thumb").or_else(|| value("image"))
+}
+
+/// A link pulled out of the note text, see
+/// [`NoteOptions::hide_urls_in_content`]
+fn link_card(ui: &mut egui::Ui, url: &str, linkify: LinkifyMode) {
+    let host = Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_owned()));
+
+    ui.add_space(2.0);
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.set_width(ui.available_width());
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("🔗").weak());
+            ui.vertical(|ui| {
+                if let Some(host) = host {
+                    ui.add(egui::Label::new(RichText::new(host).strong()).selectable(false));
+                }
+                let text = RichText::new(url).small().weak();
+                if linkify.linkifies(url) {
+                    ui.add(Hyperlink::from_label_and_url(text, url).open_in_new_tab(true));
+                } else {
+                    ui.label(text);
+                }
+            });
+        });
+    });
+}
+
+fn text_with_emoji(
+    ui: &mut egui::Ui,
+    text: &str,
+    emojis: &CustomEmojiMap,
+    selectable: bool,
+    wrap_mode: egui::TextWrapMode,
+) {
+    let font_id = egui::TextStyle::Body.resolve(ui.style());
+    let size = ui.fonts(|f| f.row_height(&font_id));
+
+    for segment in emoji::split_shortcodes(text, emojis) {
+        match segment {
+            EmojiSegment::Text(text) => {
+                ui.add(
+                    egui::Label::new(text)
+                        .selectable(selectable)
+                        .wrap_mode(wrap_mode),
+                );
+            }
+            EmojiSegment::Emoji(texture) => {
+                ui.add(Image::new((texture, egui::vec2(size, size))));
+            }
+        }
+    }
+}
+
 fn image_carousel(
     ui: &mut egui::Ui,
     img_cache: &mut ImageCache,
     images: Vec<String>,
     carousel_id: egui::Id,
-) {
+    options: NoteOptions,
+) -> Option<NoteAction> {
     // let's make sure everything is within our area
 
-    let height = 360.0;
+    let height = options.media_height();
     let width = ui.available_size().x;
     let spinsz = if height > width { width } else { height };
+    let mut action: Option<NoteAction> = None;
 
     ui.add_sized([width, height], |ui: &mut egui::Ui| {
         egui::ScrollArea::horizontal()
             .id_salt(carousel_id)
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    for image in images {
-                        // If the cache is empty, initiate the fetch
-                        let m_cached_promise = img_cache.map().get(&image);
-                        if m_cached_promise.is_none() {
-                            let res = crate::images::fetch_img(
-                                img_cache,
-                                ui.ctx(),
-                                &image,
-                                ImageType::Content(width.round() as u32, height.round() as u32),
-                            );
-                            img_cache.map_mut().insert(image.to_owned(), res);
-                        }
+                    for (ind, image) in images.iter().enumerate() {
+                        let is_gif = img_cache.is_gif(image);
+                        let play_id = carousel_id.with(("play", image));
+                        let playing =
+                            is_gif && (options.gifs_autoplay() || img_cache.is_playing(image));
 
-                        // What is the state of the fetch?
-                        match img_cache.map()[&image].ready() {
-                            // Still waiting
-                            None => {
-                                ui.allocate_space(egui::vec2(spinsz, spinsz));
-                                //ui.add(egui::Spinner::new().size(spinsz));
-                            }
-                            // Failed to fetch image!
-                            Some(Err(_err)) => {
-                                // FIXME - use content-specific error instead
-                                let no_pfp = crate::images::fetch_img(
-                                    img_cache,
-                                    ui.ctx(),
-                                    ProfilePic::no_pfp_url(),
-                                    ImageType::Profile(128),
-                                );
-                                img_cache.map_mut().insert(image.to_owned(), no_pfp);
-                                // spin until next pass
-                                ui.allocate_space(egui::vec2(spinsz, spinsz));
-                                //ui.add(egui::Spinner::new().size(spinsz));
-                            }
-                            // Use the previously resolved image
-                            Some(Ok(img)) => {
-                                let img_resp = ui.add(
-                                    Image::new(img)
+                        let img_resp = if playing {
+                            // let egui's loaders handle the animation
+                            Some(
+                                ui.add(
+                                    Image::new(image.as_str())
                                         .max_height(height)
                                         .rounding(5.0)
-                                        .fit_to_original_size(1.0),
-                                );
-                                img_resp.context_menu(|ui| {
-                                    if ui.button("Copy Link").clicked() {
-                                        ui.ctx().copy_text(image);
-                                        ui.close_menu();
-                                    }
-                                });
+                                        .fit_to_original_size(1.0)
+                                        .sense(egui::Sense::click()),
+                                ),
+                            )
+                        } else {
+                            let resp = cached_image(ui, img_cache, image, width, height, spinsz);
+
+                            // paused gifs show their first frame until tapped
+                            if let Some(resp) = resp.as_ref().filter(|_| is_gif) {
+                                if play_badge(ui, resp.rect, play_id).clicked() {
+                                    action = Some(NoteAction::PlayMedia(image.clone()));
+                                }
+                            }
+
+                            resp
+                        };
+
+                        if let Some(img_resp) = img_resp {
+                            if img_resp.clicked() && action.is_none() {
+                                action =
+                                    Some(NoteAction::ShowMediaViewer(viewer_images(&images, ind)));
                             }
+
+                            img_resp.context_menu(|ui| {
+                                if ui.button("Copy Link").clicked() {
+                                    ui.ctx().copy_text(image.clone());
+                                    ui.close_menu();
+                                }
+                            });
                         }
                     }
                 })
@@ -319,4 +720,390 @@ fn image_carousel(
             })
             .inner
     });
+
+    action
+}
+
+/// The images of a note for the media viewer, starting with the one at
+/// `start`. The viewer wraps around, so the others keep their order.
+fn viewer_images(images: &[String], start: usize) -> Vec<String> {
+    let mut images = images.to_vec();
+    images.rotate_left(start);
+    images
+}
+
+/// A cell in an image gallery row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GalleryCell {
+    image: usize,
+    span: usize,
+}
+
+/// Lay out `count` images into gallery rows: 2 columns for 2 images, 3
+/// columns otherwise. When the count is odd the first image spans 2
+/// columns.
+fn gallery_rows(count: usize) -> (usize, Vec<Vec<GalleryCell>>) {
+    let columns = if count == 2 { 2 } else { 3 };
+    let mut rows: Vec<Vec<GalleryCell>> = vec![];
+    let mut used = columns;
+
+    for image in 0..count {
+        let span = if image == 0 && count % 2 == 1 { 2 } else { 1 };
+        if used + span > columns {
+            rows.push(vec![]);
+            used = 0;
+        }
+        used += span;
+        rows.last_mut()
+            .expect("pushed a row")
+            .push(GalleryCell { image, span });
+    }
+
+    (columns, rows)
+}
+
+/// The part of a texture to show so that it covers `size` without
+/// stretching
+fn cover_uv(texture_size: egui::Vec2, size: egui::Vec2) -> egui::Rect {
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    if texture_size.x <= 0.0 || texture_size.y <= 0.0 || size.y <= 0.0 {
+        return uv;
+    }
+
+    let texture_aspect = texture_size.x / texture_size.y;
+    let aspect = size.x / size.y;
+
+    if texture_aspect > aspect {
+        let w = aspect / texture_aspect;
+        egui::Rect::from_center_size(uv.center(), egui::vec2(w, 1.0))
+    } else {
+        let h = texture_aspect / aspect;
+        egui::Rect::from_center_size(uv.center(), egui::vec2(1.0, h))
+    }
+}
+
+/// Multiple images in a grid of square cells, cropped to fill them
+fn image_gallery(
+    ui: &mut egui::Ui,
+    img_cache: &mut ImageCache,
+    images: Vec<String>,
+    gallery_id: egui::Id,
+    options: NoteOptions,
+) -> Option<NoteAction> {
+    let spacing = 4.0;
+    let width = ui.available_size().x;
+    let (columns, rows) = gallery_rows(images.len());
+    let cell = ((width - spacing * (columns - 1) as f32) / columns as f32).max(1.0);
+    let mut action: Option<NoteAction> = None;
+
+    ui.vertical(|ui| {
+        ui.spacing_mut().item_spacing = egui::vec2(spacing, spacing);
+
+        for row in rows {
+            ui.horizontal(|ui| {
+                for GalleryCell { image: ind, span } in row {
+                    let image = &images[ind];
+                    let size = egui::vec2(cell * span as f32 + spacing * (span - 1) as f32, cell);
+                    let (rect, resp) = ui.allocate_exact_size(size, egui::Sense::click());
+
+                    let is_gif = img_cache.is_gif(image);
+                    let playing =
+                        is_gif && (options.gifs_autoplay() || img_cache.is_playing(image));
+
+                    if playing {
+                        Image::new(image.as_str()).rounding(5.0).paint_at(ui, rect);
+                    } else if let Some(texture) =
+                        cached_texture(ui, img_cache, image, size.x, size.y)
+                    {
+                        Image::new(&texture)
+                            .uv(cover_uv(texture.size_vec2(), size))
+                            .rounding(5.0)
+                            .paint_at(ui, rect);
+
+                        if is_gif
+                            && play_badge(ui, rect, gallery_id.with(("play", image))).clicked()
+                        {
+                            action = Some(NoteAction::PlayMedia(image.clone()));
+                        }
+                    } else if let Some(failure) = img_cache.failure(image) {
+                        failed_image_in(ui, rect, image, failure);
+                    } else {
+                        ui.painter().rect_filled(
+                            rect,
+                            5.0,
+                            ui.visuals().noninteractive().weak_bg_fill,
+                        );
+                    }
+
+                    if resp.clicked() && action.is_none() {
+                        action = Some(NoteAction::ShowMediaViewer(viewer_images(&images, ind)));
+                    }
+
+                    resp.context_menu(|ui| {
+                        if ui.button("Copy Link").clicked() {
+                            ui.ctx().copy_text(image.clone());
+                            ui.close_menu();
+                        }
+                    });
+                }
+            });
+        }
+    });
+
+    action
+}
+
+/// Render an image from our image cache, initiating the fetch if needed.
+/// Returns the image response once the image is loaded.
+fn cached_image(
+    ui: &mut egui::Ui,
+    img_cache: &mut ImageCache,
+    image: &str,
+    width: f32,
+    height: f32,
+    spinsz: f32,
+) -> Option<egui::Response> {
+    if let Some(texture) = cached_texture(ui, img_cache, image, width, height) {
+        Some(
+            ui.add(
+                Image::new(&texture)
+                    .max_height(height)
+                    .rounding(5.0)
+                    .fit_to_original_size(1.0)
+                    .sense(egui::Sense::click()),
+            ),
+        )
+    } else if let Some(failure) = img_cache.failure(image) {
+        let (rect, resp) =
+            ui.allocate_exact_size(egui::vec2(width.min(height), spinsz), egui::Sense::hover());
+        failed_image_in(ui, rect, image, failure);
+        Some(resp)
+    } else {
+        ui.allocate_space(egui::vec2(spinsz, spinsz));
+        //ui.add(egui::Spinner::new().size(spinsz));
+        None
+    }
+}
+
+/// A placeholder for an image we couldn't show, with a way to open it
+/// outside of the app
+fn failed_image_in(ui: &mut egui::Ui, rect: egui::Rect, url: &str, failure: ImageFailure) {
+    let label = match failure {
+        ImageFailure::Unsupported => "Unsupported image",
+        ImageFailure::LoadFailed => "Image failed to load",
+    };
+
+    ui.painter()
+        .rect_filled(rect, 5.0, ui.visuals().noninteractive().weak_bg_fill);
+
+    let mut child = ui.new_child(
+        egui::UiBuilder::new()
+            .max_rect(rect.shrink(8.0))
+            .layout(egui::Layout::top_down(egui::Align::Center)),
+    );
+    child.add_space((rect.height() / 2.0 - 24.0).max(0.0));
+    child.add(egui::Label::new(RichText::new(label).weak()).selectable(false));
+    if child.small_button("Open link").clicked() {
+        child.ctx().open_url(egui::OpenUrl::new_tab(url));
+    }
+}
+
+/// Get an image's texture from our image cache, initiating the fetch if
+/// needed. Returns None while the image is loading.
+pub(crate) fn cached_texture(
+    ui: &mut egui::Ui,
+    img_cache: &mut ImageCache,
+    image: &str,
+    width: f32,
+    height: f32,
+) -> Option<egui::TextureHandle> {
+    let now = ui.input(|i| i.time);
+
+    // If the cache is empty, initiate the fetch
+    let m_cached_promise = img_cache.map().get(image);
+    if m_cached_promise.is_none() {
+        if !img_cache.should_fetch(image, now) {
+            return None;
+        }
+
+        let res = crate::images::fetch_img(
+            img_cache,
+            ui.ctx(),
+            image,
+            ImageType::Content(width.round() as u32, height.round() as u32),
+        );
+        img_cache.map_mut().insert(image.to_owned(), res);
+    }
+
+    img_cache.touch(image);
+
+    // What is the state of the fetch?
+    let failure = match img_cache.map()[image].ready() {
+        // Still waiting
+        None => return None,
+        // Failed to fetch image!
+        Some(Err(err)) => ImageFailure::from_error(err),
+        // Use the previously resolved image
+        Some(Ok(img)) => {
+            let img = img.clone();
+            if img_cache.failure(image).is_some() {
+                img_cache.clear_failure(image);
+            }
+            return Some(img);
+        }
+    };
+
+    // drop the failed fetch, we refetch it after a backoff if it's
+    // worth retrying
+    warn!("failed to load image {}: {:?}", image, failure);
+    img_cache.map_mut().remove(image);
+    img_cache.record_failure(image, failure, now);
+    ui.ctx().request_repaint_after_secs(1.0);
+    None
+}
+
+/// A map thumbnail for a geohash with a marker at its location. Tapping
+/// it opens a full map.
+fn geo_map(ui: &mut egui::Ui, img_cache: &mut ImageCache, geohash: &str) {
+    let point = if let Some(point) = geo::decode_geohash(geohash) {
+        point
+    } else {
+        return;
+    };
+
+    let size = 160.0;
+    let zoom = geo::zoom_for_geohash(geohash);
+    let (x, y) = geo::tile_coords(&point, zoom);
+    let url = geo::tile_url(zoom, x as u32, y as u32);
+
+    if img_cache.map().get(&url).is_none() {
+        let res = crate::images::fetch_img(img_cache, ui.ctx(), &url, ImageType::Content(256, 256));
+        img_cache.map_mut().insert(url.to_owned(), res);
+    }
+
+    ui.add_space(4.0);
+
+    img_cache.touch(&url);
+    let resp = match img_cache.map()[&url].ready() {
+        Some(Ok(texture)) => ui.add(
+            Image::new(texture)
+                .fit_to_exact_size(egui::vec2(size, size))
+                .rounding(5.0)
+                .sense(egui::Sense::click()),
+        ),
+        _ => {
+            let (rect, resp) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::click());
+            ui.painter()
+                .rect_filled(rect, 5.0, ui.visuals().noninteractive().weak_bg_fill);
+            resp
+        }
+    };
+
+    let marker = resp.rect.min + egui::vec2(x.fract() as f32, y.fract() as f32) * size;
+    let painter = ui.painter_at(resp.rect);
+    painter.circle(
+        marker,
+        5.0,
+        Color32::from_rgb(0xE5, 0x3E, 0x3E),
+        egui::Stroke::new(1.5, Color32::WHITE),
+    );
+
+    if resp.hovered() {
+        ui::show_pointer(ui);
+    }
+
+    if resp.on_hover_text("Open map").clicked() {
+        ui.ctx()
+            .open_url(egui::OpenUrl::new_tab(geo::map_url(&point, zoom)));
+    }
+
+    ui.add(
+        egui::Label::new(RichText::new("© OpenStreetMap contributors").small().weak())
+            .selectable(false),
+    );
+}
+
+/// A play button drawn over paused media
+fn play_badge(ui: &mut egui::Ui, media_rect: egui::Rect, id: egui::Id) -> egui::Response {
+    let resp = ui.interact(media_rect, id, egui::Sense::click());
+    if resp.hovered() {
+        ui::show_pointer(ui);
+    }
+
+    let center = media_rect.center();
+    let radius = 24.0;
+    let painter = ui.painter();
+    painter.circle_filled(center, radius, Color32::from_black_alpha(160));
+
+    let tri = radius * 0.5;
+    painter.add(egui::Shape::convex_polygon(
+        vec![
+            center + egui::vec2(-tri * 0.6, -tri),
+            center + egui::vec2(-tri * 0.6, tri),
+            center + egui::vec2(tri, 0.0),
+        ],
+        Color32::WHITE,
+        egui::Stroke::NONE,
+    ));
+
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tidy_whitespace() {
+        assert_eq!(
+            tidy_whitespace("gm  to everyone \n\n\n\nsee   \n"),
+            "gm to everyone\n\nsee"
+        );
+        assert_eq!(tidy_whitespace("\n\n  hello"), "hello");
+    }
+
+    #[test]
+    fn test_is_shortened_url() {
+        assert!(is_shortened_url("https://t.co/abc123"));
+        assert!(is_shortened_url("https://www.bit.ly/xyz"));
+        assert!(!is_shortened_url("https://damus.io/t.co"));
+        assert!(!is_shortened_url("https://notbit.ly/xyz"));
+        assert!(!is_shortened_url("not a url"));
+    }
+
+    #[test]
+    fn test_imeta_thumbnail() {
+        let url = "https://example.com/clip.mp4";
+        let entries = [
+            "url https://example.com/clip.mp4",
+            "m video/mp4",
+            "image https://example.com/poster.jpg",
+        ];
+        assert_eq!(
+            imeta_thumbnail(&entries, url),
+            Some("https://example.com/poster.jpg")
+        );
+        assert_eq!(
+            imeta_thumbnail(&entries, "https://example.com/other.mp4"),
+            None
+        );
+        assert_eq!(imeta_thumbnail(&entries[..2], url), None);
+    }
+
+    #[test]
+    fn test_gallery_rows() {
+        let spans = |count| {
+            let (columns, rows) = gallery_rows(count);
+            let spans: Vec<Vec<usize>> = rows
+                .iter()
+                .map(|row| row.iter().map(|cell| cell.span).collect())
+                .collect();
+            (columns, spans)
+        };
+
+        assert_eq!(spans(2), (2, vec![vec![1, 1]]));
+        assert_eq!(spans(3), (3, vec![vec![2, 1], vec![1]]));
+        assert_eq!(spans(4), (3, vec![vec![1, 1, 1], vec![1]]));
+        assert_eq!(spans(5), (3, vec![vec![2, 1], vec![1, 1, 1]]));
+    }
 }
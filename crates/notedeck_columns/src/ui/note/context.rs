@@ -1,25 +1,41 @@
-use egui::{Rect, Vec2};
-use enostr::{NoteId, Pubkey};
-use nostrdb::{Note, NoteKey};
-use tracing::error;
+use crate::snooze::SNOOZE_DURATIONS;
+use crate::ui::note::{
+    contents::{clean_note_text, expand_quotes_id},
+    NoteOptions,
+};
+
+use egui::{Rect, RichText, Vec2};
+use enostr::{NoteId, NoteVerification, Pubkey};
+use nostrdb::{Ndb, Note, NoteKey};
 
 #[derive(Clone)]
 #[allow(clippy::enum_variant_names)]
 pub enum NoteContextSelection {
     CopyText,
+    CopyCleanText { strip_urls: bool },
     CopyPubkey,
     CopyNoteId,
     CopyNoteJSON,
+    KeepAtTop,
+    ExpandQuotes,
+    SnoozeThread { secs: u64 },
+    VerifySignature,
 }
 
 impl NoteContextSelection {
-    pub fn process(&self, ui: &mut egui::Ui, note: &Note<'_>) {
+    pub fn process(&self, ui: &mut egui::Ui, ndb: &Ndb, note: &Note<'_>) {
         match self {
             NoteContextSelection::CopyText => {
                 ui.output_mut(|w| {
                     w.copied_text = note.content().to_string();
                 });
             }
+            NoteContextSelection::CopyCleanText { strip_urls } => {
+                if let Some(txn) = note.txn() {
+                    let text = clean_note_text(ndb, txn, note, *strip_urls);
+                    ui.output_mut(|w| w.copied_text = text);
+                }
+            }
             NoteContextSelection::CopyPubkey => {
                 ui.output_mut(|w| {
                     if let Some(bech) = Pubkey::new(*note.pubkey()).to_bech() {
@@ -34,11 +50,24 @@ impl NoteContextSelection {
                     }
                 });
             }
-            NoteContextSelection::CopyNoteJSON => {
-                ui.output_mut(|w| match note.json() {
-                    Ok(json) => w.copied_text = json,
-                    Err(err) => error!("error copying note json: {err}"),
-                });
+            // the app copies the raw event, see `NoteAction::CopyEventJson`
+            NoteContextSelection::CopyNoteJSON => {}
+            // the timeline owns the kept note, see `TimelineTabView::kept_at_top`
+            NoteContextSelection::KeepAtTop => {}
+            // the app owns snoozes, see `NoteAction::SnoozeThread`
+            NoteContextSelection::SnoozeThread { .. } => {}
+            NoteContextSelection::VerifySignature => {
+                if let Some(note_key) = note.key() {
+                    let result = verify_note(note);
+                    ui.ctx()
+                        .data_mut(|d| d.insert_temp(verification_id(note_key), result));
+                }
+            }
+            NoteContextSelection::ExpandQuotes => {
+                if let Some(note_key) = note.key() {
+                    ui.ctx()
+                        .data_mut(|d| d.insert_temp(expand_quotes_id(note_key), true));
+                }
             }
         }
     }
@@ -149,6 +178,7 @@ impl NoteContextButton {
     pub fn menu(
         ui: &mut egui::Ui,
         button_response: egui::Response,
+        options: NoteOptions,
     ) -> Option<NoteContextSelection> {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -161,6 +191,14 @@ impl NoteContextButton {
                 context_selection = Some(NoteContextSelection::CopyText);
                 ui.close_menu();
             }
+            if ui.button("Copy clean text").clicked() {
+                context_selection = Some(NoteContextSelection::CopyCleanText { strip_urls: false });
+                ui.close_menu();
+            }
+            if ui.button("Copy clean text without links").clicked() {
+                context_selection = Some(NoteContextSelection::CopyCleanText { strip_urls: true });
+                ui.close_menu();
+            }
             if ui.button("Copy user public key").clicked() {
                 context_selection = Some(NoteContextSelection::CopyPubkey);
                 ui.close_menu();
@@ -169,16 +207,103 @@ impl NoteContextButton {
                 context_selection = Some(NoteContextSelection::CopyNoteId);
                 ui.close_menu();
             }
-            if ui.button("Copy note json").clicked() {
+            if ui.button("Copy raw event").clicked() {
                 context_selection = Some(NoteContextSelection::CopyNoteJSON);
                 ui.close_menu();
             }
+            if ui.button("Expand all quotes").clicked() {
+                context_selection = Some(NoteContextSelection::ExpandQuotes);
+                ui.close_menu();
+            }
+            if options.has_keep_at_top() && ui.button("Keep at top").clicked() {
+                context_selection = Some(NoteContextSelection::KeepAtTop);
+                ui.close_menu();
+            }
+            if options.has_verify_signature() && ui.button("Verify signature").clicked() {
+                context_selection = Some(NoteContextSelection::VerifySignature);
+                ui.close_menu();
+            }
+            ui.menu_button("Snooze thread", |ui| {
+                for (label, secs) in SNOOZE_DURATIONS {
+                    if ui.button(label).clicked() {
+                        context_selection = Some(NoteContextSelection::SnoozeThread { secs });
+                        ui.close_menu();
+                    }
+                }
+            });
         });
 
         context_selection
     }
 }
 
+type VerificationResult = Result<NoteVerification, String>;
+
+fn verification_id(note_key: NoteKey) -> egui::Id {
+    egui::Id::new(("note_verification", note_key))
+}
+
+/// Re-check the note as it came off the wire rather than trusting that
+/// it was verified on the way into the database
+fn verify_note(note: &Note<'_>) -> VerificationResult {
+    let json = note.json().map_err(|err| err.to_string())?;
+    let note = enostr::Note::from_json(&json).map_err(|err| err.to_string())?;
+    Ok(note.verify_integrity())
+}
+
+/// Shows the result of [`NoteContextSelection::VerifySignature`] below
+/// `anchor` until it is dismissed
+pub fn verification_popover(ui: &mut egui::Ui, note_key: NoteKey, anchor: Rect) {
+    let id = verification_id(note_key);
+    let Some(result) = ui.ctx().data(|d| d.get_temp::<VerificationResult>(id)) else {
+        return;
+    };
+
+    let mut close = false;
+    let area = egui::Area::new(id)
+        .order(egui::Order::Foreground)
+        .pivot(egui::Align2::RIGHT_TOP)
+        .fixed_pos(anchor.right_bottom())
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(240.0);
+                ui.strong("Signature check");
+                match &result {
+                    Ok(verification) => {
+                        check_row(ui, "Id is the hash of the event", verification.id_matches);
+                        check_row(ui, "Signature is valid", verification.sig_valid);
+                    }
+                    Err(err) => {
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            format!("Could not check event: {err}"),
+                        );
+                    }
+                }
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if close || area.response.clicked_elsewhere() {
+        ui.ctx().data_mut(|d| d.remove::<VerificationResult>(id));
+    }
+}
+
+fn check_row(ui: &mut egui::Ui, label: &str, passed: bool) {
+    let (text, color) = if passed {
+        ("✔ pass", crate::colors::TEAL)
+    } else {
+        ("✘ fail", ui.visuals().error_fg_color)
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.label(RichText::new(text).color(color));
+    });
+}
+
 fn stationary_arbitrary_menu_button<R>(
     ui: &mut egui::Ui,
     button_response: egui::Response,
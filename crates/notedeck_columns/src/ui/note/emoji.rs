@@ -0,0 +1,194 @@
+use crate::images::ImageType;
+
+use nostrdb::{Ndb, Note, NoteKey, Transaction};
+use notedeck::ImageCache;
+use std::collections::HashMap;
+
+/// NIP-30 custom emoji, mapping `shortcode` to a loaded texture
+pub type CustomEmojiMap = HashMap<String, egui::TextureId>;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum EmojiSegment<'a> {
+    Text(&'a str),
+    Emoji(egui::TextureId),
+}
+
+fn is_shortcode(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split text into plain text and `:shortcode:` emoji segments. Only
+/// shortcodes found in the map become emoji, unknown shortcodes are left
+/// as plain text.
+pub fn split_shortcodes<'a>(text: &'a str, emojis: &CustomEmojiMap) -> Vec<EmojiSegment<'a>> {
+    let mut segments = Vec::new();
+    let mut last = 0;
+    let mut search = 0;
+
+    while let Some(start) = text[search..].find(':').map(|i| i + search) {
+        let end = if let Some(end) = text[start + 1..].find(':') {
+            start + 1 + end
+        } else {
+            break;
+        };
+
+        let shortcode = &text[start + 1..end];
+        match emojis.get(shortcode) {
+            Some(texture) if is_shortcode(shortcode) => {
+                if start > last {
+                    segments.push(EmojiSegment::Text(&text[last..start]));
+                }
+                segments.push(EmojiSegment::Emoji(*texture));
+                last = end + 1;
+                search = end + 1;
+            }
+
+            // the closing colon may be the start of the next shortcode
+            _ => search = end,
+        }
+    }
+
+    if last < text.len() {
+        segments.push(EmojiSegment::Text(&text[last..]));
+    }
+
+    segments
+}
+
+fn emoji_tags(note: &Note) -> Vec<(String, String)> {
+    let mut emojis = Vec::new();
+
+    for tag in note.tags().iter() {
+        if tag.count() < 3 {
+            continue;
+        }
+
+        if tag.get(0).and_then(|t| t.variant().str()) != Some("emoji") {
+            continue;
+        }
+
+        if let (Some(shortcode), Some(url)) = (
+            tag.get(1).and_then(|t| t.variant().str()),
+            tag.get(2).and_then(|t| t.variant().str()),
+        ) {
+            emojis.push((shortcode.to_owned(), url.to_owned()));
+        }
+    }
+
+    emojis
+}
+
+/// An author's emoji map, kept in egui memory so it's only rebuilt when
+/// their profile changes or while its images are loading
+#[derive(Clone, Default)]
+struct AuthorEmojis {
+    profile_key: u64,
+    urls: Vec<String>,
+    emojis: CustomEmojiMap,
+    loading: bool,
+}
+
+/// Build a custom emoji map from the `emoji` tags on an author's profile
+/// (kind 0). Emoji images are fetched into the image cache, and only
+/// the ones that have finished loading are included.
+pub fn author_emoji_map(
+    ctx: &egui::Context,
+    ndb: &Ndb,
+    txn: &Transaction,
+    img_cache: &mut ImageCache,
+    pubkey: &[u8; 32],
+) -> CustomEmojiMap {
+    let profile_key = if let Ok(profile) = ndb.get_profile_by_pubkey(txn, pubkey) {
+        profile.record().note_key()
+    } else {
+        return CustomEmojiMap::new();
+    };
+
+    let id = egui::Id::new(("author_emojis", pubkey));
+    if let Some(cached) = ctx
+        .data(|d| d.get_temp::<AuthorEmojis>(id))
+        .filter(|cached| {
+            cached.profile_key == profile_key
+                && !cached.loading
+                && cached
+                    .urls
+                    .iter()
+                    .all(|url| img_cache.map().contains_key(url))
+        })
+    {
+        // keep the textures from being evicted while they're shown
+        for url in &cached.urls {
+            img_cache.touch(url);
+        }
+        return cached.emojis;
+    }
+
+    let profile_note = if let Ok(note) = ndb.get_note_by_key(txn, NoteKey::new(profile_key)) {
+        note
+    } else {
+        return CustomEmojiMap::new();
+    };
+
+    let mut author = AuthorEmojis {
+        profile_key,
+        ..Default::default()
+    };
+    for (shortcode, url) in emoji_tags(&profile_note) {
+        if img_cache.map().get(&url).is_none() {
+            let res = crate::images::fetch_img(img_cache, ctx, &url, ImageType::Content(64, 64));
+            img_cache.map_mut().insert(url.to_owned(), res);
+        }
+
+        img_cache.touch(&url);
+        match img_cache.map()[&url].ready() {
+            Some(Ok(texture)) => {
+                author.emojis.insert(shortcode, texture.id());
+            }
+            Some(Err(_)) => {}
+            None => author.loading = true,
+        }
+        author.urls.push(url);
+    }
+
+    let emojis = author.emojis.clone();
+    ctx.data_mut(|d| d.insert_temp(id, author));
+    emojis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emojis() -> CustomEmojiMap {
+        let mut emojis = CustomEmojiMap::new();
+        emojis.insert("soapbox".to_owned(), egui::TextureId::Managed(1));
+        emojis.insert("gleasonator".to_owned(), egui::TextureId::Managed(2));
+        emojis
+    }
+
+    #[test]
+    fn test_split_shortcodes() {
+        let segments = split_shortcodes("hello :soapbox: world:gleasonator:", &emojis());
+        assert_eq!(
+            segments,
+            vec![
+                EmojiSegment::Text("hello "),
+                EmojiSegment::Emoji(egui::TextureId::Managed(1)),
+                EmojiSegment::Text(" world"),
+                EmojiSegment::Emoji(egui::TextureId::Managed(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_shortcodes_are_text() {
+        let segments = split_shortcodes("time is 12:30 :unknown::soapbox:", &emojis());
+        assert_eq!(
+            segments,
+            vec![
+                EmojiSegment::Text("time is 12:30 :unknown:"),
+                EmojiSegment::Emoji(egui::TextureId::Managed(1)),
+            ]
+        );
+    }
+}
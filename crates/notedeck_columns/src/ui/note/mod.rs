@@ -1,5 +1,7 @@
+pub mod content_warning;
 pub mod contents;
 pub mod context;
+pub mod emoji;
 pub mod options;
 pub mod post;
 pub mod quote_repost;
@@ -8,7 +10,11 @@ pub mod reply_description;
 
 pub use contents::NoteContents;
 pub use context::{NoteContextButton, NoteContextSelection};
-pub use options::NoteOptions;
+pub use emoji::CustomEmojiMap;
+pub use options::{
+    DesktopNetwork, DisplayNameFormat, ImageLayout, LinkifyMode, NetworkStatus, NoteDensity,
+    NoteOptions, VideoAutoplay,
+};
 pub use post::{PostAction, PostResponse, PostType, PostView};
 pub use quote_repost::QuoteRepostView;
 pub use reply::PostReplyView;
@@ -16,6 +22,7 @@ pub use reply_description::reply_desc;
 
 use crate::{
     actionbar::NoteAction,
+    geo,
     profile::get_display_name,
     ui::{self, View},
 };
@@ -24,10 +31,16 @@ use egui::emath::{pos2, Vec2};
 use egui::{Id, Label, Pos2, Rect, Response, RichText, Sense};
 use enostr::{NoteId, Pubkey};
 use nostrdb::{Ndb, Note, NoteKey, Transaction};
-use notedeck::{CachedNote, ImageCache, NoteCache, NotedeckTextStyle};
+use notedeck::{
+    note::{root_note_id_from_selected_id, thread_depth},
+    CachedNote, ImageCache, NoteCache, NotedeckTextStyle,
+};
 
 use super::profile::preview::one_line_display_name_widget;
 
+/// Replies are indented for their thread depth up to this depth
+const MAX_INDENT_DEPTH: u32 = 5;
+
 pub struct NoteView<'a> {
     ndb: &'a Ndb,
     note_cache: &'a mut NoteCache,
@@ -35,6 +48,9 @@ pub struct NoteView<'a> {
     parent: Option<NoteKey>,
     note: &'a nostrdb::Note<'a>,
     flags: NoteOptions,
+    custom_emoji: Option<&'a CustomEmojiMap>,
+    quote_chain: Vec<[u8; 32]>,
+    avatar_click_action: Option<NoteAction>,
 }
 
 pub struct NoteResponse {
@@ -85,9 +101,67 @@ impl<'a> NoteView<'a> {
             parent,
             note,
             flags,
+            custom_emoji: None,
+            quote_chain: vec![],
+            avatar_click_action: None,
         }
     }
 
+    /// Return `action` when the author's avatar is clicked, instead of
+    /// opening their profile
+    pub fn with_avatar_click_action(mut self, action: NoteAction) -> Self {
+        self.avatar_click_action = Some(action);
+        self
+    }
+
+    /// What clicking the author's avatar does
+    fn avatar_clicked(&self) -> NoteAction {
+        self.avatar_click_action
+            .clone()
+            .unwrap_or_else(|| NoteAction::OpenProfile(Pubkey::new(*self.note.pubkey())))
+    }
+
+    /// NIP-30 custom emoji to render in place of `:shortcode:`s in the
+    /// note content
+    pub fn custom_emoji_map(mut self, emojis: &'a CustomEmojiMap) -> Self {
+        self.custom_emoji = Some(emojis);
+        self
+    }
+
+    /// The ids of the notes quoting this one when we're expanding a quote
+    /// chain. Notes in a chain expand all of their quotes.
+    pub fn quote_chain(mut self, quote_chain: Vec<[u8; 32]>) -> Self {
+        self.quote_chain = quote_chain;
+        self
+    }
+
+    /// The content options and quote chain to render our contents with.
+    /// Quotes expand recursively when the user asked to expand them for
+    /// this note, or when we're part of an expanded chain.
+    fn quote_expansion(
+        &self,
+        ui: &egui::Ui,
+        note_key: NoteKey,
+    ) -> (NoteOptions, Option<Vec<[u8; 32]>>) {
+        let expanded = !self.quote_chain.is_empty()
+            || ui
+                .ctx()
+                .data(|d| d.get_temp::<bool>(contents::expand_quotes_id(note_key)))
+                .unwrap_or(false);
+
+        if !expanded {
+            return (self.options(), None);
+        }
+
+        let mut options = self.options();
+        options.set_note_previews(true);
+
+        let mut chain = self.quote_chain.clone();
+        chain.push(*self.note.id());
+
+        (options, Some(chain))
+    }
+
     pub fn note_options(mut self, options: NoteOptions) -> Self {
         *self.options_mut() = options;
         self
@@ -170,20 +244,24 @@ impl<'a> NoteView<'a> {
             ui.put(rect, |ui: &mut egui::Ui| {
                 ui.add(
                     ui::Username::new(profile.as_ref().ok(), self.note.pubkey())
+                        .format(self.options().display_name_format())
                         .abbreviated(6)
                         .pk_colored(true),
                 )
             });
 
-            ui.add(&mut NoteContents::new(
-                self.ndb,
-                self.img_cache,
-                self.note_cache,
-                txn,
-                self.note,
-                note_key,
-                self.flags,
-            ));
+            ui.add(
+                &mut NoteContents::new(
+                    self.ndb,
+                    self.img_cache,
+                    self.note_cache,
+                    txn,
+                    self.note,
+                    note_key,
+                    self.flags,
+                )
+                .custom_emoji(self.custom_emoji),
+            );
             //});
         })
         .response
@@ -241,14 +319,17 @@ impl<'a> NoteView<'a> {
                     ui::show_pointer(ui);
                 }
 
-                resp
+                ui::touch_target(ui, resp, self.options().has_touch_mode())
+            }
+            None => {
+                let resp = ui
+                    .add(
+                        ui::ProfilePic::new(self.img_cache, ui::ProfilePic::no_pfp_url())
+                            .size(pfp_size),
+                    )
+                    .interact(sense);
+                ui::touch_target(ui, resp, self.options().has_touch_mode())
             }
-            None => ui
-                .add(
-                    ui::ProfilePic::new(self.img_cache, ui::ProfilePic::no_pfp_url())
-                        .size(pfp_size),
-                )
-                .interact(sense),
         }
     }
 
@@ -286,7 +367,9 @@ impl<'a> NoteView<'a> {
                             .text_style(style.text_style()),
                     );
                 });
-                NoteView::new(self.ndb, self.note_cache, self.img_cache, &note_to_repost).show(ui)
+                NoteView::new(self.ndb, self.note_cache, self.img_cache, &note_to_repost)
+                    .note_options(self.options())
+                    .show(ui)
             } else {
                 self.show_standard(ui)
             }
@@ -299,6 +382,7 @@ impl<'a> NoteView<'a> {
         note: &Note,
         profile: &Result<nostrdb::ProfileRecord<'_>, nostrdb::Error>,
         options: NoteOptions,
+        zap_sats: Option<u64>,
         container_right: Pos2,
     ) -> NoteResponse {
         #[cfg(feature = "profiling")]
@@ -308,11 +392,50 @@ impl<'a> NoteView<'a> {
 
         let inner_response = ui.horizontal(|ui| {
             ui.spacing_mut().item_spacing.x = 2.0;
-            ui.add(ui::Username::new(profile.as_ref().ok(), note.pubkey()).abbreviated(20));
+            ui.add(
+                ui::Username::new(profile.as_ref().ok(), note.pubkey())
+                    .format(options.display_name_format())
+                    .abbreviated(20),
+            );
 
             let cached_note = note_cache.cached_note_or_insert_mut(note_key, note);
             render_reltime(ui, cached_note, true);
 
+            if options.has_show_client_proof_of_work() {
+                let pow = notedeck::note::pow_difficulty(note.id());
+                secondary_label(ui, "⋅");
+                ui.add(Label::new(
+                    RichText::new(format!("⛏ {pow}"))
+                        .size(10.0)
+                        .color(ui.style().visuals.noninteractive().fg_stroke.color),
+                ))
+                .on_hover_text(format!(
+                    "Note id has {pow} leading zero bits of proof of work"
+                ));
+            }
+
+            if options.has_show_geo_tag() {
+                if let Some(geohash) = geo::note_geohash(note) {
+                    secondary_label(ui, "⋅");
+                    geo_tag(ui, geohash);
+                }
+            }
+
+            if let Some(sats) = zap_sats.filter(|sats| *sats > 0) {
+                secondary_label(ui, "⋅");
+                ui.add(Label::new(
+                    RichText::new(format!("⚡ {sats} sats"))
+                        .size(10.0)
+                        .color(ui.style().visuals.noninteractive().fg_stroke.color),
+                ))
+                .on_hover_text("Total zapped to this note");
+            }
+
+            if options.has_show_event_kind_badge() && note.kind() != 1 {
+                secondary_label(ui, "⋅");
+                event_kind_badge(ui, note_key, note.kind());
+            }
+
             if options.has_options_button() {
                 let context_pos = {
                     let size = NoteContextButton::max_width();
@@ -321,7 +444,9 @@ impl<'a> NoteView<'a> {
                 };
 
                 let resp = ui.add(NoteContextButton::new(note_key).place_at(context_pos));
-                NoteContextButton::menu(ui, resp.clone())
+                let resp = ui::touch_target(ui, resp, options.has_touch_mode());
+                context::verification_popover(ui, note_key, context_pos);
+                NoteContextButton::menu(ui, resp.clone(), options)
             } else {
                 None
             }
@@ -330,6 +455,101 @@ impl<'a> NoteView<'a> {
         NoteResponse::new(inner_response.response).select_option(inner_response.inner)
     }
 
+    /// A small quoted preview of the thread root above a reply, next to a
+    /// vertical reply line. Only shown with
+    /// [`NoteOptions::show_thread_root_preview`]
+    fn thread_root_preview(
+        &mut self,
+        ui: &mut egui::Ui,
+        txn: &Transaction,
+        note_key: NoteKey,
+    ) -> Option<NoteAction> {
+        if !self.options().has_show_thread_root_preview() {
+            return None;
+        }
+
+        let root_id =
+            *root_note_id_from_selected_id(self.ndb, self.note_cache, txn, self.note.id());
+        if &root_id == self.note.id() {
+            return None;
+        }
+
+        let line_width = 2.0;
+        let resp = ui.horizontal(|ui| {
+            ui.add_space(line_width + 6.0);
+            contents::render_note_preview(
+                ui,
+                self.ndb,
+                self.note_cache,
+                self.img_cache,
+                txn,
+                &root_id,
+                note_key,
+                None,
+            )
+        });
+
+        let rect = resp.response.rect;
+        let stroke = egui::Stroke::new(line_width, ui.visuals().noninteractive().bg_stroke.color);
+        ui.painter().vline(
+            rect.left() + line_width / 2.0,
+            egui::Rangef::new(rect.top() + 8.0, rect.bottom() - 8.0),
+            stroke,
+        );
+
+        resp.inner.action
+    }
+
+    /// Whether to render the note's contents. Notes that are only a
+    /// content warning and media show the warning instead, until the
+    /// user reveals them.
+    fn show_contents(&self, ui: &mut egui::Ui) -> bool {
+        if !self.options().has_content_warning_layout() {
+            return true;
+        }
+
+        match content_warning::content_warning_only(self.note) {
+            Some(reason) => content_warning::content_warning_only_ui(ui, self.note.id(), &reason),
+            None => true,
+        }
+    }
+
+    /// How far to indent this note for its thread depth, with
+    /// [`NoteOptions::show_thread_depth`]
+    fn thread_depth_indent(&mut self, txn: &Transaction, note_key: NoteKey) -> f32 {
+        if !self.options().has_show_thread_depth() {
+            return 0.0;
+        }
+
+        let depth = thread_depth(self.ndb, self.note_cache, txn, note_key, self.note);
+        depth.min(MAX_INDENT_DEPTH) as f32 * 8.0
+    }
+
+    /// The action bar, faded in while the note is `hovered` when it's
+    /// compact
+    fn actionbar(&self, ui: &mut egui::Ui, note_key: NoteKey, hovered: bool) -> Option<NoteAction> {
+        if !self.options().has_actionbar() {
+            return None;
+        }
+
+        if !self.options().has_compact_action_bar() {
+            return render_note_actionbar(ui, self.note.id(), note_key, self.options()).inner;
+        }
+
+        let opacity = ui
+            .ctx()
+            .animate_bool(ui.id().with(("actionbar_fade", note_key)), hovered);
+        if opacity == 0.0 {
+            return None;
+        }
+
+        ui.scope(|ui| {
+            ui.set_opacity(opacity);
+            render_note_actionbar(ui, self.note.id(), note_key, self.options()).inner
+        })
+        .inner
+    }
+
     fn show_standard(&mut self, ui: &mut egui::Ui) -> NoteResponse {
         #[cfg(feature = "profiling")]
         puffin::profile_function!();
@@ -338,10 +558,17 @@ impl<'a> NoteView<'a> {
 
         let mut note_action: Option<NoteAction> = None;
         let mut selected_option: Option<NoteContextSelection> = None;
+        let (contents_options, quote_chain) = self.quote_expansion(ui, note_key);
+        let zap_sats = self.options().has_show_zap_summary().then(|| {
+            notedeck::note::note_zap_total(self.ndb, self.note_cache, txn, note_key, self.note)
+        });
 
         let hitbox_id = note_hitbox_id(note_key, self.options(), self.parent);
         let profile = self.ndb.get_profile_by_pubkey(txn, self.note.pubkey());
         let maybe_hitbox = maybe_note_hitbox(ui, hitbox_id);
+        let hovered = maybe_hitbox
+            .as_ref()
+            .map_or(false, |hitbox| hitbox.contains_pointer());
         let container_right = {
             let r = ui.available_rect_before_wrap();
             let x = r.max.x;
@@ -349,88 +576,106 @@ impl<'a> NoteView<'a> {
             Pos2::new(x, y)
         };
 
+        let indent = self.thread_depth_indent(txn, note_key);
+
         // wide design
         let response = if self.options().has_wide() {
-            ui.vertical(|ui| {
-                ui.horizontal(|ui| {
-                    if self.pfp(note_key, &profile, ui).clicked() {
-                        note_action =
-                            Some(NoteAction::OpenProfile(Pubkey::new(*self.note.pubkey())));
-                    };
-
-                    let size = ui.available_size();
+            egui::Frame::none()
+                .inner_margin(egui::Margin {
+                    left: indent,
+                    ..Default::default()
+                })
+                .show(ui, |ui| {
                     ui.vertical(|ui| {
-                        ui.add_sized([size.x, self.options().pfp_size()], |ui: &mut egui::Ui| {
-                            ui.horizontal_centered(|ui| {
-                                selected_option = NoteView::note_header(
-                                    ui,
-                                    self.note_cache,
-                                    self.note,
-                                    &profile,
-                                    self.options(),
-                                    container_right,
-                                )
-                                .context_selection;
-                            })
-                            .response
+                        ui.horizontal(|ui| {
+                            if self.pfp(note_key, &profile, ui).clicked() {
+                                note_action = Some(self.avatar_clicked());
+                            };
+
+                            let size = ui.available_size();
+                            ui.vertical(|ui| {
+                                ui.add_sized(
+                                    [size.x, self.options().pfp_size()],
+                                    |ui: &mut egui::Ui| {
+                                        ui.horizontal_centered(|ui| {
+                                            selected_option = NoteView::note_header(
+                                                ui,
+                                                self.note_cache,
+                                                self.note,
+                                                &profile,
+                                                self.options(),
+                                                zap_sats,
+                                                container_right,
+                                            )
+                                            .context_selection;
+                                        })
+                                        .response
+                                    },
+                                );
+
+                                let note_reply = self
+                                    .note_cache
+                                    .cached_note_or_insert_mut(note_key, self.note)
+                                    .reply
+                                    .borrow(self.note.tags());
+
+                                if note_reply.reply().is_some() {
+                                    let action = ui
+                                        .horizontal(|ui| {
+                                            reply_desc(
+                                                ui,
+                                                txn,
+                                                &note_reply,
+                                                self.ndb,
+                                                self.img_cache,
+                                                self.note_cache,
+                                            )
+                                        })
+                                        .inner;
+
+                                    if action.is_some() {
+                                        note_action = action;
+                                    }
+                                }
+                            });
                         });
 
-                        let note_reply = self
-                            .note_cache
-                            .cached_note_or_insert_mut(note_key, self.note)
-                            .reply
-                            .borrow(self.note.tags());
-
-                        if note_reply.reply().is_some() {
-                            let action = ui
-                                .horizontal(|ui| {
-                                    reply_desc(
-                                        ui,
-                                        txn,
-                                        &note_reply,
-                                        self.ndb,
-                                        self.img_cache,
-                                        self.note_cache,
-                                    )
-                                })
-                                .inner;
-
-                            if action.is_some() {
-                                note_action = action;
-                            }
+                        if let Some(action) = self.thread_root_preview(ui, txn, note_key) {
+                            note_action = Some(action);
                         }
-                    });
-                });
 
-                let mut contents = NoteContents::new(
-                    self.ndb,
-                    self.img_cache,
-                    self.note_cache,
-                    txn,
-                    self.note,
-                    note_key,
-                    self.options(),
-                );
+                        if self.show_contents(ui) {
+                            let mut contents = NoteContents::new(
+                                self.ndb,
+                                self.img_cache,
+                                self.note_cache,
+                                txn,
+                                self.note,
+                                note_key,
+                                contents_options,
+                            )
+                            .custom_emoji(self.custom_emoji)
+                            .quote_chain(quote_chain.as_deref());
 
-                ui.add(&mut contents);
+                            ui.add(&mut contents);
 
-                if let Some(action) = contents.action() {
-                    note_action = Some(*action);
-                }
+                            if let Some(action) = contents.action() {
+                                note_action = Some(action.clone());
+                            }
+                        }
 
-                if self.options().has_actionbar() {
-                    if let Some(action) = render_note_actionbar(ui, self.note.id(), note_key).inner
-                    {
-                        note_action = Some(action);
-                    }
-                }
-            })
-            .response
+                        if let Some(action) = self.actionbar(ui, note_key, hovered) {
+                            note_action = Some(action);
+                        }
+                    })
+                })
+                .response
         } else {
             // main design
             ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP), |ui| {
+                ui.add_space(indent);
                 if self.pfp(note_key, &profile, ui).clicked() {
-                    note_action = Some(NoteAction::OpenProfile(Pubkey::new(*self.note.pubkey())));
+                    note_action = Some(self.avatar_clicked());
                 };
 
                 ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
@@ -440,6 +685,7 @@ impl<'a> NoteView<'a> {
                         self.note,
                         &profile,
                         self.options(),
+                        zap_sats,
                         container_right,
                     )
                     .context_selection;
@@ -468,28 +714,32 @@ impl<'a> NoteView<'a> {
                         }
                     });
 
-                    let mut contents = NoteContents::new(
-                        self.ndb,
-                        self.img_cache,
-                        self.note_cache,
-                        txn,
-                        self.note,
-                        note_key,
-                        self.options(),
-                    );
-                    ui.add(&mut contents);
-
-                    if let Some(action) = contents.action() {
-                        note_action = Some(*action);
+                    if let Some(action) = self.thread_root_preview(ui, txn, note_key) {
+                        note_action = Some(action);
                     }
 
-                    if self.options().has_actionbar() {
-                        if let Some(action) =
-                            render_note_actionbar(ui, self.note.id(), note_key).inner
-                        {
-                            note_action = Some(action);
+                    if self.show_contents(ui) {
+                        let mut contents = NoteContents::new(
+                            self.ndb,
+                            self.img_cache,
+                            self.note_cache,
+                            txn,
+                            self.note,
+                            note_key,
+                            contents_options,
+                        )
+                        .custom_emoji(self.custom_emoji)
+                        .quote_chain(quote_chain.as_deref());
+                        ui.add(&mut contents);
+
+                        if let Some(action) = contents.action() {
+                            note_action = Some(action.clone());
                         }
                     }
+
+                    if let Some(action) = self.actionbar(ui, note_key, hovered) {
+                        note_action = Some(action);
+                    }
                 });
             })
             .response
@@ -583,13 +833,24 @@ fn render_note_actionbar(
     ui: &mut egui::Ui,
     note_id: &[u8; 32],
     note_key: NoteKey,
+    options: NoteOptions,
 ) -> egui::InnerResponse<Option<NoteAction>> {
     #[cfg(feature = "profiling")]
     puffin::profile_function!();
 
+    let touch_mode = options.has_touch_mode();
+
     ui.horizontal(|ui| {
+        if touch_mode {
+            // leave room between buttons so the expanded hit areas
+            // don't overlap
+            ui.spacing_mut().item_spacing.x = ui::TOUCH_TARGET_SIZE / 2.0;
+        }
+
         let reply_resp = reply_button(ui, note_key);
+        let reply_resp = ui::touch_target(ui, reply_resp, touch_mode);
         let quote_resp = quote_repost_button(ui, note_key);
+        let quote_resp = ui::touch_target(ui, quote_resp, touch_mode);
 
         if reply_resp.clicked() {
             Some(NoteAction::Reply(NoteId::new(*note_id)))
@@ -601,6 +862,94 @@ fn render_note_actionbar(
     })
 }
 
+/// 📍 and the location of a geohash, which opens a map when clicked
+fn geo_tag(ui: &mut egui::Ui, geohash: &str) {
+    let point = if let Some(point) = geo::decode_geohash(geohash) {
+        point
+    } else {
+        return;
+    };
+
+    let resp = ui
+        .add(
+            Label::new(
+                RichText::new(format!("📍 {}", geo::format_point(&point)))
+                    .size(10.0)
+                    .color(ui.style().visuals.noninteractive().fg_stroke.color),
+            )
+            .selectable(false)
+            .sense(egui::Sense::click()),
+        )
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .on_hover_text("Open in a map");
+
+    if resp.clicked() {
+        let zoom = geo::zoom_for_geohash(geohash);
+        ui.ctx()
+            .open_url(egui::OpenUrl::new_tab(geo::map_url(&point, zoom)));
+    }
+}
+
+/// "kind N", which explains what that kind is when clicked
+fn event_kind_badge(ui: &mut egui::Ui, note_key: NoteKey, kind: u32) {
+    let resp = ui
+        .add(
+            Label::new(
+                RichText::new(format!("kind {kind}"))
+                    .size(10.0)
+                    .color(ui.style().visuals.noninteractive().fg_stroke.color)
+                    .background_color(ui.visuals().faint_bg_color),
+            )
+            .selectable(false)
+            .sense(egui::Sense::click()),
+        )
+        .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+    let popup_id = ui.id().with(("kind_badge", note_key));
+    if resp.clicked() {
+        ui.memory_mut(|m| m.toggle_popup(popup_id));
+    }
+
+    egui::popup_below_widget(
+        ui,
+        popup_id,
+        &resp,
+        egui::PopupCloseBehavior::CloseOnClickOutside,
+        |ui| {
+            ui.set_max_width(240.0);
+            ui.strong(format!("What is kind {kind}?"));
+            ui.label(event_kind_description(kind).unwrap_or(
+                "A kind this app doesn't know about. Its meaning is defined by \
+                 the NIP or app that publishes it.",
+            ));
+        },
+    );
+}
+
+/// What a nostr event kind is for, for the kinds we're likely to see in
+/// a timeline
+fn event_kind_description(kind: u32) -> Option<&'static str> {
+    let desc = match kind {
+        0 => "Profile metadata: a user's name, picture and about text (NIP-01).",
+        1 => "A short text note (NIP-01).",
+        3 => "A follow list (NIP-02).",
+        5 => "A deletion request for earlier events (NIP-09).",
+        6 => "A repost of a text note (NIP-18).",
+        7 => "A reaction, such as a like (NIP-25).",
+        16 => "A repost of an event that isn't a text note (NIP-18).",
+        1063 => "File metadata for an uploaded file (NIP-94).",
+        1311 => "A live event chat message (NIP-53).",
+        9734 => "A zap request (NIP-57).",
+        9735 => "A zap receipt, proof that a lightning payment was made (NIP-57).",
+        10000 => "A mute list (NIP-51).",
+        10002 => "The relays a user reads from and writes to (NIP-65).",
+        30023 => "A long-form article (NIP-23).",
+        30311 => "A live event, such as a stream (NIP-53).",
+        _ => return None,
+    };
+    Some(desc)
+}
+
 fn secondary_label(ui: &mut egui::Ui, s: impl Into<String>) {
     let color = ui.style().visuals.noninteractive().fg_stroke.color;
     ui.add(Label::new(RichText::new(s).size(10.0).color(color)));
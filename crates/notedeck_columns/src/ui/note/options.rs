@@ -1,26 +1,246 @@
 use crate::ui::ProfilePic;
 use bitflags::bitflags;
+use enostr::Pubkey;
 
 bitflags! {
     // Attributes can be applied to flags types
     #[repr(transparent)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct NoteOptions: u64 {
-        const actionbar       = 0b0000000000000001;
-        const note_previews   = 0b0000000000000010;
-        const small_pfp       = 0b0000000000000100;
-        const medium_pfp      = 0b0000000000001000;
-        const wide            = 0b0000000000010000;
-        const selectable_text = 0b0000000000100000;
-        const textmode        = 0b0000000001000000;
-        const options_button  = 0b0000000010000000;
-        const hide_media      = 0b0000000100000000;
+        const actionbar       = 1 << 0;
+        const note_previews   = 1 << 1;
+        const small_pfp       = 1 << 2;
+        const medium_pfp      = 1 << 3;
+        const wide            = 1 << 4;
+        const selectable_text = 1 << 5;
+        const textmode        = 1 << 6;
+        const options_button  = 1 << 7;
+        const hide_media      = 1 << 8;
+        const touch_mode      = 1 << 9;
+
+        // nothing plays without a tap, whatever the other autoplay
+        // options say. See [`NoteOptions::gifs_autoplay`] and
+        // [`NoteOptions::videos_autoplay`]
+        const reduce_autoplay = 1 << 10;
+
+        const show_deleted_placeholder = 1 << 11;
+
+        // note content text wrapping, see [`NoteOptions::word_wrap_mode`]
+        const wrap_extend     = 1 << 12;
+        const wrap_truncate   = 1 << 13;
+
+        // offer "Keep at top" in the note context menu
+        const keep_at_top     = 1 << 14;
+
+        // show a preview of the thread root above replies
+        const show_thread_root_preview = 1 << 15;
+
+        // show the warning in place of media for notes that are only a
+        // content warning and media, see [`super::content_warning`]
+        const content_warning_layout = 1 << 16;
+
+        // render a map thumbnail for notes with a geohash tag. This
+        // fetches map tiles from an external tile server
+        const show_geo_map    = 1 << 17;
+
+        // play animated gifs without waiting for a tap, unless
+        // reduce_autoplay is set
+        const gif_autoplay    = 1 << 18;
+
+        // tighter note spacing, see [`NoteOptions::density`]
+        const compact         = 1 << 19;
+
+        // lay out multiple images in a grid, see [`NoteOptions::image_layout`]
+        const image_grid      = 1 << 20;
+
+        // show the note id's proof of work difficulty in the header
+        const show_client_proof_of_work = 1 << 21;
+
+        // offer "Verify signature" in the note context menu
+        const verify_signature = 1 << 22;
+
+        // indent replies by how deep they are in their thread
+        const show_thread_depth = 1 << 23;
+
+        // show links as cards below the text instead of inline
+        const hide_urls_in_content = 1 << 24;
+
+        // only show the action bar while the note is hovered
+        const compact_action_bar = 1 << 25;
+
+        // badge the header of notes that aren't kind 1 with their kind
+        const show_event_kind_badge = 1 << 26;
+
+        // show the location of notes with a geohash tag in their header
+        const show_geo_tag = 1 << 27;
+
+        // which links in note content are clickable, see [`NoteOptions::linkify_mode`]
+        const linkify_all  = 1 << 28;
+        const linkify_none = 1 << 29;
+
+        // show where shortened links like t.co really go
+        const expand_shortened_urls = 1 << 30;
+
+        // when video previews load, unless reduce_autoplay is set. See
+        // [`NoteOptions::video_autoplay`]
+        const video_autoplay_always = 1 << 31;
+        const video_autoplay_never  = 1 << 32;
+
+        // show a one line stub for muted notes that reveals them when tapped
+        const show_muted_placeholder = 1 << 33;
+
+        // show the total sats zapped to the note in its header
+        const show_zap_summary = 1 << 34;
+
+        // how authors are named, see [`NoteOptions::display_name_format`]
+        const name_username = 1 << 35;
+        const name_pubkey   = 1 << 36;
+        const name_both     = 1 << 37;
+    }
+}
+
+/// How tightly notes are packed in a timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NoteDensity {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+/// How a note with multiple images lays them out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ImageLayout {
+    /// A horizontally scrolling row
+    #[default]
+    Stack,
+
+    /// A 2 or 3 column gallery
+    Grid,
+}
+
+/// Which links in note content are clickable. Links that aren't are shown
+/// as plain text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LinkifyMode {
+    /// Links with any scheme
+    All,
+
+    /// Only `https://` links
+    #[default]
+    HttpsOnly,
+
+    /// No links
+    None,
+}
+
+impl LinkifyMode {
+    pub fn linkifies(self, url: &str) -> bool {
+        match self {
+            LinkifyMode::All => true,
+            LinkifyMode::HttpsOnly => url
+                .get(..8)
+                .map_or(false, |scheme| scheme.eq_ignore_ascii_case("https://")),
+            LinkifyMode::None => false,
+        }
+    }
+}
+
+/// How a note's author is named
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DisplayNameFormat {
+    /// The profile's `display_name`
+    #[default]
+    DisplayName,
+
+    /// The profile's `name`
+    Username,
+
+    /// The author's npub, truncated
+    Pubkey,
+
+    /// `display_name (@name)`
+    Both,
+}
+
+impl DisplayNameFormat {
+    /// The name to show for an author with these profile fields. Missing
+    /// fields fall back to the other one, `None` when there's neither.
+    pub fn format(
+        self,
+        display_name: Option<&str>,
+        name: Option<&str>,
+        pk: &[u8; 32],
+    ) -> Option<String> {
+        let display_name = display_name.filter(|n| !n.is_empty());
+        let name = name.filter(|n| !n.is_empty());
+
+        match self {
+            DisplayNameFormat::DisplayName => display_name.or(name).map(str::to_owned),
+            DisplayNameFormat::Username => name.or(display_name).map(str::to_owned),
+            DisplayNameFormat::Pubkey => Some(truncated_npub(pk)),
+            DisplayNameFormat::Both => match (display_name, name) {
+                (Some(display_name), Some(name)) => Some(format!("{} (@{})", display_name, name)),
+                (display_name, name) => display_name.or(name).map(str::to_owned),
+            },
+        }
+    }
+}
+
+/// The start of `pk`'s npub, enough to tell authors apart
+fn truncated_npub(pk: &[u8; 32]) -> String {
+    let npub = Pubkey::new(*pk).to_bech().unwrap_or_default();
+    match npub.get(..12) {
+        Some(start) => format!("{}…", start),
+        None => npub,
+    }
+}
+
+/// What kind of network we're on, so we can avoid using up metered data
+pub trait NetworkStatus {
+    fn is_wifi(&self) -> bool;
+}
+
+/// Desktops don't tell us their network type, and are rarely on metered
+/// connections, so we treat them as on wifi
+pub struct DesktopNetwork;
+
+impl NetworkStatus for DesktopNetwork {
+    fn is_wifi(&self) -> bool {
+        true
+    }
+}
+
+/// When videos in notes start loading without being asked to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum VideoAutoplay {
+    Always,
+
+    /// Only when the network isn't metered
+    #[default]
+    OnWifi,
+
+    /// Only once the play button is pressed
+    Never,
+}
+
+impl VideoAutoplay {
+    pub fn autoplays(self, network: &dyn NetworkStatus) -> bool {
+        match self {
+            VideoAutoplay::Always => true,
+            VideoAutoplay::OnWifi => network.is_wifi(),
+            VideoAutoplay::Never => false,
+        }
     }
 }
 
 impl Default for NoteOptions {
     fn default() -> NoteOptions {
-        NoteOptions::options_button | NoteOptions::note_previews | NoteOptions::actionbar
+        NoteOptions::options_button
+            | NoteOptions::note_previews
+            | NoteOptions::actionbar
+            | NoteOptions::show_deleted_placeholder
+            | NoteOptions::content_warning_layout
+            | NoteOptions::image_grid
     }
 }
 
@@ -52,6 +272,220 @@ impl NoteOptions {
     create_bit_methods!(set_wide, has_wide, wide);
     create_bit_methods!(set_options_button, has_options_button, options_button);
     create_bit_methods!(set_hide_media, has_hide_media, hide_media);
+    create_bit_methods!(set_touch_mode, has_touch_mode, touch_mode);
+    create_bit_methods!(set_reduce_autoplay, has_reduce_autoplay, reduce_autoplay);
+    create_bit_methods!(set_keep_at_top, has_keep_at_top, keep_at_top);
+    create_bit_methods!(set_show_geo_map, has_show_geo_map, show_geo_map);
+    create_bit_methods!(set_gif_autoplay, has_gif_autoplay, gif_autoplay);
+    create_bit_methods!(
+        set_content_warning_layout,
+        has_content_warning_layout,
+        content_warning_layout
+    );
+    create_bit_methods!(
+        set_show_thread_root_preview,
+        has_show_thread_root_preview,
+        show_thread_root_preview
+    );
+    create_bit_methods!(
+        set_show_client_proof_of_work,
+        has_show_client_proof_of_work,
+        show_client_proof_of_work
+    );
+    create_bit_methods!(set_verify_signature, has_verify_signature, verify_signature);
+    create_bit_methods!(set_show_zap_summary, has_show_zap_summary, show_zap_summary);
+    create_bit_methods!(set_show_geo_tag, has_show_geo_tag, show_geo_tag);
+    create_bit_methods!(
+        set_expand_shortened_urls,
+        has_expand_shortened_urls,
+        expand_shortened_urls
+    );
+    create_bit_methods!(
+        set_show_event_kind_badge,
+        has_show_event_kind_badge,
+        show_event_kind_badge
+    );
+    create_bit_methods!(
+        set_compact_action_bar,
+        has_compact_action_bar,
+        compact_action_bar
+    );
+    create_bit_methods!(
+        set_hide_urls_in_content,
+        has_hide_urls_in_content,
+        hide_urls_in_content
+    );
+    create_bit_methods!(
+        set_show_thread_depth,
+        has_show_thread_depth,
+        show_thread_depth
+    );
+    create_bit_methods!(
+        set_show_deleted_placeholder,
+        has_show_deleted_placeholder,
+        show_deleted_placeholder
+    );
+    create_bit_methods!(
+        set_show_muted_placeholder,
+        has_show_muted_placeholder,
+        show_muted_placeholder
+    );
+
+    /// How note content text wraps. Defaults to [`egui::TextWrapMode::Wrap`]
+    pub fn word_wrap_mode(self) -> egui::TextWrapMode {
+        if self.contains(NoteOptions::wrap_truncate) {
+            egui::TextWrapMode::Truncate
+        } else if self.contains(NoteOptions::wrap_extend) {
+            egui::TextWrapMode::Extend
+        } else {
+            egui::TextWrapMode::Wrap
+        }
+    }
+
+    pub fn set_word_wrap_mode(&mut self, mode: egui::TextWrapMode) {
+        self.remove(NoteOptions::wrap_extend | NoteOptions::wrap_truncate);
+        match mode {
+            egui::TextWrapMode::Wrap => {}
+            egui::TextWrapMode::Extend => self.insert(NoteOptions::wrap_extend),
+            egui::TextWrapMode::Truncate => self.insert(NoteOptions::wrap_truncate),
+        }
+    }
+
+    /// Which links in note content are clickable. Defaults to
+    /// [`LinkifyMode::HttpsOnly`]
+    pub fn linkify_mode(self) -> LinkifyMode {
+        if self.contains(NoteOptions::linkify_none) {
+            LinkifyMode::None
+        } else if self.contains(NoteOptions::linkify_all) {
+            LinkifyMode::All
+        } else {
+            LinkifyMode::HttpsOnly
+        }
+    }
+
+    pub fn set_linkify_mode(&mut self, mode: LinkifyMode) {
+        self.remove(NoteOptions::linkify_all | NoteOptions::linkify_none);
+        match mode {
+            LinkifyMode::HttpsOnly => {}
+            LinkifyMode::All => self.insert(NoteOptions::linkify_all),
+            LinkifyMode::None => self.insert(NoteOptions::linkify_none),
+        }
+    }
+
+    /// Whether gifs play without a tap. Reduced autoplay wins over gif
+    /// autoplay.
+    pub fn gifs_autoplay(self) -> bool {
+        self.has_gif_autoplay() && !self.has_reduce_autoplay()
+    }
+
+    /// Whether videos load without a tap on `network`. Reduced autoplay
+    /// wins over [`NoteOptions::video_autoplay`].
+    pub fn videos_autoplay(self, network: &dyn NetworkStatus) -> bool {
+        !self.has_reduce_autoplay() && self.video_autoplay().autoplays(network)
+    }
+
+    /// When videos autoplay. Defaults to [`VideoAutoplay::OnWifi`]
+    pub fn video_autoplay(self) -> VideoAutoplay {
+        if self.contains(NoteOptions::video_autoplay_always) {
+            VideoAutoplay::Always
+        } else if self.contains(NoteOptions::video_autoplay_never) {
+            VideoAutoplay::Never
+        } else {
+            VideoAutoplay::OnWifi
+        }
+    }
+
+    pub fn set_video_autoplay(&mut self, autoplay: VideoAutoplay) {
+        self.remove(NoteOptions::video_autoplay_always | NoteOptions::video_autoplay_never);
+        match autoplay {
+            VideoAutoplay::OnWifi => {}
+            VideoAutoplay::Always => self.insert(NoteOptions::video_autoplay_always),
+            VideoAutoplay::Never => self.insert(NoteOptions::video_autoplay_never),
+        }
+    }
+
+    /// How authors are named. Defaults to [`DisplayNameFormat::DisplayName`]
+    pub fn display_name_format(self) -> DisplayNameFormat {
+        if self.contains(NoteOptions::name_both) {
+            DisplayNameFormat::Both
+        } else if self.contains(NoteOptions::name_pubkey) {
+            DisplayNameFormat::Pubkey
+        } else if self.contains(NoteOptions::name_username) {
+            DisplayNameFormat::Username
+        } else {
+            DisplayNameFormat::DisplayName
+        }
+    }
+
+    pub fn set_display_name_format(&mut self, format: DisplayNameFormat) {
+        self.remove(NoteOptions::name_username | NoteOptions::name_pubkey | NoteOptions::name_both);
+        match format {
+            DisplayNameFormat::DisplayName => {}
+            DisplayNameFormat::Username => self.insert(NoteOptions::name_username),
+            DisplayNameFormat::Pubkey => self.insert(NoteOptions::name_pubkey),
+            DisplayNameFormat::Both => self.insert(NoteOptions::name_both),
+        }
+    }
+
+    /// How tightly notes are packed. Defaults to [`NoteDensity::Comfortable`]
+    pub fn density(self) -> NoteDensity {
+        if self.contains(NoteOptions::compact) {
+            NoteDensity::Compact
+        } else {
+            NoteDensity::Comfortable
+        }
+    }
+
+    pub fn set_density(&mut self, density: NoteDensity) {
+        self.set(NoteOptions::compact, density == NoteDensity::Compact);
+    }
+
+    /// How multiple images are laid out. Notes with a single image always
+    /// use [`ImageLayout::Stack`]
+    pub fn image_layout(self) -> ImageLayout {
+        if self.contains(NoteOptions::image_grid) {
+            ImageLayout::Grid
+        } else {
+            ImageLayout::Stack
+        }
+    }
+
+    pub fn set_image_layout(&mut self, layout: ImageLayout) {
+        self.set(NoteOptions::image_grid, layout == ImageLayout::Grid);
+    }
+
+    /// Padding around each note in a timeline
+    pub fn note_padding(self) -> f32 {
+        match self.density() {
+            NoteDensity::Comfortable => 8.0,
+            NoteDensity::Compact => 4.0,
+        }
+    }
+
+    /// Horizontal space between the parts of a note, eg. its avatar and
+    /// its content
+    pub fn note_spacing(self) -> f32 {
+        match self.density() {
+            NoteDensity::Comfortable => 4.0,
+            NoteDensity::Compact => 2.0,
+        }
+    }
+
+    /// How thick the line between notes in a timeline is
+    pub fn separator_width(self) -> f32 {
+        match self.density() {
+            NoteDensity::Comfortable => 1.0,
+            NoteDensity::Compact => 0.5,
+        }
+    }
+
+    /// How tall the images in a note are
+    pub fn media_height(self) -> f32 {
+        match self.density() {
+            NoteDensity::Comfortable => 360.0,
+            NoteDensity::Compact => 200.0,
+        }
+    }
 
     pub fn new(is_universe_timeline: bool) -> Self {
         let mut options = NoteOptions::default();
@@ -60,7 +494,7 @@ impl NoteOptions {
     }
 
     pub fn pfp_size(&self) -> f32 {
-        if self.has_small_pfp() {
+        if self.has_small_pfp() || self.density() == NoteDensity::Compact {
             ProfilePic::small_size()
         } else if self.has_medium_pfp() {
             ProfilePic::medium_size()
@@ -69,3 +503,102 @@ impl NoteOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_video_autoplay() {
+        struct Cellular;
+        impl NetworkStatus for Cellular {
+            fn is_wifi(&self) -> bool {
+                false
+            }
+        }
+
+        let mut options = NoteOptions::default();
+        assert_eq!(options.video_autoplay(), VideoAutoplay::OnWifi);
+        assert!(options.video_autoplay().autoplays(&DesktopNetwork));
+        assert!(!options.video_autoplay().autoplays(&Cellular));
+
+        options.set_video_autoplay(VideoAutoplay::Never);
+        assert_eq!(options.video_autoplay(), VideoAutoplay::Never);
+        assert!(!options.video_autoplay().autoplays(&DesktopNetwork));
+
+        options.set_video_autoplay(VideoAutoplay::Always);
+        assert_eq!(options.video_autoplay(), VideoAutoplay::Always);
+        assert!(options.video_autoplay().autoplays(&Cellular));
+    }
+
+    #[test]
+    fn test_reduce_autoplay_wins() {
+        let mut options = NoteOptions::default();
+        options.set_gif_autoplay(true);
+        options.set_video_autoplay(VideoAutoplay::Always);
+        assert!(options.gifs_autoplay());
+        assert!(options.videos_autoplay(&DesktopNetwork));
+
+        options.set_reduce_autoplay(true);
+        assert!(!options.gifs_autoplay());
+        assert!(!options.videos_autoplay(&DesktopNetwork));
+    }
+
+    #[test]
+    fn test_linkify_mode() {
+        let mut options = NoteOptions::default();
+        assert_eq!(options.linkify_mode(), LinkifyMode::HttpsOnly);
+        assert!(options.linkify_mode().linkifies("HTTPS://damus.io"));
+        assert!(!options.linkify_mode().linkifies("http://damus.io"));
+
+        options.set_linkify_mode(LinkifyMode::None);
+        assert!(!options.linkify_mode().linkifies("https://damus.io"));
+
+        options.set_linkify_mode(LinkifyMode::All);
+        assert!(options.linkify_mode().linkifies("http://damus.io"));
+        assert!(!options.contains(NoteOptions::linkify_none));
+    }
+
+    #[test]
+    fn test_note_density() {
+        let mut options = NoteOptions::default();
+        assert_eq!(options.density(), NoteDensity::Comfortable);
+        assert_eq!(options.note_padding(), 8.0);
+
+        options.set_density(NoteDensity::Compact);
+        assert_eq!(options.density(), NoteDensity::Compact);
+        assert!(options.note_padding() < 8.0);
+        assert!(options.media_height() < 360.0);
+        assert_eq!(options.pfp_size(), ProfilePic::small_size());
+    }
+
+    #[test]
+    fn test_display_name_format() {
+        let pk = [0; 32];
+        let mut options = NoteOptions::default();
+        let format = options.display_name_format();
+        assert_eq!(format, DisplayNameFormat::DisplayName);
+        assert_eq!(
+            format.format(Some(""), Some("jb55"), &pk).as_deref(),
+            Some("jb55")
+        );
+
+        options.set_display_name_format(DisplayNameFormat::Both);
+        let format = options.display_name_format();
+        assert_eq!(format, DisplayNameFormat::Both);
+        assert_eq!(
+            format.format(Some("Will"), Some("jb55"), &pk).as_deref(),
+            Some("Will (@jb55)")
+        );
+        assert_eq!(format.format(None, None, &pk), None);
+
+        options.set_display_name_format(DisplayNameFormat::Pubkey);
+        let npub = options
+            .display_name_format()
+            .format(None, None, &pk)
+            .unwrap();
+        assert!(npub.starts_with("npub1"));
+        assert!(npub.ends_with('…'));
+        assert!(!options.contains(NoteOptions::name_both));
+    }
+}
@@ -1,13 +1,15 @@
+use crate::actionbar::NoteAction;
 use crate::draft::{Draft, Drafts};
-use crate::post::NewPost;
+use crate::post::{NewPost, PublishStatus, RelayWriteStatus};
+use crate::scheduler::SCHEDULE_DELAYS;
 use crate::ui::{self, Preview, PreviewConfig};
 use crate::Result;
 use egui::widgets::text_edit::TextEdit;
-use egui::{Frame, Layout};
+use egui::{Frame, Layout, RichText};
 use enostr::{FilledKeypair, FullKeypair, NoteId, RelayPool};
 use nostrdb::{Ndb, Transaction};
 
-use notedeck::{ImageCache, NoteCache};
+use notedeck::{unix_now, ImageCache, NoteCache};
 
 use super::contents::render_note_preview;
 
@@ -61,8 +63,12 @@ impl PostAction {
             }
         };
 
+        let note_id = *note.id();
         pool.send(&enostr::ClientMessage::event(note)?);
-        drafts.get_from_post_type(&self.post_type).clear();
+
+        let draft = drafts.get_from_post_type(&self.post_type);
+        draft.clear();
+        draft.publish_status = Some(PublishStatus::new(&note_id, pool.urls()));
 
         Ok(())
     }
@@ -70,6 +76,8 @@ impl PostAction {
 
 pub struct PostResponse {
     pub action: Option<PostAction>,
+    /// A [`NoteAction::ScheduleNote`] when the user chose to post later
+    pub schedule: Option<NoteAction>,
     pub edit_response: egui::Response,
 }
 
@@ -180,6 +188,7 @@ impl<'a> PostView<'a> {
             .show(ui, |ui| {
                 ui.vertical(|ui| {
                     let edit_response = ui.horizontal(|ui| self.editbox(txn, ui)).inner;
+                    let mut schedule: Option<NoteAction> = None;
 
                     let action = ui
                         .horizontal(|ui| {
@@ -197,6 +206,7 @@ impl<'a> PostView<'a> {
                                                 txn,
                                                 id.bytes(),
                                                 nostrdb::NoteKey::new(0),
+                                                None,
                                             );
                                         });
                                     });
@@ -215,17 +225,38 @@ impl<'a> PostView<'a> {
                                         self.draft.buffer.clone(),
                                         self.poster.to_full(),
                                     );
-                                    Some(PostAction::new(self.post_type.clone(), new_post))
-                                } else {
-                                    None
+                                    return Some(PostAction::new(self.post_type.clone(), new_post));
+                                }
+
+                                let can_schedule = matches!(self.post_type, PostType::New)
+                                    && !self.draft.buffer.is_empty();
+                                if can_schedule {
+                                    ui.menu_button("Schedule", |ui| {
+                                        for (label, secs) in SCHEDULE_DELAYS {
+                                            if ui.button(label).clicked() {
+                                                schedule = Some(NoteAction::ScheduleNote {
+                                                    content: self.draft.buffer.clone(),
+                                                    publish_at: unix_now() + secs,
+                                                });
+                                                ui.close_menu();
+                                            }
+                                        }
+                                    });
                                 }
+
+                                None
                             })
                             .inner
                         })
                         .inner;
 
+                    if let Some(status) = &self.draft.publish_status {
+                        publish_status_ui(ui, status);
+                    }
+
                     PostResponse {
                         action,
+                        schedule,
                         edit_response,
                     }
                 })
@@ -235,6 +266,37 @@ impl<'a> PostView<'a> {
     }
 }
 
+/// A compact list of where our last post landed
+fn publish_status_ui(ui: &mut egui::Ui, status: &PublishStatus) {
+    ui.add_space(8.0);
+
+    for (relay, write_status) in status.relays() {
+        ui.horizontal(|ui| {
+            let (icon, color) = match write_status {
+                RelayWriteStatus::Pending => ("…", ui.visuals().weak_text_color()),
+                RelayWriteStatus::Accepted => ("✔", ui.visuals().selection.bg_fill),
+                RelayWriteStatus::Rejected(_) => ("✖", ui.visuals().error_fg_color),
+            };
+
+            ui.label(RichText::new(icon).color(color));
+            let resp = ui.label(RichText::new(relay).small().weak());
+
+            if let RelayWriteStatus::Rejected(message) = write_status {
+                if !message.is_empty() {
+                    resp.on_hover_text(message);
+                }
+            }
+        });
+    }
+
+    if status.timed_out() {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            "No relays have accepted your note yet",
+        );
+    }
+}
+
 fn post_button(interactive: bool) -> impl egui::Widget {
     move |ui: &mut egui::Ui| {
         let button = egui::Button::new("Post now");
@@ -15,7 +15,11 @@ pub use picture::ProfilePic;
 pub use preview::ProfilePreview;
 use tracing::error;
 
-use crate::{actionbar::NoteAction, notes_holder::NotesHolderStorage, profile::Profile};
+use crate::{
+    actionbar::NoteAction,
+    notes_holder::NotesHolderStorage,
+    profile::{FollowCounts, Profile},
+};
 
 use super::timeline::{tabs_ui, TimelineTabView};
 use notedeck::{Accounts, ImageCache, MuteFun, NoteCache, NotedeckTextStyle};
@@ -71,8 +75,14 @@ impl<'a> ProfileView<'a> {
             .show(ui, |ui| {
                 let mut action = None;
                 let txn = Transaction::new(self.ndb).expect("txn");
+                let follow_counts = self
+                    .profiles
+                    .notes_holder_mutated(self.ndb, self.note_cache, &txn, self.pubkey.bytes())
+                    .get_ptr()
+                    .follow_counts(self.ndb, &txn, self.pubkey.bytes());
+
                 if let Ok(profile) = self.ndb.get_profile_by_pubkey(&txn, self.pubkey.bytes()) {
-                    if self.profile_body(ui, profile) {
+                    if self.profile_body(ui, profile, follow_counts) {
                         action = Some(ProfileViewAction::EditProfile);
                     }
                 }
@@ -81,8 +91,12 @@ impl<'a> ProfileView<'a> {
                     .notes_holder_mutated(self.ndb, self.note_cache, &txn, self.pubkey.bytes())
                     .get_ptr();
 
-                profile.timeline.selected_view =
-                    tabs_ui(ui, profile.timeline.selected_view, &profile.timeline.views);
+                tabs_ui(
+                    ui,
+                    &mut profile.timeline,
+                    self.note_options.has_touch_mode(),
+                    false,
+                );
 
                 // poll for new notes and insert them into our existing notes
                 if let Err(e) = profile.poll_notes_into_view(&txn, self.ndb) {
@@ -102,6 +116,7 @@ impl<'a> ProfileView<'a> {
                     self.is_muted,
                 )
                 .show(ui)
+                .first_action()
                 {
                     action = Some(ProfileViewAction::Note(note_action));
                 }
@@ -111,7 +126,12 @@ impl<'a> ProfileView<'a> {
             .inner
     }
 
-    fn profile_body(&mut self, ui: &mut egui::Ui, profile: ProfileRecord<'_>) -> bool {
+    fn profile_body(
+        &mut self,
+        ui: &mut egui::Ui,
+        profile: ProfileRecord<'_>,
+        follow_counts: FollowCounts,
+    ) -> bool {
         let mut action = false;
         ui.vertical(|ui| {
             banner(
@@ -162,6 +182,8 @@ impl<'a> ProfileView<'a> {
 
                 ui.add(about_section_widget(&profile));
 
+                follow_counts_ui(ui, follow_counts);
+
                 ui.horizontal_wrapped(|ui| {
                     if let Some(website_url) = profile
                         .record()
@@ -380,6 +402,31 @@ pub fn unwrap_profile_url(maybe_url: Option<&str>) -> &str {
     }
 }
 
+fn follow_counts_ui(ui: &mut egui::Ui, counts: FollowCounts) {
+    ui.horizontal(|ui| {
+        if let Some(following) = counts.following {
+            ui.add(
+                Label::new(RichText::new(format!("{following} following")).strong())
+                    .selectable(false),
+            );
+        }
+
+        ui.add(
+            Label::new(
+                RichText::new(format!(
+                    "~{} followers (estimate)",
+                    counts.followers_estimate
+                ))
+                .weak(),
+            )
+            .selectable(false),
+        )
+        .on_hover_text("Counted from the contact lists we've seen, the real number may be higher");
+    });
+
+    ui.add_space(8.0);
+}
+
 fn about_section_widget<'a, 'b>(profile: &'b ProfileRecord<'a>) -> impl egui::Widget + 'b
 where
     'b: 'a,
@@ -80,6 +80,7 @@ fn render_pfp(
         img_cache.map_mut().insert(url.to_owned(), res);
     }
 
+    img_cache.touch(url);
     match img_cache.map()[url].ready() {
         None => paint_circle(ui, ui_size),
 
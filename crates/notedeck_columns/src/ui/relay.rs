@@ -104,6 +104,69 @@ impl<'a> RelayView<'a> {
     }
 }
 
+/// Ask the user to confirm adding a relay to their relay pool. Returns
+/// `Some(true)` when confirmed and `Some(false)` when cancelled.
+pub fn add_relay_prompt(ctx: &egui::Context, relay_url: &str) -> Option<bool> {
+    let mut result = None;
+
+    egui::Window::new("Add relay")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label("Add this relay to your relay pool?");
+            ui.label(
+                RichText::new(relay_url).text_style(NotedeckTextStyle::Monospace.text_style()),
+            );
+
+            ui.add_space(8.0);
+
+            ui.horizontal(|ui| {
+                if ui.button("Add relay").clicked() {
+                    result = Some(true);
+                }
+
+                if ui.button("Cancel").clicked() {
+                    result = Some(false);
+                }
+            });
+        });
+
+    result
+}
+
+/// A banner across the top of the deck explaining that we have no relays
+/// to load notes from. Returns true when the user wants to manage their
+/// relays.
+pub fn no_relays_banner(ui: &mut egui::Ui, num_relays: usize) -> bool {
+    let text = if num_relays == 0 {
+        "You don't have any relays, so there's nothing to load notes from."
+    } else {
+        "You aren't connected to any relays, so your columns won't update."
+    };
+
+    let mut clicked = false;
+
+    egui::TopBottomPanel::top("no_relays_banner")
+        .frame(
+            Frame::none()
+                .fill(ui.visuals().warn_fg_color.gamma_multiply(0.2))
+                .inner_margin(Margin::symmetric(16.0, 8.0)),
+        )
+        .show_inside(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("⚠").color(ui.visuals().warn_fg_color));
+                ui.label(text);
+
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    clicked = ui.button("Add relays").clicked();
+                });
+            });
+        });
+
+    clicked
+}
+
 fn get_right_side_width(status: RelayStatus) -> f32 {
     match status {
         RelayStatus::Connected => 150.0,
@@ -0,0 +1,80 @@
+use egui::{Label, RichText};
+
+use crate::scheduler::Scheduler;
+
+use super::padding;
+
+/// The notes waiting in the [`Scheduler`], soonest first
+pub struct ScheduledView<'a> {
+    scheduler: &'a Scheduler,
+    now: u64,
+}
+
+impl<'a> ScheduledView<'a> {
+    pub fn new(scheduler: &'a Scheduler, now: u64) -> Self {
+        Self { scheduler, now }
+    }
+
+    /// Returns the index of the note the user chose to cancel, if any
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<usize> {
+        let mut cancel: Option<usize> = None;
+
+        if self.scheduler.notes().is_empty() {
+            padding(16.0, ui, |ui| {
+                ui.add(
+                    Label::new(RichText::new("No scheduled notes").weak().italics())
+                        .selectable(false),
+                );
+            });
+            return None;
+        }
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (ind, note) in self.scheduler.notes().iter().enumerate() {
+                    padding(8.0, ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(publish_in(note.publish_at, self.now))
+                                    .small()
+                                    .weak(),
+                            );
+
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    if ui.small_button("Cancel").clicked() {
+                                        cancel = Some(ind);
+                                    }
+                                },
+                            );
+                        });
+
+                        ui.add(Label::new(&note.content).wrap());
+                    });
+
+                    super::hline(ui);
+                }
+            });
+
+        cancel
+    }
+}
+
+fn publish_in(publish_at: u64, now: u64) -> String {
+    let secs = publish_at.saturating_sub(now);
+    if secs < 60 {
+        "Publishing soon".to_string()
+    } else if secs < 60 * 60 {
+        format!("In {}m", secs / 60)
+    } else if secs < 24 * 60 * 60 {
+        format!("In {}h {}m", secs / (60 * 60), secs % (60 * 60) / 60)
+    } else {
+        format!(
+            "In {}d {}h",
+            secs / (24 * 60 * 60),
+            secs % (24 * 60 * 60) / (60 * 60)
+        )
+    }
+}
@@ -2,11 +2,11 @@ use crate::{
     actionbar::NoteAction,
     notes_holder::{NotesHolder, NotesHolderStorage},
     thread::Thread,
-    ui::note::NoteOptions,
+    ui::{self, note::NoteOptions},
 };
 
 use nostrdb::{Ndb, Transaction};
-use notedeck::{ImageCache, MuteFun, NoteCache, UnknownIds};
+use notedeck::{note::note_engagement, ImageCache, MuteFun, NoteCache, NoteRef, UnknownIds};
 use tracing::error;
 
 use super::timeline::TimelineTabView;
@@ -18,9 +18,10 @@ pub struct ThreadView<'a> {
     unknown_ids: &'a mut UnknownIds,
     img_cache: &'a mut ImageCache,
     selected_note_id: &'a [u8; 32],
-    textmode: bool,
+    note_options: NoteOptions,
     id_source: egui::Id,
     is_muted: &'a MuteFun,
+    collapse_threshold: Option<u32>,
 }
 
 impl<'a> ThreadView<'a> {
@@ -32,10 +33,15 @@ impl<'a> ThreadView<'a> {
         unknown_ids: &'a mut UnknownIds,
         img_cache: &'a mut ImageCache,
         selected_note_id: &'a [u8; 32],
-        textmode: bool,
+        note_options: NoteOptions,
         is_muted: &'a MuteFun,
     ) -> Self {
         let id_source = egui::Id::new("threadscroll_threadview");
+
+        // the root is already at the top of the thread
+        let mut note_options = note_options;
+        note_options.set_show_thread_root_preview(false);
+
         ThreadView {
             threads,
             ndb,
@@ -43,9 +49,10 @@ impl<'a> ThreadView<'a> {
             unknown_ids,
             img_cache,
             selected_note_id,
-            textmode,
+            note_options,
             id_source,
             is_muted,
+            collapse_threshold: None,
         }
     }
 
@@ -54,6 +61,13 @@ impl<'a> ThreadView<'a> {
         self
     }
 
+    /// Collapse replies with `threshold` or fewer reactions and zaps
+    /// behind a toggle, so the high-signal replies stand out
+    pub fn collapse_replies(mut self, threshold: Option<u32>) -> Self {
+        self.collapse_threshold = threshold;
+        self
+    }
+
     pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<NoteAction> {
         let txn = Transaction::new(self.ndb).expect("txn");
 
@@ -109,23 +123,106 @@ impl<'a> ThreadView<'a> {
                     Err(err) => error!("{err}"),
                 };
 
-                // This is threadview. We are not the universe view...
-                let is_universe = false;
-                let mut note_options = NoteOptions::new(is_universe);
-                note_options.set_textmode(self.textmode);
+                let show_collapsed_id = self.id_source.with("show_collapsed_replies");
+                let show_collapsed = ui
+                    .data(|d| d.get_temp::<bool>(show_collapsed_id))
+                    .unwrap_or(false);
+
+                let (visible, collapsed) = if let Some(threshold) = self.collapse_threshold {
+                    split_low_engagement(
+                        self.ndb,
+                        self.note_cache,
+                        &txn,
+                        &thread.view().notes,
+                        &[root_id, self.selected_note_id],
+                        threshold,
+                    )
+                } else {
+                    (vec![], 0)
+                };
 
-                TimelineTabView::new(
+                let mut view = TimelineTabView::new(
                     thread.view(),
                     true,
-                    note_options,
+                    self.note_options,
                     &txn,
                     self.ndb,
                     self.note_cache,
                     self.img_cache,
                     self.is_muted,
-                )
-                .show(ui)
+                );
+
+                if collapsed > 0 && !show_collapsed {
+                    view = view.notes(&visible);
+                }
+
+                let action = view.show(ui).first_action();
+
+                if collapsed > 0 {
+                    let label = if show_collapsed {
+                        "Hide low-engagement replies".to_string()
+                    } else if collapsed == 1 {
+                        "Show 1 low-engagement reply".to_string()
+                    } else {
+                        format!("Show {} low-engagement replies", collapsed)
+                    };
+
+                    ui::padding(8.0, ui, |ui| {
+                        if ui.button(label).clicked() {
+                            ui.data_mut(|d| d.insert_temp(show_collapsed_id, !show_collapsed));
+                            thread.view().list.borrow_mut().reset();
+                        }
+                    });
+                }
+
+                action
             })
             .inner
     }
 }
+
+/// Split thread notes into the ones worth showing and a count of the
+/// replies with `threshold` or fewer reactions and zaps. Notes in
+/// `always_show` are never collapsed.
+fn split_low_engagement(
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    txn: &Transaction,
+    notes: &[NoteRef],
+    always_show: &[&[u8; 32]],
+    threshold: u32,
+) -> (Vec<NoteRef>, usize) {
+    let mut visible = Vec::with_capacity(notes.len());
+    let mut collapsed = 0;
+
+    for note_ref in notes {
+        let note = if let Ok(note) = ndb.get_note_by_key(txn, note_ref.key) {
+            note
+        } else {
+            visible.push(*note_ref);
+            continue;
+        };
+
+        if always_show.contains(&note.id()) {
+            visible.push(*note_ref);
+            continue;
+        }
+
+        // we only need to know if we're over the threshold
+        let engagement = note_engagement(
+            ndb,
+            note_cache,
+            txn,
+            note_ref.key,
+            &note,
+            threshold.saturating_add(1),
+        );
+        if engagement > threshold {
+            visible.push(*note_ref);
+        } else {
+            collapsed += 1;
+        }
+    }
+
+    (visible, collapsed)
+}
@@ -1,18 +1,115 @@
 use crate::actionbar::NoteAction;
-use crate::timeline::TimelineTab;
+use crate::timeline::{RecentNotes, Timeline, TimelineTab};
 use crate::{
+    colors,
     column::Columns,
-    timeline::{TimelineId, ViewFilter},
+    help::{HelpItem, HelpTrigger},
+    timeline::{ColumnAnalytics, ReadMarkers, TimelineId, TimelineSearch, ViewFilter},
     ui,
-    ui::note::NoteOptions,
+    ui::column::{ColumnDivider, COLUMN_SNAP_GRID},
+    ui::note::{
+        content_warning::{content_rating, ContentRating},
+        emoji::author_emoji_map,
+        NoteContextSelection, NoteDensity, NoteOptions,
+    },
 };
 use egui::containers::scroll_area::ScrollBarVisibility;
 use egui::{Direction, Layout};
 use egui_tabs::TabColor;
-use nostrdb::{Ndb, Transaction};
-use notedeck::note::root_note_id_from_selected_id;
-use notedeck::{ImageCache, MuteFun, NoteCache};
-use tracing::{error, warn};
+use egui_virtual_list::VirtualList;
+use enostr::NoteId;
+use nostrdb::{Ndb, Note, NoteKey, Transaction};
+use notedeck::note::{
+    is_note_deleted, pow_difficulty, root_note_id_from_selected_id, thread_depth,
+};
+use notedeck::{date_label, unix_now, ImageCache, MuteFun, NoteCache, NoteRef, NotedeckTextStyle};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+/// Shown in place of the notes when a timeline view has none, see
+/// [`TimelineView::with_custom_empty_state_widget`]
+type EmptyState<'a> = Box<dyn FnOnce(&mut egui::Ui) -> egui::Response + 'a>;
+
+/// Which way a [`TimelineView`] scrolls through its notes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// Notes stacked top to bottom
+    #[default]
+    Vertical,
+    /// Notes side by side as full height cards, scrolling left to right
+    Horizontal,
+}
+
+/// How wide each note is in a [`Orientation::Horizontal`] timeline
+const HORIZONTAL_CARD_WIDTH: f32 = 300.0;
+
+/// How often a [`Orientation::Horizontal`] timeline checks its notes
+/// against mutes again when the tab hasn't changed, in seconds
+const HORIZONTAL_CARDS_RECHECK: f64 = 1.0;
+
+/// The notes a [`Orientation::Horizontal`] timeline shows as cards, and
+/// whether each carries a muted marker, see
+/// [`TimelineTabView::show_horizontal`]
+#[derive(Debug, Clone, Default)]
+struct HorizontalCards {
+    /// The tab revision, since, reversed and note count they were found
+    /// for
+    key: Option<(u64, Option<u64>, bool, usize)>,
+    found_at: f64,
+    cards: Arc<Vec<(NoteKey, bool)>>,
+}
+
+/// Every note action a timeline produced in a frame, in the order they
+/// happened
+#[derive(Debug, Default)]
+pub struct TimelineResponse {
+    pub actions: Vec<NoteAction>,
+}
+
+impl TimelineResponse {
+    /// The first action, for callers that only handle one per frame.
+    /// `Seen` actions are skipped so they never shadow a click
+    pub fn first_action(self) -> Option<NoteAction> {
+        self.actions
+            .into_iter()
+            .find(|action| !matches!(action, NoteAction::Seen(_)))
+    }
+
+    fn merge(&mut self, other: TimelineResponse) {
+        self.actions.extend(other.actions);
+    }
+}
+
+/// What [`TimelineView::measure_content_height`] measured for a view
+#[derive(Debug, Clone, Default)]
+struct ContentHeight {
+    /// The notes and width the height was measured for
+    key: Option<egui::Id>,
+    height: f32,
+    /// Heights of the notes that were laid out invisibly because they
+    /// were never rendered
+    laid_out: HashMap<NoteKey, f32>,
+}
+
+/// `is_muted`, also hiding notes with fewer than `min_pow` leading zero
+/// bits of proof of work or rated above `max_rating`
+fn hidden_by(
+    is_muted: &MuteFun,
+    min_pow: u8,
+    max_rating: ContentRating,
+) -> impl Fn(&Note, &[u8; 32]) -> bool + '_ {
+    let min_pow = u32::from(min_pow);
+    move |note: &Note, thread: &[u8; 32]| {
+        is_muted(note, thread)
+            || pow_difficulty(note.id()) < min_pow
+            || content_rating(note) > max_rating
+    }
+}
 
 pub struct TimelineView<'a> {
     timeline_id: TimelineId,
@@ -23,6 +120,28 @@ pub struct TimelineView<'a> {
     note_options: NoteOptions,
     reverse: bool,
     is_muted: &'a MuteFun,
+    mutes: Option<&'a MuteFun>,
+    column_type_icon: Option<&'static str>,
+    debug_overlay: bool,
+    header_collapse_on_scroll: bool,
+    tab_drag_to_reorder: bool,
+    auto_scroll_velocity: f32,
+    loading_progress: Option<f32>,
+    focus_key: Option<egui::Key>,
+    min_pow: u8,
+    max_rating: ContentRating,
+    watermark: Option<&'a str>,
+    empty_state: Option<EmptyState<'a>>,
+    column_divider: Option<ColumnDivider>,
+    column_snap: Option<bool>,
+    column_snap_grid: f32,
+    archive_after: Option<Duration>,
+    orientation: Orientation,
+    column_analytics: Option<bool>,
+    read_marker_sync: Option<bool>,
+    search_box: Option<bool>,
+    reply_depth_limit: Option<u8>,
+    help: Vec<HelpItem>,
 }
 
 impl<'a> TimelineView<'a> {
@@ -45,41 +164,438 @@ impl<'a> TimelineView<'a> {
             reverse,
             note_options,
             is_muted,
+            mutes: None,
+            column_type_icon: None,
+            debug_overlay: false,
+            header_collapse_on_scroll: false,
+            tab_drag_to_reorder: false,
+            auto_scroll_velocity: 0.0,
+            loading_progress: None,
+            focus_key: None,
+            min_pow: 0,
+            max_rating: ContentRating::default(),
+            watermark: None,
+            empty_state: None,
+            column_divider: None,
+            column_snap: None,
+            column_snap_grid: COLUMN_SNAP_GRID,
+            archive_after: None,
+            orientation: Orientation::default(),
+            column_analytics: None,
+            read_marker_sync: None,
+            search_box: None,
+            reply_depth_limit: None,
+            help: vec![],
         }
     }
 
-    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<NoteAction> {
-        timeline_ui(
-            ui,
-            self.ndb,
-            self.timeline_id,
-            self.columns,
-            self.note_cache,
-            self.img_cache,
-            self.reverse,
-            self.note_options,
-            self.is_muted,
-        )
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> TimelineResponse {
+        let is_hidden = hidden_by(self.is_muted, self.min_pow, self.max_rating);
+
+        if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+            timeline.count_muted = self.debug_overlay;
+        }
+
+        if let Some(icon) = self.column_type_icon {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                timeline.icon = Some(icon);
+            }
+        }
+
+        if let Some(divider) = self.column_divider {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                timeline.divider = Some(divider);
+            }
+        }
+
+        if let Some(enabled) = self.column_snap {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                timeline.column_snap = enabled.then_some(self.column_snap_grid);
+            }
+        }
+
+        if let Some(after) = self.archive_after {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                timeline.archive_after = Some(after);
+            }
+        }
+
+        if let Some(enabled) = self.column_analytics {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                if !enabled {
+                    timeline.analytics = None;
+                } else if timeline.analytics.is_none() {
+                    timeline.analytics = Some(ColumnAnalytics::new(unix_now()));
+                }
+            }
+        }
+
+        if let Some(enabled) = self.read_marker_sync {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                if !enabled {
+                    timeline.read_markers = None;
+                } else if timeline.read_markers.is_none() {
+                    timeline.read_markers = Some(ReadMarkers::default());
+                }
+            }
+        }
+
+        if let Some(enabled) = self.search_box {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                if !enabled {
+                    timeline.search = None;
+                } else if timeline.search.is_none() {
+                    timeline.search = Some(TimelineSearch::default());
+                }
+            }
+        }
+
+        if let Some(key) = self.focus_key {
+            if let Some(timeline) = self.columns.find_timeline_mut(self.timeline_id) {
+                timeline.focus_key = Some(key);
+            }
+        }
+
+        timeline_ui(ui, self, &is_hidden)
+    }
+
+    /// Estimate the total scroll height of the current view, so the
+    /// scroll thumb is sized for all of its notes rather than the ones
+    /// the virtual list has seen. Notes that were rendered use their last
+    /// rendered height. The rest are laid out once in an invisible ui with
+    /// their media hidden, so nothing is fetched for them. Hidden, deleted
+    /// and collapsed notes aren't counted, so this errs on the short side.
+    ///
+    /// The result is kept in egui memory until the view's notes or width
+    /// change, wherever notes were added or removed, and only notes that
+    /// were never measured are laid out again.
+    pub fn measure_content_height(&mut self, ui: &mut egui::Ui, txn: &Transaction) -> f32 {
+        let timeline = if let Some(timeline) = self.columns.find_timeline(self.timeline_id) {
+            timeline
+        } else {
+            return 0.0;
+        };
+
+        let notes = timeline.current_view().display_notes(timeline.since());
+        let apply_mutes = timeline.apply_mutes;
+        let view_id = timeline.view_id();
+        let width = ui.available_width() - ui.spacing().scroll.allocated_width();
+        let id = egui::Id::new(("content_height", view_id));
+        let key = notes.iter().fold(
+            egui::Id::new((width.round() as i32, self.note_options)),
+            |key, note_ref| key.with(note_ref.key),
+        );
+
+        if let Some(height) = ui.data_mut(|d| {
+            let measured = d.get_temp_mut_or_default::<ContentHeight>(id);
+            (measured.key == Some(key)).then_some(measured.height)
+        }) {
+            return height;
+        }
+
+        let mut laid_out = ui.data_mut(|d| {
+            std::mem::take(&mut d.get_temp_mut_or_default::<ContentHeight>(id).laid_out)
+        });
+
+        // a salt of its own keeps the invisible notes' ids away from the
+        // real rows'
+        let mut measure_ui = ui.new_child(
+            egui::UiBuilder::new()
+                .id_salt(("measure_content_height", view_id))
+                .max_rect(ui.max_rect().with_max_x(ui.max_rect().left() + width))
+                .invisible(),
+        );
+        measure_ui.spacing_mut().item_spacing.y = 0.0;
+        measure_ui.spacing_mut().item_spacing.x = self.note_options.note_spacing();
+
+        let mut note_options = self.note_options;
+        note_options.set_hide_media(true);
+
+        let is_hidden = hidden_by(self.is_muted, self.min_pow, self.max_rating);
+        let mut height = 0.0;
+        let mut still_laid_out = HashMap::new();
+        for note_ref in notes.iter() {
+            let note = if let Ok(note) = self.ndb.get_note_by_key(txn, note_ref.key) {
+                note
+            } else {
+                continue;
+            };
+
+            let thread = *root_note_id_from_selected_id(self.ndb, self.note_cache, txn, note.id());
+            let muted = apply_mutes && self.mutes.map_or(false, |mutes| mutes(&note, &thread));
+            if muted || is_hidden(&note, &thread) {
+                continue;
+            }
+
+            if self.note_options.has_show_deleted_placeholder()
+                && is_note_deleted(self.ndb, self.note_cache, txn, note_ref.key, &note)
+            {
+                continue;
+            }
+
+            if let Some(limit) = self.reply_depth_limit {
+                let depth = thread_depth(self.ndb, self.note_cache, txn, note_ref.key, &note);
+                if depth > u32::from(limit) {
+                    continue;
+                }
+            }
+
+            let cached = self.note_cache.cached_note_or_insert(note_ref.key, &note);
+            if let Some(rendered) = cached.estimated_height {
+                height += rendered;
+                continue;
+            }
+
+            let note_height = if let Some(note_height) = laid_out.remove(&note_ref.key) {
+                note_height
+            } else {
+                let top = measure_ui.cursor().min.y;
+                ui::padding(note_options.note_padding(), &mut measure_ui, |ui| {
+                    ui::NoteView::new(self.ndb, self.note_cache, self.img_cache, &note)
+                        .note_options(note_options)
+                        .show(ui);
+                });
+                ui::hline_with_width(&measure_ui, note_options.separator_width());
+                measure_ui.cursor().min.y - top
+            };
+            still_laid_out.insert(note_ref.key, note_height);
+            height += note_height;
+        }
+
+        ui.data_mut(|d| {
+            d.insert_temp(
+                id,
+                ContentHeight {
+                    key: Some(key),
+                    height,
+                    laid_out: still_laid_out,
+                },
+            )
+        });
+        height
     }
 
     pub fn reversed(mut self) -> Self {
         self.reverse = true;
         self
     }
+
+    /// Record how this column is used: notes scrolled past, notes opened,
+    /// other note actions and how long it's been recording. See
+    /// [`Columns::export_analytics`]
+    pub fn with_column_analytics(mut self, enabled: bool) -> Self {
+        self.column_analytics = Some(enabled);
+        self
+    }
+
+    /// Publish the notes scrolled past as kind 15 read markers, batched
+    /// every few seconds, so other devices know what's been read. Notes
+    /// older than a marker published from another device aren't marked
+    /// again.
+    pub fn with_read_marker_sync(mut self, enabled: bool) -> Self {
+        self.read_marker_sync = Some(enabled);
+        self
+    }
+
+    /// Show a search box above the tabs that filters the notes the column
+    /// already has by their content or author's name, without asking
+    /// relays
+    pub fn with_search_box(mut self, enabled: bool) -> Self {
+        self.search_box = Some(enabled);
+        self
+    }
+
+    /// Show the first of `items` whose trigger fires as a tip in the
+    /// column. Dismissing it emits [`NoteAction::DismissHelp`].
+    pub fn with_contextual_help(mut self, items: Vec<HelpItem>) -> Self {
+        self.help = items;
+        self
+    }
+
+    /// Scroll through notes top to bottom or left to right. Horizontal
+    /// timelines show their tabs as a strip down the left edge.
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Hide each note's action bar until the note is hovered, saving a
+    /// row per note. Pairs well with the compact density.
+    pub fn with_compact_action_bar(mut self, enable: bool) -> Self {
+        self.note_options.set_compact_action_bar(enable);
+        self
+    }
+
+    /// Collapse replies more than `limit` replies deep in their thread
+    /// into a summary that opens the thread. 0 turns it off
+    pub fn with_reply_depth_limit(mut self, limit: u8) -> Self {
+        self.reply_depth_limit = (limit > 0).then_some(limit);
+        self
+    }
+
+    /// Show `icon` next to the column title instead of the default icon
+    /// for this column's [`TimelineKind`](crate::timeline::TimelineKind)
+    pub fn with_column_type_icon(mut self, icon: &'static str) -> Self {
+        self.column_type_icon = Some(icon);
+        self
+    }
+
+    /// Show note insertion stats for the current view on top of the
+    /// timeline. Click the overlay to reset them.
+    pub fn with_debug_overlay(mut self, enable: bool) -> Self {
+        self.debug_overlay = enable;
+        self
+    }
+
+    /// Hide the column header while scrolling down through the timeline,
+    /// bringing it back when scrolling up
+    pub fn with_header_collapse_on_scroll(mut self, enable: bool) -> Self {
+        self.header_collapse_on_scroll = enable;
+        self
+    }
+
+    /// Let the column's tabs be reordered by dragging them. Off by
+    /// default, since some columns' tab order means something. Tabs can
+    /// be clicked to switch to them either way.
+    pub fn with_tab_drag_to_reorder(mut self, enable: bool) -> Self {
+        self.tab_drag_to_reorder = enable;
+        self
+    }
+
+    /// Scroll down through the timeline on its own at `velocity` pixels
+    /// per second, like a teleprompter. Hovering the column pauses it.
+    /// 0 turns it off, which is the default.
+    pub fn with_auto_scroll(mut self, velocity: f32) -> Self {
+        self.auto_scroll_velocity = velocity;
+        self
+    }
+
+    /// Show a progress bar in the column footer while fetching older
+    /// notes, from 0.0 to 1.0. Completed progress is shown briefly before
+    /// the bar hides.
+    pub fn with_loading_progress(mut self, progress: Option<f32>) -> Self {
+        self.loading_progress = progress;
+        self
+    }
+
+    /// Focus this column when `key` is pressed, unless a text field has
+    /// keyboard focus. The key is remembered by the column and shown in
+    /// its header.
+    pub fn with_focus_mode_shortcut(mut self, key: egui::Key) -> Self {
+        self.focus_key = Some(key);
+        self
+    }
+
+    /// Hide notes whose id has fewer than `bits` leading zero bits of
+    /// proof of work. 0 shows everything.
+    pub fn with_min_pow_threshold(mut self, bits: u8) -> Self {
+        self.min_pow = bits;
+        self
+    }
+
+    /// Hide notes whose content warning rates them above `max_rating`.
+    /// Defaults to [`ContentRating::Sensitive`], which hides adult content.
+    pub fn with_content_rating_filter(mut self, max_rating: ContentRating) -> Self {
+        self.max_rating = max_rating;
+        self
+    }
+
+    /// Check the account's mutes separately from `is_muted`, so the
+    /// column's [`Timeline::apply_mutes`](crate::timeline::Timeline::apply_mutes)
+    /// setting decides whether muted notes are hidden or shown with a
+    /// marker
+    pub fn with_mute_scope(mut self, mutes: &'a MuteFun) -> Self {
+        self.mutes = Some(mutes);
+        self
+    }
+
+    /// Separate this column from the next with `divider` instead of the
+    /// app's default. Like the column type icon, the column remembers it.
+    pub fn with_column_divider(mut self, divider: ColumnDivider) -> Self {
+        self.column_divider = Some(divider);
+        self
+    }
+
+    /// Snap the column's width to multiples of the snap grid while it's
+    /// resized by dragging its right edge. Holding Shift while dragging
+    /// resizes it freely. The column remembers it.
+    pub fn with_column_snap(mut self, enabled: bool) -> Self {
+        self.column_snap = Some(enabled);
+        self
+    }
+
+    /// Move the column to the archive once it's gone `archive_after`
+    /// without new notes or being scrolled. It keeps its subscription
+    /// and can be restored from the "Archived" drawer, see
+    /// [`Columns::archive_column`].
+    pub fn with_column_archive(mut self, archive_after: Duration) -> Self {
+        self.archive_after = Some(archive_after);
+        self
+    }
+
+    /// The grid [`TimelineView::with_column_snap`] snaps to, in points.
+    /// Defaults to [`COLUMN_SNAP_GRID`].
+    pub fn with_column_snap_grid(mut self, grid: f32) -> Self {
+        self.column_snap_grid = grid;
+        self
+    }
+
+    /// Show `widget` centered in the column while the selected view has
+    /// no notes, eg. an icon with a call to action
+    pub fn with_custom_empty_state_widget(mut self, widget: impl egui::Widget + 'a) -> Self {
+        self.empty_state = Some(Box::new(move |ui| ui.add(widget)));
+        self
+    }
+
+    /// Show `msg` while the selected view has no notes. A shorthand for
+    /// [`TimelineView::with_custom_empty_state_widget`] with a label
+    pub fn with_empty_state(self, msg: &'a str) -> Self {
+        self.with_custom_empty_state_widget(
+            egui::Label::new(egui::RichText::new(msg).weak()).selectable(false),
+        )
+    }
+
+    /// Draw `text` faintly and diagonally across the whole column, above
+    /// the notes, eg. "Internal - Confidential"
+    pub fn with_watermark_overlay(mut self, text: &'a str) -> Self {
+        self.watermark = Some(text);
+        self
+    }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn timeline_ui(
-    ui: &mut egui::Ui,
-    ndb: &Ndb,
-    timeline_id: TimelineId,
-    columns: &mut Columns,
-    note_cache: &mut NoteCache,
-    img_cache: &mut ImageCache,
-    reversed: bool,
-    note_options: NoteOptions,
-    is_muted: &MuteFun,
-) -> Option<NoteAction> {
+/// Render `view`'s timeline, hiding the notes `is_muted` matches
+fn timeline_ui(ui: &mut egui::Ui, view: &mut TimelineView, is_muted: &MuteFun) -> TimelineResponse {
+    let compact = view
+        .columns
+        .find_timeline(view.timeline_id)
+        .map_or(false, |tl| tl.compact_when_inactive)
+        && !view.columns.is_timeline_focused(view.timeline_id);
+    if compact {
+        view.note_options.set_density(NoteDensity::Compact);
+    }
+
+    // sizes the scroll thumb for all of the view's notes, not just the
+    // ones the virtual list has seen
+    let content_height = if view.orientation == Orientation::Vertical {
+        Transaction::new(view.ndb)
+            .ok()
+            .map(|txn| view.measure_content_height(ui, &txn))
+    } else {
+        None
+    };
+
+    let timeline_id = view.timeline_id;
+    let ndb = view.ndb;
+    let columns = &mut *view.columns;
+    let note_cache = &mut *view.note_cache;
+    let img_cache = &mut *view.img_cache;
+    let reversed = view.reverse;
+    let note_options = view.note_options;
+    let mutes = view.mutes;
+    let watermark = view.watermark;
+    let empty_state = view.empty_state.take();
+    let help = &view.help;
+
     //padding(4.0, ui, |ui| ui.heading("Notifications"));
     /*
     let font_id = egui::TextStyle::Body.resolve(ui.style());
@@ -87,31 +603,130 @@ fn timeline_ui(
 
     */
 
-    let scroll_id = {
+    let mut response = TimelineResponse::default();
+    let column_rect = ui.max_rect();
+
+    // clicking anywhere in the column focuses it
+    let pressed_inside = ui.input(|i| {
+        i.pointer.primary_pressed()
+            && i.pointer
+                .interact_pos()
+                .map_or(false, |pos| ui.max_rect().contains(pos))
+    });
+    if pressed_inside {
+        columns.focus_timeline(timeline_id);
+    }
+
+    let focus_key = columns
+        .find_timeline(timeline_id)
+        .and_then(|tl| tl.focus_key);
+    if let Some(key) = focus_key {
+        let typing = ui.memory(|m| m.focused().is_some());
+        if !typing && ui.input(|i| i.key_pressed(key)) {
+            // bring the column into view in the deck
+            ui.scroll_to_rect(ui.max_rect(), None);
+            response.actions.push(NoteAction::FocusColumn(timeline_id));
+        }
+    }
+
+    if view.orientation == Orientation::Horizontal {
+        let horizontal_response = horizontal_timeline_ui(
+            ui,
+            ndb,
+            timeline_id,
+            columns,
+            note_cache,
+            img_cache,
+            reversed,
+            note_options,
+            is_muted,
+            mutes,
+        );
+
+        if let Some(text) = watermark {
+            watermark_overlay(ui, column_rect, text);
+        }
+
+        response.merge(horizontal_response);
+        record_analytics(columns, timeline_id, &[], &response.actions);
+        return response;
+    }
+
+    let (scroll_id, searching) = {
         let timeline = if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
             timeline
         } else {
             error!("tried to render timeline in column, but timeline was missing");
             // TODO (jb55): render error when timeline is missing?
             // this shouldn't happen...
-            return None;
+            return response;
         };
 
-        timeline.selected_view = tabs_ui(ui, timeline.selected_view, &timeline.views);
+        if let Some(search) = timeline.search.as_mut() {
+            search_box(ui, &mut search.query);
+        }
+
+        tabs_ui(
+            ui,
+            timeline,
+            note_options.has_touch_mode(),
+            view.tab_drag_to_reorder,
+        );
+
+        if let Ok(txn) = Transaction::new(ndb) {
+            let ordering = timeline.ordering;
+            timeline
+                .current_view_mut()
+                .apply_ordering(ordering, ndb, &txn);
+
+            let view_id = timeline.view_id();
+            let since = timeline.since();
+            if let Some(search) = timeline.search.as_mut().filter(|s| s.is_active()) {
+                let notes = timeline.views[timeline.selected_view].display_notes(since);
+                search.update_matches(ndb, &txn, view_id, &notes);
+            }
+        }
+
+        if view.debug_overlay {
+            insertion_stats_overlay(ui, timeline.current_view_mut());
+        }
+
+        if let Some(note_key) = timeline.kept_at_top {
+            let resp = kept_at_top_ui(ui, ndb, note_cache, img_cache, note_options, note_key);
+            if resp.clear {
+                timeline.kept_at_top = None;
+            }
+            response.actions.extend(resp.action);
+        }
 
         // need this for some reason??
         ui.add_space(3.0);
 
-        egui::Id::new(("tlscroll", timeline.view_id()))
+        if let Some(progress) = view.loading_progress {
+            loading_progress_footer(ui, timeline.view_id(), progress);
+        }
+
+        // the view id includes the selected tab, so every tab keeps its
+        // own scroll offset. New notes at the top keep our place through
+        // VirtualList::items_inserted_at_start, see TimelineTab::insert.
+        // Search results scroll separately, so clearing the search
+        // returns to where we were.
+        let searching = timeline.search.as_ref().map_or(false, |s| s.is_active());
+        let scroll_id = if searching {
+            egui::Id::new(("tlscroll_search", timeline.view_id()))
+        } else {
+            egui::Id::new(("tlscroll", timeline.view_id()))
+        };
+        (scroll_id, searching)
     };
 
-    egui::ScrollArea::vertical()
+    let mut scroll_output = egui::ScrollArea::vertical()
         .id_salt(scroll_id)
         .animated(false)
         .auto_shrink([false, false])
         .scroll_bar_visibility(ScrollBarVisibility::AlwaysVisible)
         .show(ui, |ui| {
-            let timeline = if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+            let timeline = if let Some(timeline) = columns.find_timeline(timeline_id) {
                 timeline
             } else {
                 error!("tried to render timeline in column, but timeline was missing");
@@ -120,8 +735,48 @@ fn timeline_ui(
                 return None;
             };
 
+            if let Some(empty_state) = empty_state {
+                if timeline
+                    .current_view()
+                    .display_notes(timeline.since())
+                    .is_empty()
+                {
+                    ui.with_layout(Layout::centered_and_justified(Direction::TopDown), |ui| {
+                        empty_state(ui);
+                    });
+                    return None;
+                }
+            } else if timeline.current_view().notes.is_empty() {
+                // nothing loaded yet, the subscription may be new or the
+                // relays still connecting
+                let mut tab_response = TimelineResponse::default();
+                if loading_empty_state(ui) {
+                    tab_response
+                        .actions
+                        .push(NoteAction::RefreshTimeline(timeline_id));
+                }
+                return Some((tab_response, None, vec![], None, vec![], None));
+            }
+
+            let search = timeline.search.as_ref().filter(|s| s.is_active());
+            if search.map_or(false, |s| s.matches().is_empty()) {
+                ui.with_layout(Layout::centered_and_justified(Direction::TopDown), |ui| {
+                    ui.weak("No matching notes");
+                });
+                return None;
+            }
+
+            // search results are a subset of what was measured
+            if let Some(height) = content_height.filter(|_| search.is_none()) {
+                ui.set_min_height(height);
+            }
+
+            let mut note_options = note_options;
+            note_options.set_keep_at_top(true);
+
+            let focused = columns.is_timeline_focused(timeline_id);
             let txn = Transaction::new(ndb).expect("failed to create txn");
-            TimelineTabView::new(
+            let mut tab_view = TimelineTabView::new(
                 timeline.current_view(),
                 reversed,
                 note_options,
@@ -131,56 +786,687 @@ fn timeline_ui(
                 img_cache,
                 is_muted,
             )
-            .show(ui)
-        })
-        .inner
-}
+            .since(timeline.since())
+            .with_sticky_date_header(true)
+            .with_thread_connectors(timeline.thread_connectors)
+            .with_reply_depth_limit(view.reply_depth_limit)
+            .with_keyboard_focus(focused && search.is_none())
+            .with_seen_notes(&timeline.seen_notes)
+            .with_revealed_notes(&timeline.revealed_notes);
 
-pub fn tabs_ui(ui: &mut egui::Ui, selected: usize, views: &[TimelineTab]) -> usize {
-    ui.spacing_mut().item_spacing.y = 0.0;
-
-    let tab_res = egui_tabs::Tabs::new(views.len() as i32)
-        .selected(selected as i32)
-        .hover_bg(TabColor::none())
-        .selected_fg(TabColor::none())
-        .selected_bg(TabColor::none())
-        .hover_bg(TabColor::none())
-        //.hover_bg(TabColor::custom(egui::Color32::RED))
-        .height(32.0)
-        .layout(Layout::centered_and_justified(Direction::TopDown))
-        .show(ui, |ui, state| {
-            ui.spacing_mut().item_spacing.y = 0.0;
-
-            let ind = state.index();
-
-            let txt = match views[ind as usize].filter {
-                ViewFilter::Notes => "Notes",
-                ViewFilter::NotesAndReplies => "Notes & Replies",
-            };
-
-            let res = ui.add(egui::Label::new(txt).selectable(false));
+            if let Some(search) = search {
+                tab_view = tab_view
+                    .notes(search.matches())
+                    .with_virtual_list(search.list.clone());
+            }
 
-            // underline
-            if state.is_selected() {
-                let rect = res.rect;
-                let underline =
-                    shrink_range_to_width(rect.x_range(), get_label_width(ui, txt) * 1.15);
-                let underline_y = ui.painter().round_to_pixel(rect.bottom()) - 1.5;
-                return (underline, underline_y);
+            if let Some(mutes) = mutes {
+                tab_view = tab_view.with_mute_scope(mutes, timeline.apply_mutes);
             }
 
-            (egui::Rangef::new(0.0, 0.0), 0.0)
+            let tab_response = tab_view.show(ui);
+            Some((
+                tab_response,
+                tab_view.kept_at_top(),
+                tab_view.scrolled_past().to_vec(),
+                tab_view.clicked_row(),
+                tab_view.newly_seen().to_vec(),
+                tab_view.revealed(),
+            ))
         });
 
-    //ui.add_space(0.5);
-    ui::hline(ui);
+    let (tab_response, kept_at_top, scrolled_past, clicked_row, newly_seen, revealed) =
+        scroll_output.inner.take().unwrap_or_default();
+    // rows of search results aren't rows of the tab
+    if let Some(row) = clicked_row.filter(|_| !searching) {
+        if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+            timeline.current_view_mut().selection = Some(row);
+        }
+    }
 
-    let sel = tab_res.selected().unwrap_or_default();
+    let help_action = columns.find_timeline(timeline_id).and_then(|timeline| {
+        let empty = timeline
+            .current_view()
+            .display_notes(timeline.since())
+            .is_empty();
+        let scrolled = scroll_output.state.offset.y > 0.0;
 
-    let (underline, underline_y) = tab_res.inner()[sel as usize].inner;
-    let underline_width = underline.span();
+        let item = help.iter().find(|item| match item.trigger {
+            HelpTrigger::EmptyColumn => empty,
+            HelpTrigger::FirstNote => !empty,
+            HelpTrigger::FirstScroll => scrolled,
+        })?;
 
-    let tab_anim_id = ui.id().with("tab_anim");
+        help_tip(ui, timeline.view_id(), scroll_output.inner_rect, item)
+    });
+    response.merge(tab_response);
+    response.actions.extend(help_action);
+    record_analytics(columns, timeline_id, &scrolled_past, &response.actions);
+    if let Some(markers) = columns
+        .find_timeline_mut(timeline_id)
+        .and_then(|tl| tl.read_markers.as_mut())
+    {
+        markers.mark_read(&scrolled_past, unix_now());
+    }
+    if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+        for note_key in newly_seen {
+            timeline.seen_notes.insert(note_key);
+        }
+        if let Some(note_key) = revealed {
+            timeline.revealed_notes.insert(note_key);
+        }
+    }
+    if let Some(note_key) = kept_at_top {
+        if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+            // only one kept note per column, the newest one wins
+            timeline.kept_at_top = Some(note_key);
+        }
+    }
+
+    if view.header_collapse_on_scroll {
+        if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+            timeline.update_header_collapse(scroll_output.state.offset.y);
+        }
+    }
+
+    // swiping sideways switches tabs on touch screens, as if the tab
+    // was tapped
+    if note_options.has_touch_mode() && !searching {
+        let swipe_id = egui::Id::new(("tlswipe", timeline_id));
+        if let Some(step) =
+            finished_drag(ui, swipe_id, scroll_output.inner_rect).and_then(swipe_step)
+        {
+            if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+                timeline.selected_view =
+                    step_visible_view(&timeline.views, timeline.selected_view, step);
+                ui.ctx().request_repaint();
+            }
+        }
+    }
+
+    // scrolling keeps the column out of the archive
+    if ui.rect_contains_pointer(scroll_output.inner_rect)
+        && ui.input(|i| i.smooth_scroll_delta.y != 0.0)
+    {
+        if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+            timeline.last_active = unix_now();
+        }
+    }
+
+    if let Some(speed) = columns
+        .find_timeline(timeline_id)
+        .filter(|tl| tl.auto_scroll)
+        .map(|tl| tl.auto_scroll_speed)
+    {
+        auto_scroll(ui, &mut scroll_output, speed);
+    }
+
+    if view.auto_scroll_velocity != 0.0 {
+        teleprompter_scroll(ui, &mut scroll_output, view.auto_scroll_velocity);
+    }
+
+    if let Some(text) = watermark {
+        watermark_overlay(ui, column_rect, text);
+    }
+
+    response
+}
+
+/// A search box filtering the notes of a timeline as the user types
+fn search_box(ui: &mut egui::Ui, query: &mut String) {
+    egui::Frame::none()
+        .inner_margin(egui::Margin::symmetric(8.0, 4.0))
+        .show(ui, |ui| {
+            ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                if !query.is_empty() && ui.small_button("✕").clicked() {
+                    query.clear();
+                }
+                ui.add(
+                    egui::TextEdit::singleline(query)
+                        .hint_text("Search this timeline")
+                        .desired_width(ui.available_width()),
+                );
+            });
+        });
+}
+
+/// Shown in place of the notes while a timeline has none. Returns true
+/// when the user asks to refresh it.
+fn loading_empty_state(ui: &mut egui::Ui) -> bool {
+    let mut refresh = false;
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() / 3.0);
+        ui.add(egui::Spinner::new());
+        ui.add_space(8.0);
+        ui.weak("Nothing here yet");
+        ui.add_space(8.0);
+        refresh = ui.button("Refresh").clicked();
+    });
+    refresh
+}
+
+/// A dismissible tip in the top right of the notes at `rect`
+fn help_tip(
+    ui: &mut egui::Ui,
+    view_id: egui::Id,
+    rect: egui::Rect,
+    item: &HelpItem,
+) -> Option<NoteAction> {
+    let width = (rect.width() - 32.0).clamp(120.0, 260.0);
+    let mut dismissed = false;
+
+    egui::Area::new(view_id.with(("help_tip", item.trigger)))
+        .order(egui::Order::Foreground)
+        .fixed_pos(egui::pos2(rect.right() - width - 16.0, rect.top() + 8.0))
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(width);
+                ui.label(egui::RichText::new("💡 Tip").strong());
+                ui.label(&item.message);
+                ui.with_layout(Layout::right_to_left(egui::Align::Min), |ui| {
+                    dismissed = ui.button("Got it").clicked();
+                });
+            });
+        });
+
+    dismissed.then_some(NoteAction::DismissHelp(item.trigger))
+}
+
+/// Repeat `text` diagonally down the column at a low opacity. This only
+/// paints, so it never gets in the way of interacting with notes.
+fn watermark_overlay(ui: &mut egui::Ui, rect: egui::Rect, text: &str) {
+    let painter = ui.painter_at(rect);
+    let color = ui.visuals().text_color().gamma_multiply(0.08);
+    let font = egui::FontId::proportional(28.0);
+    let galley = painter.layout_no_wrap(text.to_owned(), font, color);
+
+    // rotating around the top left of the text, so center it by hand
+    let angle = -std::f32::consts::FRAC_PI_6;
+    let half = galley.size() / 2.0;
+    let offset = egui::vec2(
+        half.x * angle.cos() - half.y * angle.sin(),
+        half.x * angle.sin() + half.y * angle.cos(),
+    );
+
+    let spacing = (galley.size().x * angle.sin().abs()).max(120.0) + 80.0;
+    let mut y = rect.top() + spacing / 2.0;
+    while y < rect.bottom() {
+        let pos = egui::pos2(rect.center().x, y) - offset;
+        painter.add(egui::epaint::TextShape::new(pos, galley.clone(), color).with_angle(angle));
+        y += spacing;
+    }
+}
+
+/// How long completed loading progress stays up before hiding, in seconds
+const LOADING_COMPLETE_LINGER: f64 = 1.0;
+
+/// A progress bar along the bottom of the column. This takes space from
+/// the remaining area, so it needs to come before the timeline itself.
+fn loading_progress_footer(ui: &mut egui::Ui, view_id: egui::Id, progress: f32) {
+    let completed_id = view_id.with("loading_completed_at");
+    let now = ui.input(|i| i.time);
+
+    if progress < 1.0 {
+        ui.data_mut(|d| d.remove::<f64>(completed_id));
+    } else {
+        let completed_at = ui.data_mut(|d| *d.get_temp_mut_or_insert_with(completed_id, || now));
+        let remaining = LOADING_COMPLETE_LINGER - (now - completed_at);
+        if remaining <= 0.0 {
+            return;
+        }
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+    }
+
+    egui::TopBottomPanel::bottom(view_id.with("loading_progress"))
+        .show_separator_line(false)
+        .frame(egui::Frame::none().inner_margin(egui::Margin::symmetric(8.0, 4.0)))
+        .show_inside(ui, |ui| {
+            ui.add(egui::ProgressBar::new(progress.clamp(0.0, 1.0)).desired_height(4.0));
+        });
+}
+
+/// How many times we retry a note we failed to load before giving up on it
+const MAX_NOTE_RETRIES: u8 = 5;
+
+/// Failed note lookups: the number of attempts so far and when to count
+/// the next one, in egui time
+type NoteRetries = HashMap<NoteKey, (u8, f64)>;
+
+fn note_retries_id() -> egui::Id {
+    egui::Id::new("note_lookup_retries")
+}
+
+/// Whether a note we failed to load should be looked up again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NoteLookup {
+    /// It never failed, or its backoff is up
+    Due,
+    /// Waiting out the backoff after a failed lookup
+    Waiting,
+    /// It failed [`MAX_NOTE_RETRIES`] times and we stopped trying
+    GaveUp,
+}
+
+fn note_lookup(ctx: &egui::Context, note_key: NoteKey) -> NoteLookup {
+    let now = ctx.input(|i| i.time);
+
+    ctx.data_mut(|d| {
+        let retries = d.get_temp_mut_or_default::<NoteRetries>(note_retries_id());
+        match retries.get(&note_key) {
+            None => NoteLookup::Due,
+            Some((_, retry_at)) if now < *retry_at => NoteLookup::Waiting,
+            Some((attempts, _)) if *attempts >= MAX_NOTE_RETRIES => NoteLookup::GaveUp,
+            Some(_) => NoteLookup::Due,
+        }
+    })
+}
+
+/// Count a failed note lookup. Returns true while we should keep trying,
+/// scheduling a repaint after an exponential backoff of 100ms × 2^attempt.
+/// Lookups that fail again before the backoff is up aren't counted.
+fn retry_note_later(ctx: &egui::Context, note_key: NoteKey) -> bool {
+    let now = ctx.input(|i| i.time);
+
+    let backoff = ctx.data_mut(|d| {
+        let retries = d.get_temp_mut_or_default::<NoteRetries>(note_retries_id());
+        let (attempts, retry_at) = retries.entry(note_key).or_insert((0, now));
+
+        if now < *retry_at {
+            return Some(*retry_at - now);
+        }
+
+        if *attempts >= MAX_NOTE_RETRIES {
+            return None;
+        }
+
+        let backoff = 0.1 * f64::from(1u32 << *attempts);
+        *attempts += 1;
+        *retry_at = now + backoff;
+        Some(backoff)
+    });
+
+    if let Some(backoff) = backoff {
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(backoff));
+        true
+    } else {
+        false
+    }
+}
+
+fn forget_note_retries(ctx: &egui::Context, note_key: NoteKey) {
+    ctx.data_mut(|d| {
+        if let Some(retries) = d
+            .get_temp_mut_or_default::<NoteRetries>(note_retries_id())
+            .remove(&note_key)
+        {
+            debug!("note {:?} loaded after {} retries", note_key, retries.0);
+        }
+    });
+}
+
+/// How long newly inserted notes stay highlighted, in seconds
+const INSERT_HIGHLIGHT_DURATION: f32 = 1.0;
+
+/// How far down we look for the previous newest note. If it isn't in
+/// there the notes were replaced rather than inserted, eg. when switching
+/// filters, so nothing is highlighted.
+const MAX_INSERT_HIGHLIGHTS: usize = 100;
+
+/// Notes inserted at the top of a timeline since it was last shown, with
+/// the egui time they showed up
+#[derive(Clone, Default)]
+struct RecentlyInserted {
+    newest: Option<NoteKey>,
+    inserted: HashMap<NoteKey, f64>,
+}
+
+fn insert_highlight_id(id: egui::Id, note_key: NoteKey) -> egui::Id {
+    id.with(("insert_highlight", note_key))
+}
+
+/// Find notes that were inserted above the newest note we saw last time,
+/// starting their highlight animation. Returns the notes that are still
+/// highlighted.
+fn track_insertions(ctx: &egui::Context, id: egui::Id, notes: &[NoteRef]) -> HashMap<NoteKey, f64> {
+    let now = ctx.input(|i| i.time);
+    let mut recent = ctx.data_mut(|d| d.get_temp::<RecentlyInserted>(id).unwrap_or_default());
+
+    if let Some(newest) = recent.newest {
+        let new_count = notes
+            .iter()
+            .take(MAX_INSERT_HIGHLIGHTS)
+            .position(|note| note.key == newest);
+
+        for note in &notes[..new_count.unwrap_or(0)] {
+            recent.inserted.insert(note.key, now);
+            // start fully highlighted, we fade to nothing from here
+            ctx.animate_value_with_time(
+                insert_highlight_id(id, note.key),
+                1.0,
+                INSERT_HIGHLIGHT_DURATION,
+            );
+        }
+    }
+
+    recent.newest = notes.first().map(|note| note.key);
+    recent
+        .inserted
+        .retain(|_, at| now - *at < f64::from(INSERT_HIGHLIGHT_DURATION));
+
+    let inserted = recent.inserted.clone();
+    ctx.data_mut(|d| d.insert_temp(id, recent));
+    inserted
+}
+
+/// Advance the scroll offset ticker-style while the timeline is hovered.
+/// We pause whenever the user is scrolling or clicking around.
+fn auto_scroll<R>(
+    ui: &egui::Ui,
+    scroll_output: &mut egui::scroll_area::ScrollAreaOutput<R>,
+    speed: f32,
+) {
+    let hovered = ui.rect_contains_pointer(scroll_output.inner_rect);
+    let interacting =
+        ui.input(|i| i.pointer.any_down() || i.smooth_scroll_delta != egui::Vec2::ZERO);
+    if !hovered || interacting {
+        return;
+    }
+
+    let dt = ui.input(|i| i.stable_dt);
+    advance_scroll(ui, scroll_output, speed * dt);
+}
+
+/// Advance the scroll offset teleprompter-style until the user hovers
+/// the timeline, resuming when the pointer leaves it
+fn teleprompter_scroll<R>(
+    ui: &egui::Ui,
+    scroll_output: &mut egui::scroll_area::ScrollAreaOutput<R>,
+    velocity: f32,
+) {
+    if ui.rect_contains_pointer(scroll_output.inner_rect) {
+        let pos = scroll_output.inner_rect.right_bottom() - egui::vec2(24.0, 24.0);
+        ui.painter().text(
+            pos,
+            egui::Align2::CENTER_CENTER,
+            "⏸",
+            egui::FontId::proportional(16.0),
+            ui.visuals().weak_text_color(),
+        );
+        return;
+    }
+
+    let dt = ui.input(|i| i.predicted_dt);
+    advance_scroll(ui, scroll_output, velocity * dt);
+}
+
+/// Move the scroll offset by `distance`, staying within the content
+fn advance_scroll<R>(
+    ui: &egui::Ui,
+    scroll_output: &mut egui::scroll_area::ScrollAreaOutput<R>,
+    distance: f32,
+) {
+    let max_offset = (scroll_output.content_size.y - scroll_output.inner_rect.height()).max(0.0);
+    let offset = &mut scroll_output.state.offset.y;
+    let next = (*offset + distance).clamp(0.0, max_offset);
+    if next == *offset {
+        return;
+    }
+
+    *offset = next;
+    scroll_output.state.store(ui.ctx(), scroll_output.id);
+    ui.ctx().request_repaint();
+}
+
+struct KeptAtTopResponse {
+    action: Option<NoteAction>,
+    clear: bool,
+}
+
+/// Render the note the user is keeping above the feed. Tapping the
+/// "Kept at top" label lets go of it.
+fn kept_at_top_ui(
+    ui: &mut egui::Ui,
+    ndb: &Ndb,
+    note_cache: &mut NoteCache,
+    img_cache: &mut ImageCache,
+    note_options: NoteOptions,
+    note_key: NoteKey,
+) -> KeptAtTopResponse {
+    let txn = Transaction::new(ndb).expect("failed to create txn");
+    let note = if let Ok(note) = ndb.get_note_by_key(&txn, note_key) {
+        note
+    } else {
+        warn!("kept note {:?} missing from db", note_key);
+        return KeptAtTopResponse {
+            action: None,
+            clear: true,
+        };
+    };
+
+    let mut action = None;
+    let mut clear = false;
+
+    ui::padding(note_options.note_padding(), ui, |ui| {
+        let label = egui::Label::new(
+            egui::RichText::new("📌 Kept at top · tap to clear")
+                .text_style(NotedeckTextStyle::Small.text_style())
+                .weak(),
+        )
+        .selectable(false)
+        .sense(egui::Sense::click());
+
+        clear = ui
+            .add(label)
+            .on_hover_cursor(egui::CursorIcon::PointingHand)
+            .clicked();
+
+        action = ui::NoteView::new(ndb, note_cache, img_cache, &note)
+            .note_options(note_options)
+            .show(ui)
+            .action;
+    });
+
+    ui::hline(ui);
+
+    KeptAtTopResponse { action, clear }
+}
+
+fn insertion_stats_overlay(ui: &mut egui::Ui, tab: &mut TimelineTab) {
+    let stats = tab.note_insertion_stats();
+    let text = format!(
+        "total {} filtered {} muted {} dupes {}",
+        stats.total, stats.filtered, stats.muted, stats.duplicate
+    );
+
+    let rect = ui.available_rect_before_wrap();
+    let resp = egui::Area::new(ui.id().with("insertion_stats"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(rect.left_top())
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style())
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::Label::new(
+                            egui::RichText::new(text)
+                                .text_style(NotedeckTextStyle::Tiny.text_style()),
+                        )
+                        .selectable(false)
+                        .sense(egui::Sense::click()),
+                    )
+                })
+                .inner
+        })
+        .inner;
+
+    if resp.on_hover_text("Click to reset").clicked() {
+        tab.clear_stats();
+    }
+}
+
+fn record_analytics(
+    columns: &mut Columns,
+    timeline_id: TimelineId,
+    scrolled_past: &[NoteKey],
+    actions: &[NoteAction],
+) {
+    let analytics = columns
+        .find_timeline_mut(timeline_id)
+        .and_then(|tl| tl.analytics.as_mut());
+
+    if let Some(analytics) = analytics {
+        analytics.record_read(scrolled_past);
+        for action in actions {
+            analytics.record_action(action);
+        }
+    }
+}
+
+/// A [`Orientation::Horizontal`] timeline: its tabs down the left edge and
+/// its notes scrolling left to right
+#[allow(clippy::too_many_arguments)]
+fn horizontal_timeline_ui(
+    ui: &mut egui::Ui,
+    ndb: &Ndb,
+    timeline_id: TimelineId,
+    columns: &mut Columns,
+    note_cache: &mut NoteCache,
+    img_cache: &mut ImageCache,
+    reversed: bool,
+    note_options: NoteOptions,
+    is_muted: &MuteFun,
+    mutes: Option<&MuteFun>,
+) -> TimelineResponse {
+    let timeline = if let Some(timeline) = columns.find_timeline_mut(timeline_id) {
+        timeline
+    } else {
+        error!("tried to render timeline in column, but timeline was missing");
+        return TimelineResponse::default();
+    };
+
+    let txn = Transaction::new(ndb).expect("failed to create txn");
+    let ordering = timeline.ordering;
+    timeline
+        .current_view_mut()
+        .apply_ordering(ordering, ndb, &txn);
+
+    ui.with_layout(Layout::left_to_right(egui::Align::TOP), |ui| {
+        if timeline.views.len() > 1 {
+            timeline.selected_view = vertical_tabs_ui(ui, timeline.selected_view, &timeline.views);
+            ui.separator();
+        }
+
+        let mut view = TimelineTabView::new(
+            timeline.current_view(),
+            reversed,
+            note_options,
+            &txn,
+            ndb,
+            note_cache,
+            img_cache,
+            is_muted,
+        )
+        .since(timeline.since());
+
+        if let Some(mutes) = mutes {
+            view = view.with_mute_scope(mutes, timeline.apply_mutes);
+        }
+
+        view.show_horizontal(ui, HORIZONTAL_CARD_WIDTH)
+    })
+    .inner
+}
+
+/// The timeline's tabs stacked down the left edge, see
+/// [`Orientation::Horizontal`]
+fn vertical_tabs_ui(ui: &mut egui::Ui, selected: usize, views: &[TimelineTab]) -> usize {
+    let mut selected = selected;
+    ui.vertical(|ui| {
+        for (ind, view) in views.iter().enumerate() {
+            if ui
+                .selectable_label(ind == selected, view.filter.name())
+                .clicked()
+            {
+                selected = ind;
+            }
+        }
+    });
+    selected
+}
+
+/// The timeline's tabs, switching views when they're clicked. With
+/// `drag_to_reorder` they can also be dragged to move their views.
+pub fn tabs_ui(
+    ui: &mut egui::Ui,
+    timeline: &mut Timeline,
+    touch_mode: bool,
+    drag_to_reorder: bool,
+) {
+    ui.spacing_mut().item_spacing.y = 0.0;
+
+    let selected = timeline.selected_view;
+    let views = &timeline.views;
+
+    // tabs are laid out from the visible views, and `selected` is an
+    // index into all of them
+    let visible: Vec<usize> = views
+        .iter()
+        .enumerate()
+        .filter(|(_, view)| !view.hidden)
+        .map(|(i, _)| i)
+        .collect();
+    let selected_tab = visible.iter().position(|i| *i == selected).unwrap_or(0);
+
+    let tab_height = if touch_mode {
+        ui::TOUCH_TARGET_SIZE
+    } else {
+        32.0
+    };
+
+    let tab_res = egui_tabs::Tabs::new(visible.len() as i32)
+        .selected(selected_tab as i32)
+        .hover_bg(TabColor::none())
+        .selected_fg(TabColor::none())
+        .selected_bg(TabColor::none())
+        .hover_bg(TabColor::none())
+        //.hover_bg(TabColor::custom(egui::Color32::RED))
+        .height(tab_height)
+        .layout(Layout::centered_and_justified(Direction::TopDown))
+        .show(ui, |ui, state| {
+            ui.spacing_mut().item_spacing.y = 0.0;
+
+            let view = &views[visible[state.index() as usize]];
+            let txt = view.filter.name();
+
+            let res = ui.add(egui::Label::new(txt).selectable(false));
+
+            if view.is_live() {
+                let text_width = get_label_width(ui, txt);
+                let center = res.rect.center() - egui::vec2(text_width / 2.0 + 8.0, 0.0);
+                live_dot(ui, center);
+            }
+
+            // underline
+            if state.is_selected() {
+                let rect = res.rect;
+                let underline =
+                    shrink_range_to_width(rect.x_range(), get_label_width(ui, txt) * 1.15);
+                let underline_y = ui.painter().round_to_pixel(rect.bottom()) - 1.5;
+                return (underline, underline_y);
+            }
+
+            (egui::Rangef::new(0.0, 0.0), 0.0)
+        });
+
+    //ui.add_space(0.5);
+    ui::hline(ui);
+
+    let sel = tab_res.selected().unwrap_or_default();
+
+    let (underline, underline_y) = tab_res.inner()[sel as usize].inner;
+    let underline_width = underline.span();
+
+    let tab_anim_id = ui.id().with("tab_anim");
     let tab_anim_size = tab_anim_id.with("size");
 
     let stroke = egui::Stroke {
@@ -190,7 +1476,8 @@ pub fn tabs_ui(ui: &mut egui::Ui, selected: usize, views: &[TimelineTab]) -> usi
 
     let speed = 0.1f32;
 
-    // animate underline position
+    // animate underline position. This follows the selected tab when
+    // it's moved, since we animate to wherever it's drawn
     let x = ui
         .ctx()
         .animate_value_with_time(tab_anim_id, underline.min, speed);
@@ -204,7 +1491,123 @@ pub fn tabs_ui(ui: &mut egui::Ui, selected: usize, views: &[TimelineTab]) -> usi
 
     ui.painter().hline(underline, underline_y, stroke);
 
-    sel as usize
+    let tab_rects: Vec<egui::Rect> = tab_res
+        .inner()
+        .iter()
+        .map(|tab| tab.response.rect)
+        .collect();
+    timeline.selected_view = visible.get(sel as usize).copied().unwrap_or(selected);
+
+    if drag_to_reorder {
+        if let Some((from, to)) = tab_drag(ui, &tab_rects) {
+            timeline.move_view(visible[from], visible[to]);
+        }
+    }
+}
+
+/// Dragging a tab onto another one. Returns the tab that was dropped and
+/// the tab it was dropped on, as indices into `tab_rects`. Only senses
+/// drags, so clicks still switch tabs.
+fn tab_drag(ui: &egui::Ui, tab_rects: &[egui::Rect]) -> Option<(usize, usize)> {
+    for (i, rect) in tab_rects.iter().enumerate() {
+        let response = ui.interact(*rect, ui.id().with(("tab_drag", i)), egui::Sense::drag());
+        if !response.dragged() && !response.drag_stopped() {
+            continue;
+        }
+
+        let x = response.interact_pointer_pos()?.x;
+        let target = tab_at(tab_rects, x);
+
+        if response.drag_stopped() {
+            return (target != i).then_some((i, target));
+        }
+
+        ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        if target != i {
+            ui.painter()
+                .rect_stroke(tab_rects[target], 4.0, ui.visuals().selection.stroke);
+        }
+    }
+
+    None
+}
+
+/// The tab under `x`, or the first or last tab when it's past either end
+fn tab_at(tab_rects: &[egui::Rect], x: f32) -> usize {
+    tab_rects
+        .iter()
+        .position(|rect| x < rect.right())
+        .unwrap_or(tab_rects.len().saturating_sub(1))
+}
+
+/// How far a swipe has to travel sideways to change tabs, in points
+const SWIPE_THRESHOLD: f32 = 80.0;
+
+/// The drag that ended this frame, from where it was pressed to where it
+/// was let go, if it was pressed in `rect`
+fn finished_drag(ui: &egui::Ui, id: egui::Id, rect: egui::Rect) -> Option<egui::Vec2> {
+    let (pressed, released, pos) = ui.input(|i| {
+        (
+            i.pointer.primary_pressed(),
+            i.pointer.primary_released(),
+            i.pointer.latest_pos(),
+        )
+    });
+
+    if pressed {
+        if let Some(pos) = pos.filter(|pos| rect.contains(*pos)) {
+            ui.data_mut(|d| d.insert_temp(id, (pos, pos)));
+        }
+        return None;
+    }
+
+    // touches end with the pointer gone, so we remember where it was
+    let (origin, last): (egui::Pos2, egui::Pos2) = ui.data(|d| d.get_temp(id))?;
+    let last = pos.unwrap_or(last);
+    if released {
+        ui.data_mut(|d| d.remove::<(egui::Pos2, egui::Pos2)>(id));
+        Some(last - origin)
+    } else {
+        ui.data_mut(|d| d.insert_temp(id, (origin, last)));
+        None
+    }
+}
+
+/// The tab to move by for a swipe of `delta`: the next one when swiping
+/// left, the previous one when swiping right. Swipes that are short, or
+/// that aren't mostly sideways like a vertical scroll, don't count.
+fn swipe_step(delta: egui::Vec2) -> Option<i32> {
+    if delta.x.abs() < SWIPE_THRESHOLD || delta.x.abs() < delta.y.abs() * 2.0 {
+        return None;
+    }
+
+    Some(if delta.x < 0.0 { 1 } else { -1 })
+}
+
+/// The visible view `step` tabs away from the view at `selected`,
+/// stopping at the first and last tabs
+fn step_visible_view(views: &[TimelineTab], selected: usize, step: i32) -> usize {
+    let visible: Vec<usize> = views
+        .iter()
+        .enumerate()
+        .filter(|(_, view)| !view.hidden)
+        .map(|(i, _)| i)
+        .collect();
+    let Some(pos) = visible.iter().position(|i| *i == selected) else {
+        return selected;
+    };
+
+    let pos = (pos as i32 + step).clamp(0, visible.len() as i32 - 1);
+    visible[pos as usize]
+}
+
+/// A slowly pulsing green dot, marking a tab that's receiving notes
+fn live_dot(ui: &mut egui::Ui, center: egui::Pos2) {
+    let time = ui.input(|i| i.time);
+    let pulse = ((time * std::f64::consts::PI).sin() * 0.5 + 0.5) as f32;
+    let color = colors::GREEN.gamma_multiply(0.4 + 0.6 * pulse);
+    ui.painter().circle_filled(center, 3.0, color);
+    ui.ctx().request_repaint_after_secs(0.05);
 }
 
 fn get_label_width(ui: &mut egui::Ui, text: &str) -> f32 {
@@ -232,6 +1635,50 @@ pub struct TimelineTabView<'a> {
     note_cache: &'a mut NoteCache,
     img_cache: &'a mut ImageCache,
     is_muted: &'a MuteFun,
+    mutes: Option<(&'a MuteFun, bool)>,
+    since: Option<u64>,
+    notes: Option<&'a [NoteRef]>,
+    sticky_date_header: bool,
+    thread_connectors: bool,
+    kept_at_top: Option<NoteKey>,
+    header_pinned_note: Option<NoteKey>,
+    list: Option<Rc<RefCell<VirtualList>>>,
+    font_fallback: Vec<egui::FontFamily>,
+    scrolled_past: Vec<NoteKey>,
+    keyboard_focus: bool,
+    clicked_row: Option<usize>,
+    reply_depth_limit: Option<u8>,
+    seen_notes: Option<&'a RecentNotes>,
+    newly_seen: Vec<NoteKey>,
+    revealed_notes: Option<&'a RecentNotes>,
+    revealed: Option<NoteKey>,
+}
+
+/// Why a note might not be shown, see [`TimelineTabView::mute_state`]
+#[derive(Debug, Clone, Copy)]
+struct MuteState {
+    /// Hidden by the view, eg. snoozed or below its proof of work
+    hidden: bool,
+    /// Muted by the account
+    muted: bool,
+    /// Muted by the account, but shown with a marker because the view
+    /// ignores mutes
+    marker: bool,
+}
+
+impl MuteState {
+    fn is_hidden(self) -> bool {
+        self.hidden || self.muted
+    }
+}
+
+/// A rendered row, used for drawing thread connectors
+struct ConnectorRow {
+    index: usize,
+    left: f32,
+    top: f32,
+    id: [u8; 32],
+    parent: Option<[u8; 32]>,
 }
 
 impl<'a> TimelineTabView<'a> {
@@ -255,21 +1702,401 @@ impl<'a> TimelineTabView<'a> {
             note_cache,
             img_cache,
             is_muted,
+            mutes: None,
+            since: None,
+            notes: None,
+            sticky_date_header: false,
+            thread_connectors: false,
+            kept_at_top: None,
+            header_pinned_note: None,
+            list: None,
+            font_fallback: vec![],
+            scrolled_past: vec![],
+            keyboard_focus: false,
+            clicked_row: None,
+            reply_depth_limit: None,
+            seen_notes: None,
+            newly_seen: vec![],
+            revealed_notes: None,
+            revealed: None,
         }
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<NoteAction> {
+    /// Let Enter open the selected note, see [`TimelineTab::selection`].
+    /// Only the focused column should have this.
+    pub fn with_keyboard_focus(mut self, focused: bool) -> Self {
+        self.keyboard_focus = focused;
+        self
+    }
+
+    /// The row whose note was clicked during the last [`TimelineTabView::show`],
+    /// so the keyboard selection can follow the mouse
+    pub fn clicked_row(&self) -> Option<usize> {
+        self.clicked_row
+    }
+
+    /// Report notes as they scroll into view with [`NoteAction::Seen`],
+    /// skipping the ones in `seen`. Add [`TimelineTabView::newly_seen`] to
+    /// it afterwards.
+    pub fn with_seen_notes(mut self, seen: &'a RecentNotes) -> Self {
+        self.seen_notes = Some(seen);
+        self
+    }
+
+    /// The notes reported with [`NoteAction::Seen`] during the last
+    /// [`TimelineTabView::show`]
+    pub fn newly_seen(&self) -> &[NoteKey] {
+        &self.newly_seen
+    }
+
+    /// Let the user tap a muted note's placeholder to show it. Muted notes
+    /// in `revealed` are shown, add [`TimelineTabView::revealed`] to it
+    /// afterwards.
+    pub fn with_revealed_notes(mut self, revealed: &'a RecentNotes) -> Self {
+        self.revealed_notes = Some(revealed);
+        self
+    }
+
+    /// The muted note the user tapped to show during the last
+    /// [`TimelineTabView::show`], if any
+    pub fn revealed(&self) -> Option<NoteKey> {
+        self.revealed
+    }
+
+    /// The note the user chose "Keep at top" on during the last
+    /// [`TimelineTabView::show`], if any
+    pub fn kept_at_top(&self) -> Option<NoteKey> {
+        self.kept_at_top
+    }
+
+    /// The rendered notes that were above the top of the scroll area
+    /// during the last [`TimelineTabView::show`]
+    pub fn scrolled_past(&self) -> &[NoteKey] {
+        &self.scrolled_past
+    }
+
+    /// Collapse replies more than `limit` replies deep in their thread
+    /// into a summary that opens the thread
+    pub fn with_reply_depth_limit(mut self, limit: Option<u8>) -> Self {
+        self.reply_depth_limit = limit;
+        self
+    }
+
+    /// Connect the avatars of neighboring notes that reply to each other,
+    /// such as self-reply chains
+    pub fn with_thread_connectors(mut self, enable: bool) -> Self {
+        self.thread_connectors = enable;
+        self
+    }
+
+    /// Check `mutes` in addition to `is_muted`. When `apply` is false,
+    /// notes they match are shown with a muted marker instead of hidden
+    pub fn with_mute_scope(mut self, mutes: &'a MuteFun, apply: bool) -> Self {
+        self.mutes = Some((mutes, apply));
+        self
+    }
+
+    /// Fall back to the fonts of these families, in order, for glyphs the
+    /// proportional font lacks, such as CJK text or emoji
+    pub fn with_font_fallback_stack(mut self, fonts: Vec<egui::FontFamily>) -> Self {
+        self.font_fallback = fonts;
+        self
+    }
+
+    /// Pin the date of the topmost visible note to the top of the
+    /// scroll area while scrolling through older notes
+    pub fn with_sticky_date_header(mut self, enable: bool) -> Self {
+        self.sticky_date_header = enable;
+        self
+    }
+
+    /// Keep `note_key` in view above the notes as they scroll, on a
+    /// darker card, eg. a community's announcement
+    pub fn with_header_pinned_note(mut self, note_key: Option<NoteKey>) -> Self {
+        self.header_pinned_note = note_key;
+        self
+    }
+
+    /// Show these notes instead of the tab's notes. They should be a
+    /// subset of the tab's notes in the same order.
+    pub fn notes(mut self, notes: &'a [NoteRef]) -> Self {
+        self.notes = Some(notes);
+        self
+    }
+
+    /// Lay out our notes with `list` instead of the tab's, eg. for a
+    /// subset of them given to [`TimelineTabView::notes`]
+    pub fn with_virtual_list(mut self, list: Rc<RefCell<VirtualList>>) -> Self {
+        self.list = Some(list);
+        self
+    }
+
+    /// Only show notes created at or after `since`
+    pub fn since(mut self, since: Option<u64>) -> Self {
+        self.since = since;
+        self
+    }
+
+    /// Lay our notes out left to right as cards `card_width` wide and as
+    /// tall as the view. A note that doesn't fit its card scrolls within
+    /// it.
+    pub fn show_horizontal(&mut self, ui: &mut egui::Ui, card_width: f32) -> TimelineResponse {
+        let mut response = TimelineResponse::default();
+        let tab = self.tab;
+        let notes = if let Some(notes) = self.notes {
+            Cow::Borrowed(notes)
+        } else {
+            tab.display_notes(self.since)
+        };
+
+        // hidden notes are dropped up front so the cards stay evenly
+        // spaced. That looks up every note, so the cards are only found
+        // again when the tab changes, or now and then for mute changes.
+        let cards_id = ui.id().with(("horizontal_cards", tab.filter));
+        let key = (tab.revision(), self.since, self.reversed, notes.len());
+        let now = ui.input(|i| i.time);
+        let cached = ui
+            .data(|d| d.get_temp::<HorizontalCards>(cards_id))
+            .filter(|c| c.key == Some(key) && now - c.found_at < HORIZONTAL_CARDS_RECHECK);
+        let cards = if let Some(cached) = cached {
+            cached.cards
+        } else {
+            let mut cards: Vec<(NoteKey, bool)> = Vec::with_capacity(notes.len());
+            for note_ref in notes.iter() {
+                if let Ok(note) = self.ndb.get_note_by_key(self.txn, note_ref.key) {
+                    let state = self.mute_state(&note);
+                    if !state.is_hidden() {
+                        cards.push((note_ref.key, state.marker));
+                    }
+                }
+            }
+            if self.reversed {
+                cards.reverse();
+            }
+
+            let cards = Arc::new(cards);
+            ui.data_mut(|d| {
+                d.insert_temp(
+                    cards_id,
+                    HorizontalCards {
+                        key: Some(key),
+                        found_at: now,
+                        cards: cards.clone(),
+                    },
+                )
+            });
+            cards
+        };
+
+        egui::ScrollArea::horizontal()
+            .id_salt(ui.id().with("horizontal_notes"))
+            .auto_shrink([false, false])
+            .show_viewport(ui, |ui, viewport| {
+                let height = ui.available_height();
+                let origin = ui.max_rect().min;
+                ui.set_min_size(egui::vec2(card_width * cards.len() as f32, height));
+
+                // only the cards in view are laid out
+                let first = (viewport.min.x / card_width).floor().max(0.0) as usize;
+                let last =
+                    ((viewport.max.x / card_width).ceil().max(0.0) as usize).min(cards.len());
+
+                for (ind, (note_key, show_muted_marker)) in
+                    cards.iter().enumerate().take(last).skip(first)
+                {
+                    let rect = egui::Rect::from_min_size(
+                        origin + egui::vec2(ind as f32 * card_width, 0.0),
+                        egui::vec2(card_width, height),
+                    );
+
+                    ui.allocate_new_ui(egui::UiBuilder::new().max_rect(rect), |ui| {
+                        egui::ScrollArea::vertical()
+                            .id_salt(("note_card", *note_key))
+                            .auto_shrink([false, false])
+                            .show(ui, |ui| {
+                                let note = if let Ok(note) =
+                                    self.ndb.get_note_by_key(self.txn, *note_key)
+                                {
+                                    note
+                                } else {
+                                    return;
+                                };
+
+                                if let Some(note_action) =
+                                    self.note_ui(ui, &note, *note_key, *show_muted_marker)
+                                {
+                                    response.actions.push(note_action);
+                                }
+                            });
+                    });
+
+                    ui.painter().vline(
+                        rect.right(),
+                        rect.y_range(),
+                        ui.visuals().widgets.noninteractive.bg_stroke,
+                    );
+                }
+            });
+
+        response
+    }
+
+    /// How many levels too deep a reply is for
+    /// [`TimelineTabView::with_reply_depth_limit`], if it is
+    fn replies_too_deep(&mut self, note_key: NoteKey, note: &Note) -> Option<u32> {
+        let limit = u32::from(self.reply_depth_limit?);
+        let depth = thread_depth(self.ndb, self.note_cache, self.txn, note_key, note);
+        (depth > limit).then_some(depth - limit)
+    }
+
+    /// Whether `note` is hidden by this view or muted by the account,
+    /// and whether it should carry a muted marker because it's only shown
+    /// since this view ignores mutes
+    fn mute_state(&mut self, note: &Note) -> MuteState {
+        let thread = *root_note_id_from_selected_id(self.ndb, self.note_cache, self.txn, note.id());
+
+        let globally_muted = self
+            .mutes
+            .map_or(false, |(mutes, _apply)| mutes(note, &thread));
+        let apply_mutes = self.mutes.map_or(true, |(_mutes, apply)| apply);
+        MuteState {
+            hidden: (self.is_muted)(note, &thread),
+            muted: globally_muted && apply_mutes,
+            marker: globally_muted && !apply_mutes,
+        }
+    }
+
+    /// A single padded note, handling the context menu selections that
+    /// concern the timeline
+    fn note_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        note: &Note,
+        note_key: NoteKey,
+        show_muted_marker: bool,
+    ) -> Option<NoteAction> {
         let mut action: Option<NoteAction> = None;
-        let len = self.tab.notes.len();
+        let custom_emoji =
+            author_emoji_map(ui.ctx(), self.ndb, self.txn, self.img_cache, note.pubkey());
 
-        let is_muted = self.is_muted;
-        self.tab
-            .list
-            .clone()
+        ui::padding(self.note_options.note_padding(), ui, |ui| {
+            if show_muted_marker {
+                ui.add(
+                    egui::Label::new(egui::RichText::new("🔇 Muted").small().weak())
+                        .selectable(false),
+                )
+                .on_hover_text("Shown because this column ignores mutes");
+            }
+
+            let resp = ui::NoteView::new(self.ndb, self.note_cache, self.img_cache, note)
+                .note_options(self.note_options)
+                .custom_emoji_map(&custom_emoji)
+                .show(ui);
+
+            if let Some(note_action) = resp.action {
+                action = Some(note_action)
+            }
+
+            if let Some(context) = resp.context_selection {
+                match context {
+                    NoteContextSelection::KeepAtTop => {
+                        self.kept_at_top = Some(note_key);
+                    }
+                    NoteContextSelection::SnoozeThread { secs } => {
+                        action = Some(NoteAction::SnoozeThread(NoteId::new(*note.id()), secs));
+                    }
+                    NoteContextSelection::CopyNoteJSON => {
+                        action = Some(NoteAction::CopyEventJson(note_key));
+                    }
+                    _ => {}
+                }
+                context.process(ui, self.ndb, note);
+            }
+        });
+
+        action
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) -> TimelineResponse {
+        let stack = if self.font_fallback.is_empty() {
+            None
+        } else {
+            fallback_stack_family(ui.ctx(), &self.font_fallback)
+        };
+
+        let stack = if let Some(stack) = stack {
+            stack
+        } else {
+            return self.show_notes(ui);
+        };
+
+        let text_styles = ui.style().text_styles.clone();
+        for font_id in ui.style_mut().text_styles.values_mut() {
+            if font_id.family == egui::FontFamily::Proportional {
+                font_id.family = stack.clone();
+            }
+        }
+
+        let response = self.show_notes(ui);
+        ui.style_mut().text_styles = text_styles;
+        response
+    }
+
+    fn show_notes(&mut self, ui: &mut egui::Ui) -> TimelineResponse {
+        let mut response = TimelineResponse::default();
+        self.scrolled_past.clear();
+        self.clicked_row = None;
+        self.newly_seen.clear();
+        self.revealed = None;
+        let tab = self.tab;
+        let notes = if let Some(notes) = self.notes {
+            Cow::Borrowed(notes)
+        } else {
+            tab.display_notes(self.since)
+        };
+        let notes: &[NoteRef] = &notes;
+        let len = notes.len();
+
+        if len == 0 && tab.filter == ViewFilter::Media {
+            ui.with_layout(Layout::centered_and_justified(Direction::TopDown), |ui| {
+                ui.weak("No media yet");
+            });
+            return response;
+        }
+
+        // room for the pinned note, so it only covers notes once we
+        // scroll. It's drawn last, so this is last frame's height.
+        let pinned_height_id = ui.id().with("header_pinned_note_height");
+        if self.header_pinned_note.is_some() {
+            let height: f32 = ui.data(|d| d.get_temp(pinned_height_id)).unwrap_or(0.0);
+            ui.add_space(height);
+        }
+
+        let content_top = ui.cursor().min;
+        let clip_top = ui.clip_rect().top();
+        let clip_bottom = ui.clip_rect().bottom();
+
+        // the first note whose bottom edge is below the top of the scroll
+        // area, along with that bottom edge
+        let mut top_note: Option<(usize, f32)> = None;
+        let mut rows: Vec<ConnectorRow> = vec![];
+
+        let highlight_id = ui.id().with("recently_inserted");
+        let recently_inserted = track_insertions(ui.ctx(), highlight_id, notes);
+
+        let open_selected = self.keyboard_focus
+            && !ui.ctx().wants_keyboard_input()
+            && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let scrolled_to_id = ui.id().with("scrolled_to_selection");
+        let scrolled_to: Option<usize> = ui.data(|d| d.get_temp(scrolled_to_id));
+
+        let list = self.list.clone().unwrap_or_else(|| self.tab.list.clone());
+        let list_resp = list
             .borrow_mut()
             .ui_custom_layout(ui, len, |ui, start_index| {
                 ui.spacing_mut().item_spacing.y = 0.0;
-                ui.spacing_mut().item_spacing.x = 4.0;
+                ui.spacing_mut().item_spacing.x = self.note_options.note_spacing();
+                let row_top = ui.cursor().min;
 
                 let ind = if self.reversed {
                     len - start_index - 1
@@ -277,42 +2104,544 @@ impl<'a> TimelineTabView<'a> {
                     start_index
                 };
 
-                let note_key = self.tab.notes[ind].key;
+                let note_key = notes[ind].key;
+
+                // painted behind the row once we know how tall it is
+                let highlight = recently_inserted
+                    .contains_key(&note_key)
+                    .then(|| ui.painter().add(egui::Shape::Noop));
 
-                let note = if let Ok(note) = self.ndb.get_note_by_key(self.txn, note_key) {
+                // notes that failed to load are only queried again once
+                // their backoff is up
+                let lookup = note_lookup(ui.ctx(), note_key);
+                let note = if lookup == NoteLookup::Due {
+                    self.ndb.get_note_by_key(self.txn, note_key).ok()
+                } else {
+                    None
+                };
+
+                let note = if let Some(note) = note {
+                    forget_note_retries(ui.ctx(), note_key);
                     note
                 } else {
-                    warn!("failed to query note {:?}", note_key);
-                    return 0;
+                    let gave_up = match lookup {
+                        NoteLookup::Due => {
+                            warn!("failed to query note {:?}", note_key);
+                            !retry_note_later(ui.ctx(), note_key)
+                        }
+                        NoteLookup::Waiting => false,
+                        NoteLookup::GaveUp => true,
+                    };
+                    let text = if gave_up {
+                        "Note unavailable"
+                    } else {
+                        "Loading note…"
+                    };
+
+                    // the row always takes up space, the virtual list
+                    // misbehaves around zero height items
+                    ui::padding(self.note_options.note_padding(), ui, |ui| {
+                        ui.add(
+                            egui::Label::new(egui::RichText::new(text).weak().italics())
+                                .selectable(false),
+                        );
+                    });
+                    ui::hline_with_width(ui, self.note_options.separator_width());
+                    return 1;
                 };
 
-                let muted = is_muted(
-                    &note,
-                    root_note_id_from_selected_id(self.ndb, self.note_cache, self.txn, note.id()),
-                );
+                // only the account's mutes get a placeholder the user can
+                // reveal. Snoozes, proof of work and content ratings stay
+                // hidden.
+                let state = self.mute_state(&note);
+                let revealable = state.muted && !state.hidden;
+                let revealed_mute = revealable
+                    && (self.revealed == Some(note_key)
+                        || self.revealed_notes.map_or(false, |r| r.contains(&note_key)));
+                let muted = state.is_hidden() && !revealed_mute;
+                let show_muted_marker = state.marker;
+                let muted_placeholder = revealable
+                    && !revealed_mute
+                    && self.revealed_notes.is_some()
+                    && self.note_options.has_show_muted_placeholder();
 
-                if !muted {
-                    ui::padding(8.0, ui, |ui| {
-                        let resp =
-                            ui::NoteView::new(self.ndb, self.note_cache, self.img_cache, &note)
-                                .note_options(self.note_options)
-                                .show(ui);
+                let deleted = !muted
+                    && self.note_options.has_show_deleted_placeholder()
+                    && is_note_deleted(self.ndb, self.note_cache, self.txn, note_key, &note);
 
-                        if let Some(note_action) = resp.action {
-                            action = Some(note_action)
-                        }
+                let too_deep = if muted || deleted {
+                    None
+                } else {
+                    self.replies_too_deep(note_key, &note)
+                };
 
-                        if let Some(context) = resp.context_selection {
-                            context.process(ui, &note);
+                if let Some(too_deep) = too_deep {
+                    ui::padding(self.note_options.note_padding(), ui, |ui| {
+                        let label = if too_deep == 1 {
+                            "… 1 deeper reply".to_owned()
+                        } else {
+                            format!("… {} deeper replies", too_deep)
+                        };
+                        let resp = ui
+                            .add(
+                                egui::Label::new(egui::RichText::new(label).weak())
+                                    .selectable(false)
+                                    .sense(egui::Sense::click()),
+                            )
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .on_hover_text("Open the thread");
+                        if resp.clicked() {
+                            response
+                                .actions
+                                .push(NoteAction::OpenThread(NoteId::new(*note.id())));
+                            self.clicked_row = Some(start_index);
                         }
                     });
 
-                    ui::hline(ui);
+                    ui::hline_with_width(ui, self.note_options.separator_width());
+                } else if deleted {
+                    ui::padding(self.note_options.note_padding(), ui, |ui| {
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new("This note was deleted by its author")
+                                    .weak()
+                                    .italics(),
+                            )
+                            .selectable(false),
+                        );
+                    });
+
+                    ui::hline_with_width(ui, self.note_options.separator_width());
+                } else if !muted {
+                    if let Some(note_action) = self.note_ui(ui, &note, note_key, show_muted_marker)
+                    {
+                        response.actions.push(note_action);
+                        self.clicked_row = Some(start_index);
+                    }
+
+                    ui::hline_with_width(ui, self.note_options.separator_width());
+                } else if muted_placeholder {
+                    let mut reveal = false;
+                    ui::padding(self.note_options.note_padding(), ui, |ui| {
+                        reveal = ui
+                            .add(
+                                egui::Label::new(
+                                    egui::RichText::new("🔇 Muted note · tap to show")
+                                        .small()
+                                        .weak(),
+                                )
+                                .selectable(false)
+                                .sense(egui::Sense::click()),
+                            )
+                            .on_hover_cursor(egui::CursorIcon::PointingHand)
+                            .clicked();
+                    });
+                    ui::hline_with_width(ui, self.note_options.separator_width());
+
+                    if reveal {
+                        self.revealed = Some(note_key);
+                    }
+                }
+
+                if self.thread_connectors && !muted && !deleted {
+                    let parent = self
+                        .note_cache
+                        .cached_note_or_insert(note_key, &note)
+                        .reply
+                        .borrow(note.tags())
+                        .reply()
+                        .map(|r| *r.id);
+
+                    rows.push(ConnectorRow {
+                        index: start_index,
+                        left: row_top.x,
+                        top: row_top.y,
+                        id: *note.id(),
+                        parent,
+                    });
+                }
+
+                let bottom = ui.cursor().min.y;
+                if !muted && !deleted && too_deep.is_none() {
+                    self.note_cache
+                        .cached_note_or_insert_mut(note_key, &note)
+                        .estimated_height = Some(bottom - row_top.y);
+                }
+
+                let visible = bottom > clip_top && row_top.y < clip_bottom;
+                let unseen = self
+                    .seen_notes
+                    .map_or(false, |seen| !seen.contains(&note_key))
+                    && !self.newly_seen.contains(&note_key);
+                if visible && unseen && !muted && !deleted && too_deep.is_none() {
+                    response.actions.push(NoteAction::Seen(note_key));
+                    self.newly_seen.push(note_key);
+                }
+
+                if top_note.is_none() && bottom > clip_top {
+                    top_note = Some((ind, bottom));
+                }
+                if bottom <= clip_top {
+                    self.scrolled_past.push(note_key);
+                }
+
+                if let Some(shape) = highlight {
+                    let fade = ui.ctx().animate_value_with_time(
+                        insert_highlight_id(highlight_id, note_key),
+                        0.0,
+                        INSERT_HIGHLIGHT_DURATION,
+                    );
+                    let rect = egui::Rect::from_min_max(
+                        row_top,
+                        egui::pos2(ui.max_rect().right(), bottom),
+                    );
+                    let color = ui.visuals().selection.bg_fill.gamma_multiply(fade);
+                    ui.painter()
+                        .set(shape, egui::Shape::rect_filled(rect, 0.0, color));
+                }
+
+                if tab.selection == Some(start_index) {
+                    let rect = egui::Rect::from_min_max(
+                        row_top,
+                        egui::pos2(ui.max_rect().right(), bottom),
+                    );
+                    ui.painter()
+                        .rect_stroke(rect.shrink(1.0), 2.0, ui.visuals().selection.stroke);
+
+                    // only follow the selection when it moves, so
+                    // scrolling away from it still works
+                    if scrolled_to != Some(start_index) {
+                        ui.scroll_to_rect(rect, None);
+                        ui.data_mut(|d| d.insert_temp(scrolled_to_id, start_index));
+                    }
+
+                    if open_selected && !muted {
+                        response
+                            .actions
+                            .push(NoteAction::OpenThread(NoteId::new(*note.id())));
+                    }
                 }
 
                 1
             });
 
-        action
+        // notes are only "above" us when we're reverse-chronological
+        let notes_above = if self.reversed {
+            0
+        } else {
+            list_resp.item_range.start
+        };
+        new_notes_pill(ui, notes_above, content_top);
+
+        if self.thread_connectors && !self.note_options.has_textmode() {
+            thread_connectors(ui, &rows, self.note_options);
+        }
+
+        if self.sticky_date_header {
+            if let Some((ind, bottom)) = top_note {
+                let created_at = notes[ind].created_at;
+                let scrolled = clip_top > content_top.y;
+
+                // fade out as the next day's notes reach the top
+                let next = if self.reversed {
+                    ind.checked_sub(1)
+                } else {
+                    Some(ind + 1).filter(|i| *i < len)
+                };
+                let near_boundary = next.map_or(false, |next| {
+                    notes[next].created_at / 86_400 != created_at / 86_400
+                }) && bottom - clip_top < 32.0;
+
+                sticky_date_header(ui, &date_label(created_at), scrolled && !near_boundary);
+            }
+        }
+
+        if let Some(note_key) = self.header_pinned_note {
+            let (height, action) = self.header_pinned_note_ui(ui, note_key);
+            ui.data_mut(|d| d.insert_temp(pinned_height_id, height));
+            response.actions.extend(action);
+        }
+
+        response
+    }
+
+    /// Draw the pinned note at the top of the visible area, over the
+    /// notes scrolled under it. Returns how tall it was.
+    fn header_pinned_note_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        note_key: NoteKey,
+    ) -> (f32, Option<NoteAction>) {
+        let note = if let Ok(note) = self.ndb.get_note_by_key(self.txn, note_key) {
+            note
+        } else {
+            warn!("pinned note {:?} missing from db", note_key);
+            return (0.0, None);
+        };
+
+        let rect = ui
+            .max_rect()
+            .with_min_y(ui.clip_rect().top())
+            .with_max_y(ui.clip_rect().bottom());
+        let mut card_ui = ui.new_child(
+            egui::UiBuilder::new()
+                .id_salt("header_pinned_note")
+                .max_rect(rect)
+                .layout(Layout::top_down(egui::Align::LEFT)),
+        );
+
+        let mut action = None;
+        let card = egui::Frame::none()
+            .fill(ui.visuals().extreme_bg_color)
+            .show(&mut card_ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui::padding(self.note_options.note_padding(), ui, |ui| {
+                    ui.add(
+                        egui::Label::new(egui::RichText::new("📌 Pinned").small().weak())
+                            .selectable(false),
+                    );
+                    action = ui::NoteView::new(self.ndb, self.note_cache, self.img_cache, &note)
+                        .note_options(self.note_options)
+                        .show(ui)
+                        .action;
+                });
+            });
+        ui::hline_with_width(&card_ui, self.note_options.separator_width());
+
+        (card.response.rect.height(), action)
+    }
+}
+
+/// The family that renders with the proportional fonts followed by the
+/// fonts of each of `fallbacks`. Fonts can only change between frames, so
+/// the first time a stack is asked for it is installed and None is
+/// returned until it is available.
+fn fallback_stack_family(
+    ctx: &egui::Context,
+    fallbacks: &[egui::FontFamily],
+) -> Option<egui::FontFamily> {
+    let name = fallbacks
+        .iter()
+        .map(|family| family.to_string())
+        .collect::<Vec<_>>()
+        .join("+");
+    let stack = egui::FontFamily::Name(format!("fallback:{name}").into());
+
+    if ctx.fonts(|fonts| fonts.families().contains(&stack)) {
+        return Some(stack);
+    }
+
+    let mut definitions = ctx.fonts(|fonts| fonts.lock().fonts.definitions().clone());
+    let mut fonts = definitions
+        .families
+        .get(&egui::FontFamily::Proportional)
+        .cloned()
+        .unwrap_or_default();
+    for family in fallbacks {
+        for font in definitions.families.get(family).into_iter().flatten() {
+            if !fonts.contains(font) {
+                fonts.push(font.clone());
+            }
+        }
+    }
+    definitions.families.insert(stack, fonts);
+    ctx.set_fonts(definitions);
+    ctx.request_repaint();
+
+    None
+}
+
+/// Draw a line between the avatars of neighboring rows when one replies
+/// to the other. Rows that weren't rendered next to each other this frame
+/// (virtualization gaps) are never connected.
+fn thread_connectors(ui: &mut egui::Ui, rows: &[ConnectorRow], note_options: NoteOptions) {
+    let pad = note_options.note_padding();
+    let pfp_size = note_options.pfp_size();
+    let stroke = egui::Stroke::new(2.0, ui.visuals().widgets.noninteractive.bg_stroke.color);
+    let painter = ui.painter();
+
+    for pair in rows.windows(2) {
+        let (upper, lower) = (&pair[0], &pair[1]);
+        if lower.index != upper.index + 1 {
+            continue;
+        }
+
+        let linked = lower.parent == Some(upper.id) || upper.parent == Some(lower.id);
+        if !linked {
+            continue;
+        }
+
+        let x = upper.left + pad + pfp_size / 2.0;
+        painter.line_segment(
+            [
+                egui::pos2(x, upper.top + pad + pfp_size + 4.0),
+                egui::pos2(x, lower.top + pad - 4.0),
+            ],
+            stroke,
+        );
+    }
+}
+
+/// The date of the notes we're currently looking at, pinned to the top
+/// left of the scroll area
+fn sticky_date_header(ui: &mut egui::Ui, label: &str, visible: bool) {
+    let id = ui.id().with("sticky_date_header");
+    let anim = ui.ctx().animate_bool(id, visible);
+    if anim == 0.0 {
+        return;
+    }
+
+    let font_id = NotedeckTextStyle::Small.get_font_id(ui.ctx());
+    let galley = ui.painter().layout_no_wrap(
+        label.to_owned(),
+        font_id,
+        ui.visuals().strong_text_color().gamma_multiply(anim),
+    );
+
+    let padding = egui::vec2(10.0, 4.0);
+    let size = galley.size() + padding * 2.0;
+    let visible_rect = ui.clip_rect();
+    let rect = egui::Rect::from_min_size(visible_rect.left_top() + egui::vec2(8.0, 8.0), size);
+
+    let painter = ui.painter();
+    painter.rect(
+        rect,
+        egui::Rounding::same(size.y / 2.0),
+        ui.visuals().widgets.inactive.bg_fill.gamma_multiply(anim),
+        egui::Stroke::NONE,
+    );
+    painter.galley(rect.min + padding, galley, egui::Color32::WHITE);
+}
+
+/// A floating "↑ N new notes" pill at the top of the scroll area. This
+/// shows up when we have scrolled down and there are newer notes above
+/// us. Tapping it scrolls back to the newest note.
+fn new_notes_pill(ui: &mut egui::Ui, count: usize, content_top: egui::Pos2) {
+    let id = ui.id().with("new_notes_pill");
+    let anim = ui.ctx().animate_bool(id, count > 0);
+    if anim == 0.0 {
+        return;
+    }
+
+    // remember the last count so we have something to show while we
+    // animate out
+    let count = if count > 0 {
+        ui.ctx().data_mut(|d| d.insert_temp(id, count));
+        count
+    } else {
+        ui.ctx().data(|d| d.get_temp(id)).unwrap_or_default()
+    };
+
+    let text = if count == 1 {
+        "↑ 1 new note".to_string()
+    } else {
+        format!("↑ {} new notes", count)
+    };
+
+    let font_id = NotedeckTextStyle::Small.get_font_id(ui.ctx());
+    let galley = ui
+        .painter()
+        .layout_no_wrap(text, font_id, ui.visuals().strong_text_color());
+
+    let padding = egui::vec2(12.0, 6.0);
+    let size = galley.size() + padding * 2.0;
+    let visible = ui.clip_rect();
+    let offset = egui::lerp(-size.y..=8.0, anim);
+    let rect = egui::Rect::from_min_size(
+        egui::pos2(visible.center().x - size.x / 2.0, visible.top() + offset),
+        size,
+    );
+
+    let resp = ui.interact(rect, id, egui::Sense::click());
+    if resp.hovered() {
+        ui::show_pointer(ui);
+    }
+
+    let fill = if resp.hovered() {
+        ui.visuals().widgets.hovered.bg_fill
+    } else {
+        ui.visuals().widgets.inactive.bg_fill
+    };
+
+    let painter = ui.painter();
+    painter.rect(
+        rect,
+        egui::Rounding::same(size.y / 2.0),
+        fill.gamma_multiply(anim),
+        ui.visuals().widgets.noninteractive.bg_stroke,
+    );
+    painter.galley(rect.min + padding, galley, egui::Color32::WHITE);
+
+    if resp.clicked() {
+        let top = egui::Rect::from_min_size(content_top, egui::vec2(1.0, 1.0));
+        ui.scroll_to_rect(top, Some(egui::Align::TOP));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_at(ctx: &egui::Context, time: f64, run: impl FnMut(&egui::Context)) {
+        let input = egui::RawInput {
+            time: Some(time),
+            ..Default::default()
+        };
+        let _ = ctx.run(input, run);
+    }
+
+    #[test]
+    fn test_note_lookup_gives_up() {
+        let ctx = egui::Context::default();
+        let key = NoteKey::new(1);
+        let mut time = 0.0;
+
+        for _ in 0..MAX_NOTE_RETRIES {
+            run_at(&ctx, time, |ctx| {
+                assert_eq!(note_lookup(ctx, key), NoteLookup::Due);
+                assert!(retry_note_later(ctx, key));
+                assert_eq!(note_lookup(ctx, key), NoteLookup::Waiting);
+            });
+            time += 10.0;
+        }
+
+        run_at(&ctx, time, |ctx| {
+            assert_eq!(note_lookup(ctx, key), NoteLookup::GaveUp);
+            forget_note_retries(ctx, key);
+            assert_eq!(note_lookup(ctx, key), NoteLookup::Due);
+        });
+    }
+
+    #[test]
+    fn test_swipe_changes_visible_tab() {
+        // mostly vertical or short drags are scrolling
+        assert_eq!(swipe_step(egui::vec2(-120.0, 80.0)), None);
+        assert_eq!(swipe_step(egui::vec2(-40.0, 0.0)), None);
+        assert_eq!(swipe_step(egui::vec2(-120.0, 20.0)), Some(1));
+        assert_eq!(swipe_step(egui::vec2(120.0, -20.0)), Some(-1));
+
+        let mut views = vec![
+            TimelineTab::new(ViewFilter::Notes),
+            TimelineTab::new(ViewFilter::NotesAndReplies),
+            TimelineTab::new(ViewFilter::Media),
+        ];
+        views[1].hidden = true;
+        assert_eq!(step_visible_view(&views, 0, 1), 2);
+        assert_eq!(step_visible_view(&views, 2, 1), 2);
+        assert_eq!(step_visible_view(&views, 0, -1), 0);
+    }
+
+    #[test]
+    fn test_tab_at() {
+        let tab_rects: Vec<egui::Rect> = (0..3)
+            .map(|i| {
+                let left = i as f32 * 100.0;
+                egui::Rect::from_x_y_ranges(left..=left + 100.0, 0.0..=32.0)
+            })
+            .collect();
+
+        assert_eq!(tab_at(&tab_rects, -20.0), 0);
+        assert_eq!(tab_at(&tab_rects, 150.0), 1);
+        assert_eq!(tab_at(&tab_rects, 400.0), 2);
     }
 }
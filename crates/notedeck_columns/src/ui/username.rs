@@ -1,3 +1,4 @@
+use crate::ui::note::DisplayNameFormat;
 use egui::{Color32, RichText, Widget};
 use nostrdb::ProfileRecord;
 use notedeck::fonts::NamedFontFamily;
@@ -7,6 +8,7 @@ pub struct Username<'a> {
     pk: &'a [u8; 32],
     pk_colored: bool,
     abbrev: usize,
+    format: DisplayNameFormat,
 }
 
 impl<'a> Username<'a> {
@@ -20,6 +22,11 @@ impl<'a> Username<'a> {
         self
     }
 
+    pub fn format(mut self, format: DisplayNameFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     pub fn new(profile: Option<&'a ProfileRecord>, pk: &'a [u8; 32]) -> Self {
         let pk_colored = false;
         let abbrev: usize = 1000;
@@ -28,6 +35,7 @@ impl<'a> Username<'a> {
             pk,
             pk_colored,
             abbrev,
+            format: DisplayNameFormat::default(),
         }
     }
 }
@@ -43,15 +51,16 @@ impl Widget for Username<'_> {
                 None
             };
 
-            if let Some(profile) = self.profile {
-                if let Some(prof) = profile.record().profile() {
-                    if prof.display_name().is_some() && prof.display_name().unwrap() != "" {
-                        ui_abbreviate_name(ui, prof.display_name().unwrap(), self.abbrev, color);
-                    } else if let Some(name) = prof.name() {
-                        ui_abbreviate_name(ui, name, self.abbrev, color);
-                    }
-                }
-            } else {
+            let prof = self.profile.and_then(|profile| profile.record().profile());
+            let name = self.format.format(
+                prof.and_then(|prof| prof.display_name()),
+                prof.and_then(|prof| prof.name()),
+                self.pk,
+            );
+
+            if let Some(name) = name {
+                ui_abbreviate_name(ui, &name, self.abbrev, color);
+            } else if self.profile.is_none() {
                 let mut txt = RichText::new("nostrich").family(NamedFontFamily::Medium.as_family());
                 if let Some(col) = color {
                     txt = txt.color(col)
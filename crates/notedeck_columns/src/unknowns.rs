@@ -61,9 +61,7 @@ pub fn get_unknown_ids(
 
     // This is mainly done to avoid the double mutable borrow that would happen
     // if we tried to update the note_cache mutably in the loop above
-    for (note_key, note) in new_cached_notes {
-        note_cache.cache_mut().insert(note_key, note);
-    }
+    note_cache.bulk_insert(new_cached_notes);
 
     Ok(())
 }
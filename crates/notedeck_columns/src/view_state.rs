@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
 use enostr::Pubkey;
+use url::Url;
 
 use crate::deck_state::DeckState;
 use crate::login_manager::AcquireKeyState;
 use crate::profile_state::ProfileState;
+use crate::ui::media_viewer::MediaViewerState;
 
 /// Various state for views
 #[derive(Default)]
@@ -14,6 +16,17 @@ pub struct ViewState {
     pub id_state_map: HashMap<egui::Id, AcquireKeyState>,
     pub id_string_map: HashMap<egui::Id, String>,
     pub pubkey_to_profile_state: HashMap<Pubkey, ProfileState>,
+
+    /// A relay mentioned in a note that the user wants to add to their
+    /// relay pool, waiting for them to confirm
+    pub pending_relay: Option<Url>,
+
+    /// Why the workspace the user tried to load couldn't be loaded,
+    /// shown until they dismiss it
+    pub workspace_error: Option<String>,
+
+    /// The images shown fullscreen over the deck, if any
+    pub media_viewer: Option<MediaViewerState>,
 }
 
 impl ViewState {